@@ -1,6 +1,7 @@
 use crate::keystroke::{Keystroke, KeystrokeSequence};
 use crossterm::event::KeyCode;
 use crossterm::event::KeyModifiers;
+use serde::Serialize;
 
 fn stringify_keystroke(keystroke: Keystroke) -> String {
     let mut result = String::new();
@@ -61,6 +62,14 @@ impl std::fmt::Display for KeystrokeSequence {
         write!(f, "{}", stringify_keystroke_sequence(self))
     }
 }
+impl Serialize for KeystrokeSequence {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
 impl std::fmt::Debug for KeystrokeSequence {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", stringify_keystroke_sequence(self))
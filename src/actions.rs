@@ -1,6 +1,12 @@
 use crate::canvas::raw::iter::CanvasIndexIteratorInfinite;
 use crate::canvas::raw::iter::CanvasIterationJump;
 use crate::canvas::raw::transform::mirror_cells;
+use crate::canvas::raw::transform::pair_wide_characters;
+use crate::canvas::raw::transform::rotate_cells;
+use crate::canvas::raw::transform::scale_cells;
+use crate::canvas::raw::transform::scale_up_factor_max;
+use crate::canvas::raw::transform::Scale;
+use crate::canvas::raw::WIDE_CHARACTER_CONTINUATION;
 use crate::canvas::CanvasModification;
 use crate::color_picker::target::ColorPickerTarget;
 use crate::color_picker::target::ColorPickerTargetEnum;
@@ -28,6 +34,7 @@ use crate::Axis;
 use crate::DirectionFree;
 use crate::Ground;
 use crate::ProgramState;
+use crate::RotationDirection;
 use enum_dispatch::enum_dispatch;
 use keystrokes_parsing::FromKeystrokes;
 use keystrokes_parsing::FromKeystrokesError;
@@ -39,7 +46,13 @@ use serde::Serialize;
 use std::fmt::Debug;
 
 pub mod change_focus;
+pub mod clipboard;
+pub mod frames;
+pub mod history;
+pub mod layers;
+pub mod metadata;
 pub mod mouse;
+pub mod page;
 pub mod session;
 
 #[enum_dispatch]
@@ -153,6 +166,8 @@ pub enum ActionEnum {
     Paste(Paste),
     SetYankActive(SetYankActive),
     MirrorYank(MirrorYank),
+    RotateYank(RotateYank),
+    ScaleYank(ScaleYank),
     MarkSet(MarkSet),
     MacroRecordingStartStop(MacroRecordingStartStop),
     Quit(session::Quit),
@@ -162,6 +177,20 @@ pub enum ActionEnum {
     SaveQuit(session::SaveQuit),
     ReloadConfig(ReloadConfig),
     ChangeFocus(change_focus::ChangeFocus),
+    AddLayer(layers::AddLayer),
+    DeleteLayer(layers::DeleteLayer),
+    ActivateLayer(layers::ActivateLayer),
+    MoveLayer(layers::MoveLayer),
+    ToggleLayerVisibility(layers::ToggleLayerVisibility),
+    ToggleLayerLock(layers::ToggleLayerLock),
+    AddFrame(frames::AddFrame),
+    DuplicateFrame(frames::DuplicateFrame),
+    DeleteFrame(frames::DeleteFrame),
+    StepFrame(frames::StepFrame),
+    PlayFrames(frames::PlayFrames),
+    ToggleOnionSkin(frames::ToggleOnionSkin),
+    BrowseHistory(history::BrowseHistory),
+    ToggleChangedCells(history::ToggleChangedCells),
 }
 
 #[enum_dispatch(Action)]
@@ -171,6 +200,8 @@ pub enum ActionRepeatableEnum {
     #[presetable(default)]
     Undo(Undo),
     Redo(Redo),
+    EarlierRevision(history::EarlierRevision),
+    LaterRevision(history::LaterRevision),
     MacroExecute(MacroExecute),
 }
 #[derive(Clone, Debug, PartialEq, Presetable)]
@@ -346,7 +377,13 @@ impl Action for MoveCursor {
             }
             .execute(program_state);
         }
-        let cells = self.motion.cells(program_state);
+        let cells = match self.motion.try_cells(program_state) {
+            Ok(cells) => cells,
+            Err(err) => {
+                program_state.new_messages.push_back(err.to_string());
+                return;
+            }
+        };
         // if let MotionRepeat {
         //     motion: MotionEnum::FindChar(ref find_char),
         // ..
@@ -360,12 +397,19 @@ impl Action for MoveCursor {
         let Some(cursor_to) = cells.last() else {
             return;
         };
-        program_state.cursor_position = *cursor_to;
+        let cursor_from = program_state.cursor_position;
+        let rightwards = cursor_to.0 == cursor_from.0 && cursor_to.1 > cursor_from.1;
+        let cursor_to = program_state.canvas.clamp_to_page(*cursor_to);
+        let cursor_to = program_state
+            .canvas
+            .raw()
+            .skip_continuation(cursor_to, rightwards);
+        program_state.cursor_position = cursor_to;
         if let Some(it) = &mut program_state.cursor_position_iterator {
-            it.tracer.index = *cursor_to;
+            it.tracer.index = cursor_to;
         }
         if let Some((index_a, _)) = &mut program_state.visual_rect {
-            *index_a = *cursor_to;
+            *index_a = cursor_to;
         }
         let (rows_away, columns_away) = program_state
             .canvas_visible
@@ -395,7 +439,15 @@ pub struct Operation {
 }
 impl Action for Operation {
     fn execute(&self, program_state: &mut ProgramState) {
-        let cells = self.motion.cells(program_state);
+        let mut cells = match self.motion.try_cells(program_state) {
+            Ok(cells) => cells,
+            Err(err) => {
+                program_state.new_messages.push_back(err.to_string());
+                return;
+            }
+        };
+        // Operators don't reach outside the page
+        cells.retain(|index| program_state.canvas.in_page(*index));
         if self.clear_visual_rect {
             program_state.visual_rect = None;
         }
@@ -450,6 +502,8 @@ impl Action for SetCursorPositionIterator {
             self.jump,
         );
         program_state.cursor_position_iterator = Some(it);
+        // Characters typed from now on don't combine with earlier ones
+        program_state.cursor_position_previous = None;
     }
 }
 
@@ -460,14 +514,40 @@ pub struct InsertCharMoveCursor {
 }
 impl Action for InsertCharMoveCursor {
     fn execute(&self, program_state: &mut ProgramState) {
+        // Combining characters, like accents typed after the letter, are added to the grapheme
+        // cluster of the previous character instead of taking up a cell
+        if let Some(previous) = program_state.cursor_position_previous {
+            if program_state
+                .canvas
+                .raw()
+                .continues_grapheme(previous, self.ch)
+            {
+                program_state
+                    .canvas
+                    .stage(CanvasModification::AddCombiningCharacter(previous, self.ch));
+                return;
+            }
+        }
         program_state.canvas.stage(CanvasModification::SetCharacter(
             program_state.cursor_position,
             self.ch,
         ));
-        // I think this is obsolete
+        // Combining characters typed next are added to this cell
         program_state.cursor_position_previous = Some(program_state.cursor_position);
         if let Some(it) = &mut program_state.cursor_position_iterator {
             program_state.cursor_position = it.go_forward();
+            // Step over the continuation cell of a wide character
+            if program_state
+                .canvas
+                .raw()
+                .character(program_state.cursor_position)
+                == WIDE_CHARACTER_CONTINUATION
+            {
+                program_state.cursor_position = it.go_forward();
+            }
+            program_state.cursor_position = program_state
+                .canvas
+                .clamp_to_page(program_state.cursor_position);
         }
         let away = program_state
             .canvas_visible
@@ -484,10 +564,22 @@ pub struct MoveCursorBackInsertChar {
 }
 impl Action for MoveCursorBackInsertChar {
     fn execute(&self, program_state: &mut ProgramState) {
-        // I think this is obsolete
-        program_state.cursor_position_previous = Some(program_state.cursor_position);
+        // Combining characters typed after erasing don't belong to any character
+        program_state.cursor_position_previous = None;
         if let Some(it) = &mut program_state.cursor_position_iterator {
             program_state.cursor_position = it.go_backward();
+            // Step back onto the wide character instead of its continuation cell
+            if program_state
+                .canvas
+                .raw()
+                .character(program_state.cursor_position)
+                == WIDE_CHARACTER_CONTINUATION
+            {
+                program_state.cursor_position = it.go_backward();
+            }
+            program_state.cursor_position = program_state
+                .canvas
+                .clamp_to_page(program_state.cursor_position);
         }
         let away = program_state
             .canvas_visible
@@ -658,6 +750,56 @@ impl Action for MirrorYank {
         }
     }
 }
+/// Rotates the yank a quarter turn around its origo, replacing characters by their rotated
+/// counterparts from `character_rotations` in the config.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct RotateYank {
+    pub slot: YankSlotSpecification,
+    pub direction: RotationDirection,
+}
+impl Action for RotateYank {
+    fn execute(&self, program_state: &mut ProgramState) {
+        if let Some(yank) = program_state
+            .yanks
+            .get_mut(&self.slot.as_char(&program_state))
+        {
+            let swaps = program_state
+                .config
+                .character_rotations
+                .swaps(self.direction);
+            rotate_cells(&mut yank.cells, (0, 0), self.direction, swaps);
+            pair_wide_characters(&mut yank.cells);
+        }
+    }
+}
+/// Scales the yank up or down by a whole number around its origo.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct ScaleYank {
+    pub slot: YankSlotSpecification,
+    pub scale: Scale,
+}
+impl FallibleAction for ScaleYank {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        if let Scale::Up(0) | Scale::Down(0) = self.scale {
+            anyhow::bail!("Can't scale by 0");
+        }
+        if let Some(yank) = program_state
+            .yanks
+            .get_mut(&self.slot.as_char(&program_state))
+        {
+            if let Scale::Up(factor) = self.scale {
+                let factor_max = scale_up_factor_max(&yank.cells);
+                if factor > factor_max {
+                    anyhow::bail!("Can't scale this yank up by more than {factor_max}");
+                }
+            }
+            scale_cells(&mut yank.cells, self.scale);
+        }
+        Ok(())
+    }
+}
 #[derive(Clone, Debug, PartialEq, Presetable)]
 #[presetable(config_type = "ProgramState")]
 pub struct MarkSet {
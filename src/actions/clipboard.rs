@@ -0,0 +1,24 @@
+use crate::actions::{Action, Paste};
+use crate::clipboard::{yank_from_text, CLIPBOARD_YANK_SLOT};
+use crate::yank_slots::YankSlotSpecification;
+use crate::ProgramState;
+
+use super::{ExecuteActionResult, FallibleAction};
+
+/// Reads text pasted into the terminal into the clipboard yank slot, and pastes it at the cursor.
+/// ANSI escape sequences in the text are interpreted.
+#[derive(Clone, Debug)]
+pub struct PasteText {
+    pub text: String,
+}
+impl FallibleAction for PasteText {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let yank = yank_from_text(&self.text)?;
+        program_state.yanks.insert(CLIPBOARD_YANK_SLOT, yank);
+        Paste {
+            slot: YankSlotSpecification::Specific(CLIPBOARD_YANK_SLOT),
+        }
+        .execute(program_state);
+        Ok(())
+    }
+}
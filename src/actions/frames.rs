@@ -0,0 +1,144 @@
+use std::time::Instant;
+
+use keystrokes_parsing::Presetable;
+
+use crate::ProgramState;
+
+use super::{ExecuteActionResult, FallibleAction};
+
+/// Adds an empty frame after the active frame, with the same layers, and activates it.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct AddFrame {}
+impl FallibleAction for AddFrame {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        program_state.canvas.add_frame();
+        Ok(())
+    }
+}
+
+/// Adds a copy of the active frame after it and activates the copy.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct DuplicateFrame {}
+impl FallibleAction for DuplicateFrame {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        program_state.canvas.duplicate_frame();
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct DeleteFrame {}
+impl FallibleAction for DeleteFrame {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        Ok(program_state.canvas.delete_frame()?)
+    }
+}
+
+/// Activates the frame `offset` frames after the active frame, or before it if negative. Steps
+/// past the last frame continue from the first one, like the playback does.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct StepFrame {
+    pub offset: i16,
+}
+impl FallibleAction for StepFrame {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let frames = program_state.canvas.frames().len() as i64;
+        let index =
+            (program_state.canvas.active_frame() as i64 + self.offset as i64).rem_euclid(frames);
+        Ok(program_state.canvas.set_active_frame(index as usize)?)
+    }
+}
+
+/// Starts playing the frames in a loop, until any key is pressed.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct PlayFrames {}
+impl FallibleAction for PlayFrames {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        program_state.canvas.commit_staged();
+        program_state.playback = Some(Instant::now());
+        Ok(())
+    }
+}
+
+/// Toggles showing the previous frame dimmed below the active frame.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct ToggleOnionSkin {}
+impl FallibleAction for ToggleOnionSkin {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        program_state.onion_skin = !program_state.onion_skin;
+        Ok(())
+    }
+}
+
+/// Runs a `:frame` command, which manages the frames of an animation by a subcommand:
+///
+/// - `:frame` lists the frames with their durations, marking the active one with `*`
+/// - `:frame <number>` activates a frame, counting from 1
+/// - `:frame new` adds an empty frame after the active frame
+/// - `:frame duplicate` adds a copy of the active frame after it
+/// - `:frame delete` deletes the active frame
+/// - `:frame duration <milliseconds>` sets how long the active frame is shown
+/// - `:frame play` plays the frames in a loop
+/// - `:frame onion` toggles the onion skin
+#[derive(Clone, Debug)]
+pub struct FrameCommand {
+    pub arguments: String,
+}
+impl FallibleAction for FrameCommand {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let (subcommand, argument) = self
+            .arguments
+            .split_once(char::is_whitespace)
+            .map(|(subcommand, argument)| (subcommand, argument.trim()))
+            .unwrap_or((self.arguments.as_str(), ""));
+        match subcommand {
+            "" => {
+                let canvas = &program_state.canvas;
+                let list = canvas
+                    .frames()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, frame)| {
+                        format!(
+                            "{}{} {} ms",
+                            if index == canvas.active_frame() {
+                                "*"
+                            } else {
+                                " "
+                            },
+                            index + 1,
+                            frame.duration
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                program_state.new_messages.push_back(list);
+            }
+            "new" => AddFrame {}.try_execute(program_state)?,
+            "duplicate" => DuplicateFrame {}.try_execute(program_state)?,
+            "delete" => DeleteFrame {}.try_execute(program_state)?,
+            "duration" => {
+                let duration = argument
+                    .trim_end_matches("ms")
+                    .parse::<u32>()
+                    .map_err(|_| {
+                        anyhow::anyhow!("Invalid duration {argument:?}, expected milliseconds")
+                    })?;
+                program_state.canvas.set_frame_duration(duration)?;
+            }
+            "play" => PlayFrames {}.try_execute(program_state)?,
+            "onion" => ToggleOnionSkin {}.try_execute(program_state)?,
+            number => match number.parse::<usize>() {
+                Ok(number) if number > 0 => program_state.canvas.set_active_frame(number - 1)?,
+                _ => anyhow::bail!("Unknown frame command {number:?}"),
+            },
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+use std::time::{Duration, SystemTime};
+
+use keystrokes_parsing::Presetable;
+use serde::Deserialize;
+
+use crate::history_browser::HistoryBrowser;
+use crate::input_mode::InputMode;
+use crate::ProgramState;
+
+use super::{Action, ExecuteActionResult, FallibleAction};
+
+/// Moves to the revision made before the current one, like `g-` in vim. Unlike undo, this can
+/// move to other branches of the undo tree.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct EarlierRevision {}
+impl Action for EarlierRevision {
+    fn execute(&self, program_state: &mut ProgramState) {
+        program_state.canvas.go_to_earlier_revision();
+    }
+}
+
+/// Moves to the revision made after the current one, like `g+` in vim.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct LaterRevision {}
+impl Action for LaterRevision {
+    fn execute(&self, program_state: &mut ProgramState) {
+        program_state.canvas.go_to_later_revision();
+    }
+}
+
+/// Opens the history browser, which lists every revision and previews the selected one.
+/// Confirming with enter moves the canvas to the selected revision.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct BrowseHistory {}
+impl FallibleAction for BrowseHistory {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        program_state.canvas.commit_staged();
+        if program_state.canvas.commits().next().is_none() {
+            anyhow::bail!("Nothing has been changed yet");
+        }
+        program_state.history_browser = Some(HistoryBrowser::new(&program_state.canvas));
+        Ok(())
+    }
+}
+
+/// Toggles highlighting the cells which look different than when the artwork was last saved.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct ToggleChangedCells {}
+impl FallibleAction for ToggleChangedCells {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        program_state.changed_cells_highlighted = !program_state.changed_cells_highlighted;
+        Ok(())
+    }
+}
+
+/// Runs a `:checkpoint` command, which saves the current revision under the given name, to
+/// return to it with `:restore`. Without a name, the checkpoints are listed.
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    pub name: String,
+}
+impl FallibleAction for Checkpoint {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let name = self.name.trim();
+        if !name.is_empty() {
+            program_state.canvas.set_checkpoint(name.to_string());
+            return Ok(());
+        }
+        let checkpoints = program_state.canvas.checkpoints();
+        if checkpoints.is_empty() {
+            anyhow::bail!("There are no checkpoints, create one with :checkpoint <name>");
+        }
+        let mut lines = vec!["revision  name".to_string()];
+        for (name, revision) in checkpoints {
+            lines.push(format!("{revision:>8}  {name}"));
+        }
+        program_state.new_messages.push_back(lines.join("\n"));
+        Ok(())
+    }
+}
+
+/// Runs a `:restore` command, which moves to the revision saved by `:checkpoint` under the given
+/// name. Like moving between revisions in any other way, no commits are lost.
+#[derive(Clone, Debug)]
+pub struct RestoreCheckpoint {
+    pub name: String,
+}
+impl FallibleAction for RestoreCheckpoint {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let name = self.name.trim();
+        let Some(revision) = program_state.canvas.checkpoints().get(name).copied() else {
+            anyhow::bail!("There is no checkpoint named {name:?}");
+        };
+        program_state.canvas.commit_staged();
+        program_state.canvas.go_to_revision(revision);
+        program_state.cursor_position = program_state
+            .canvas
+            .clamp_to_page(program_state.cursor_position);
+        Ok(())
+    }
+}
+
+/// How consecutive commits are merged into one, so that they are undone at once.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub enum CommitMerging {
+    /// Every commit is undone separately.
+    #[default]
+    Off,
+    /// Commits made within this many milliseconds of the previous commit are merged into it.
+    Within(u64),
+    /// Commits made from entering one of these input modes until leaving them are merged, like
+    /// the ones of an insert mode session.
+    Session(Vec<InputMode>),
+}
+
+/// Merges the commits made while handling an input as set in the config. `latest_revision` is
+/// the latest revision of the canvas before handling the input.
+///
+/// The last saved revision is never merged into a later one, so that the saved canvas stays in
+/// the history.
+pub fn merge_commits(program_state: &mut ProgramState, latest_revision: u64) {
+    let canvas = &mut program_state.canvas;
+    let saved = program_state.last_saved_revision;
+    match &program_state.config.commit_merging {
+        CommitMerging::Off => (),
+        CommitMerging::Within(milliseconds) => {
+            let within = Duration::from_millis(*milliseconds);
+            while let Some(commit) = canvas.commit(canvas.get_current_revision()) {
+                let Some(parent) = canvas.commit(commit.parent()) else {
+                    break;
+                };
+                let made_within = commit
+                    .time()
+                    .duration_since(parent.time())
+                    .is_ok_and(|elapsed| elapsed <= within);
+                if commit.revision() <= latest_revision
+                    || commit.parent() == saved
+                    || !made_within
+                    || !canvas.merge_with_parent()
+                {
+                    break;
+                }
+            }
+        }
+        CommitMerging::Session(modes) => {
+            let in_session = modes.contains(&program_state.input_mode);
+            match (program_state.commit_merging_session, in_session) {
+                (None, true) => {
+                    program_state.commit_merging_session = Some(canvas.get_current_revision());
+                }
+                (Some(start), false) => {
+                    let ancestors = canvas.ancestors(canvas.get_current_revision());
+                    let position = |revision| ancestors.iter().position(|r| *r == revision);
+                    let start = match (position(saved), position(start)) {
+                        (Some(saved_position), Some(start_position))
+                            if saved_position < start_position =>
+                        {
+                            saved
+                        }
+                        _ => start,
+                    };
+                    canvas.merge_commits_since(start);
+                    program_state.commit_merging_session = None;
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// How long ago `time` was, like "5 minutes ago".
+pub fn format_age(time: SystemTime) -> String {
+    let seconds = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or(Duration::ZERO)
+        .as_secs();
+    let (amount, unit) = match seconds {
+        0..100 => (seconds, "second"),
+        100..6000 => (seconds / 60, "minute"),
+        6000..172800 => (seconds / 3600, "hour"),
+        _ => (seconds / 86400, "day"),
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+    format!("{amount} {unit}{plural} ago")
+}
+
+/// Runs an `:undolist` command, which lists the branches of the undo tree by their last
+/// revision, with the number of changes from the initial canvas. The branch containing the
+/// current revision is marked with `*`.
+#[derive(Clone, Debug)]
+pub struct UndoList {}
+impl FallibleAction for UndoList {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let canvas = &program_state.canvas;
+        let current = canvas.get_current_revision();
+        let mut lines = vec![format!("At revision {current}")];
+        lines.push("  revision  changes  when".to_string());
+        for commit in canvas.commits() {
+            if canvas.children(commit.revision()).next().is_some() {
+                continue;
+            }
+            let ancestors = canvas.ancestors(commit.revision());
+            lines.push(format!(
+                "{} {:>8} {:>8}  {}",
+                if ancestors.contains(&current) {
+                    "*"
+                } else {
+                    " "
+                },
+                commit.revision(),
+                ancestors.len() - 1,
+                format_age(commit.time()),
+            ));
+        }
+        if lines.len() == 2 {
+            anyhow::bail!("Nothing has been changed yet");
+        }
+        program_state.new_messages.push_back(lines.join("\n"));
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use keystrokes_parsing::Presetable;
+
+use crate::canvas::layers::LayerProperties;
+use crate::canvas::raw::composite::OPACITY_OPAQUE;
+use crate::ProgramState;
+
+use super::{ExecuteActionResult, FallibleAction};
+
+/// Index of the layer `offset` layers above the active layer, where negative offsets go below.
+fn layer_offset(program_state: &ProgramState, offset: i16) -> anyhow::Result<usize> {
+    let index = program_state.canvas.active_layer() as i64 + offset as i64;
+    if index < 0 {
+        anyhow::bail!("There is no layer below");
+    }
+    if index >= program_state.canvas.layers().len() as i64 {
+        anyhow::bail!("There is no layer above");
+    }
+    Ok(index as usize)
+}
+
+fn set_layer_properties(
+    program_state: &mut ProgramState,
+    change: impl FnOnce(&mut LayerProperties),
+) {
+    let mut properties = program_state.canvas.active().properties.clone();
+    change(&mut properties);
+    program_state.canvas.set_layer_properties(properties);
+}
+
+/// Adds an empty layer above the active layer and activates it.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct AddLayer {}
+impl FallibleAction for AddLayer {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        program_state.canvas.add_layer(None);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct DeleteLayer {}
+impl FallibleAction for DeleteLayer {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        Ok(program_state.canvas.delete_layer()?)
+    }
+}
+
+/// Activates the layer `offset` layers above the active layer, or below it if negative.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct ActivateLayer {
+    pub offset: i16,
+}
+impl FallibleAction for ActivateLayer {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let index = layer_offset(program_state, self.offset)?;
+        Ok(program_state.canvas.set_active_layer(index)?)
+    }
+}
+
+/// Moves the active layer `offset` layers up, or down if negative.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct MoveLayer {
+    pub offset: i16,
+}
+impl FallibleAction for MoveLayer {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let index = layer_offset(program_state, self.offset)?;
+        Ok(program_state.canvas.move_layer(index)?)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct ToggleLayerVisibility {}
+impl FallibleAction for ToggleLayerVisibility {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        set_layer_properties(program_state, |properties| {
+            properties.visible = !properties.visible
+        });
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct ToggleLayerLock {}
+impl FallibleAction for ToggleLayerLock {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        set_layer_properties(program_state, |properties| {
+            properties.locked = !properties.locked
+        });
+        Ok(())
+    }
+}
+
+/// Runs a `:layer` command, which manages the layers by a subcommand:
+///
+/// - `:layer` lists the layers, marking the active one with `*`
+/// - `:layer <number>` activates a layer, counting from 1 at the bottom
+/// - `:layer new [name]` adds a layer above the active layer
+/// - `:layer delete` deletes the active layer
+/// - `:layer rename <name>`
+/// - `:layer up` and `:layer down` move the active layer
+/// - `:layer show`, `:layer hide`, `:layer lock` and `:layer unlock`
+/// - `:layer opacity <percent>`
+#[derive(Clone, Debug)]
+pub struct LayerCommand {
+    pub arguments: String,
+}
+impl FallibleAction for LayerCommand {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let (subcommand, argument) = self
+            .arguments
+            .split_once(char::is_whitespace)
+            .map(|(subcommand, argument)| (subcommand, argument.trim()))
+            .unwrap_or((self.arguments.as_str(), ""));
+        match subcommand {
+            "" => {
+                let canvas = &program_state.canvas;
+                let list = canvas
+                    .layers()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, layer)| {
+                        let properties = &layer.properties;
+                        let mut line = format!(
+                            "{}{} {}",
+                            if index == canvas.active_layer() {
+                                "*"
+                            } else {
+                                " "
+                            },
+                            index + 1,
+                            properties.name
+                        );
+                        if !properties.visible {
+                            line.push_str(" [hidden]");
+                        }
+                        if properties.locked {
+                            line.push_str(" [locked]");
+                        }
+                        if properties.opacity != OPACITY_OPAQUE {
+                            line.push_str(&format!(" [{}%]", properties.opacity));
+                        }
+                        line
+                    })
+                    .rev()
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                program_state.new_messages.push_back(list);
+            }
+            "new" => program_state
+                .canvas
+                .add_layer((!argument.is_empty()).then(|| argument.to_string())),
+            "delete" => DeleteLayer {}.try_execute(program_state)?,
+            "rename" if argument.is_empty() => anyhow::bail!("Missing layer name"),
+            "rename" => set_layer_properties(program_state, |properties| {
+                properties.name = argument.to_string()
+            }),
+            "up" => MoveLayer { offset: 1 }.try_execute(program_state)?,
+            "down" => MoveLayer { offset: -1 }.try_execute(program_state)?,
+            "show" | "hide" => set_layer_properties(program_state, |properties| {
+                properties.visible = subcommand == "show"
+            }),
+            "lock" | "unlock" => set_layer_properties(program_state, |properties| {
+                properties.locked = subcommand == "lock"
+            }),
+            "opacity" => {
+                let opacity = argument
+                    .trim_end_matches('%')
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|opacity| *opacity <= OPACITY_OPAQUE)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Invalid opacity {argument:?}, expected 0 to 100")
+                    })?;
+                set_layer_properties(program_state, |properties| properties.opacity = opacity);
+            }
+            number => match number.parse::<usize>() {
+                Ok(number) if number > 0 => program_state.canvas.set_active_layer(number - 1)?,
+                _ => anyhow::bail!("Unknown layer command {number:?}"),
+            },
+        }
+        Ok(())
+    }
+}
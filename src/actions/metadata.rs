@@ -0,0 +1,63 @@
+use crate::canvas::raw::CanvasMetadata;
+use crate::ProgramState;
+
+use super::{ExecuteActionResult, FallibleAction};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MetadataField {
+    Title,
+    Author,
+    Group,
+    Font,
+}
+
+impl MetadataField {
+    fn get_mut(self, metadata: &mut CanvasMetadata) -> &mut String {
+        match self {
+            Self::Title => &mut metadata.title,
+            Self::Author => &mut metadata.author,
+            Self::Group => &mut metadata.group,
+            Self::Font => &mut metadata.font,
+        }
+    }
+}
+
+/// Commits `metadata` as the new metadata of the canvas, so that the change can be undone.
+fn commit_metadata(program_state: &mut ProgramState, metadata: CanvasMetadata) {
+    if metadata != *program_state.canvas.metadata() {
+        program_state.canvas.set_metadata(metadata);
+    }
+}
+
+/// Sets a metadata field of the canvas. An empty value clears the field.
+#[derive(Clone, Debug)]
+pub struct SetMetadataField {
+    pub field: MetadataField,
+    pub value: String,
+}
+impl FallibleAction for SetMetadataField {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let mut metadata = program_state.canvas.metadata().clone();
+        *self.field.get_mut(&mut metadata) = self.value.clone();
+        commit_metadata(program_state, metadata);
+        Ok(())
+    }
+}
+
+/// Adds a comment line to the canvas metadata. An empty comment removes all comment lines.
+#[derive(Clone, Debug)]
+pub struct AddMetadataComment {
+    pub comment: String,
+}
+impl FallibleAction for AddMetadataComment {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let mut metadata = program_state.canvas.metadata().clone();
+        if self.comment.is_empty() {
+            metadata.comments.clear();
+        } else {
+            metadata.comments.push(self.comment.clone());
+        }
+        commit_metadata(program_state, metadata);
+        Ok(())
+    }
+}
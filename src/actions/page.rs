@@ -0,0 +1,93 @@
+use crate::canvas::rect::CanvasRect;
+use crate::ProgramState;
+
+use super::{ExecuteActionResult, FallibleAction};
+
+fn set_page(program_state: &mut ProgramState, page: Option<CanvasRect>) {
+    program_state.canvas.set_page(page);
+    program_state.cursor_position = program_state
+        .canvas
+        .clamp_to_page(program_state.cursor_position);
+}
+
+/// Parses a page size given as `<columns>x<rows>` or `<columns> <rows>`.
+fn parse_size(arguments: &str) -> anyhow::Result<(u16, u16)> {
+    let invalid = || anyhow::anyhow!("Invalid size {arguments:?}, expected <columns>x<rows>");
+    let (columns, rows) = arguments.split_once(['x', ' ']).ok_or_else(invalid)?;
+    let columns = columns.trim().parse::<u16>().map_err(|_| invalid())?;
+    let rows = rows.trim().parse::<u16>().map_err(|_| invalid())?;
+    if columns == 0 || rows == 0 {
+        anyhow::bail!("The page must be at least 1x1");
+    }
+    Ok((columns, rows))
+}
+
+/// Runs a `:resize` command, which sets the fixed size of the page. The top left corner of the
+/// page stays where it is, or starts at the top left corner of the canvas if there is no page.
+///
+/// `:resize` without a size, or `:resize off`, lets the canvas grow freely again. Cells outside
+/// the page are kept, but not exported.
+#[derive(Clone, Debug)]
+pub struct Resize {
+    pub arguments: String,
+}
+impl FallibleAction for Resize {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let arguments = self.arguments.trim();
+        if arguments.is_empty() || arguments == "off" {
+            set_page(program_state, None);
+            return Ok(());
+        }
+        let (columns, rows) = parse_size(arguments)?;
+        let (row, column) = match program_state.canvas.page() {
+            Some(page) => (page.row, page.column),
+            None => {
+                let area = program_state.canvas.content_area();
+                if area.rows == 0 {
+                    (0, 0)
+                } else {
+                    (area.row, area.column)
+                }
+            }
+        };
+        set_page(
+            program_state,
+            Some(CanvasRect {
+                row,
+                column,
+                rows,
+                columns,
+            }),
+        );
+        Ok(())
+    }
+}
+
+/// Runs a `:crop` command, which sets the page to the visual rect.
+#[derive(Clone, Debug)]
+pub struct Crop {}
+impl FallibleAction for Crop {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let Some(corners) = program_state.visual_rect else {
+            anyhow::bail!("Select the area to crop to in visual rect mode first");
+        };
+        set_page(program_state, Some(CanvasRect::from_corners(corners)));
+        program_state.visual_rect = None;
+        Ok(())
+    }
+}
+
+/// Runs a `:trim` command, which sets the page to the smallest area containing every cell that
+/// isn't empty, in any layer of any frame.
+#[derive(Clone, Debug)]
+pub struct Trim {}
+impl FallibleAction for Trim {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let area = program_state.canvas.content_area();
+        if area.rows == 0 || area.columns == 0 {
+            anyhow::bail!("The canvas is empty, there is nothing to trim to");
+        }
+        set_page(program_state, Some(area));
+        Ok(())
+    }
+}
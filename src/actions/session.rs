@@ -1,3 +1,13 @@
+use crate::canvas::raw::image_import::ImageImportOptions;
+use crate::canvas::raw::quantize::{ColorDepth, Dithering};
+use crate::canvas::raw::yank::CanvasYank;
+use crate::canvas::raw::Canvas;
+use crate::canvas::CanvasModification;
+use crate::config::Config;
+use crate::file_formats::image::Image;
+use crate::file_formats::sauce::{self, Sauce};
+use crate::file_formats::undo::UndoFile;
+use crate::file_formats::upaint::UpaintFile;
 use crate::{file_formats::FileFormat, ProgramState};
 use keystrokes_parsing::Presetable;
 use serde::Deserialize;
@@ -5,6 +15,118 @@ use serde::Serialize;
 
 use super::{Action, ExecuteActionResult, FallibleAction};
 
+/// Options changing the exported canvas, which don't apply to `.upaint` files.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ExportOptions {
+    pub color_depth: ColorDepth,
+    pub dithering: Dithering,
+}
+
+impl ExportOptions {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            color_depth: config.export_color_depth,
+            dithering: config.export_dithering,
+        }
+    }
+}
+
+/// Exports the canvas to `file_name` in the format given by its extension.
+///
+/// `.upaint` files also store the workspace state of `program_state`. `.html`, `.svg` and `.png`
+/// files use its color theme, and `.irc` files the IRC palette of its config. ANSI files get all
+/// frames of an animation, and a SAUCE record if the canvas has metadata. As they are UTF-8
+/// rather than CP437, the record only names the font if the file is plain ASCII. A fixed page is
+/// exported completely, even where its cells are empty.
+///
+/// If reducing the color depth changes any cells, saving fails unless `lossy` is set, in which
+/// case the number of changed cells is reported.
+fn save_to_file(
+    program_state: &mut ProgramState,
+    file_name: &str,
+    lossy: bool,
+    options: ExportOptions,
+) -> ExecuteActionResult {
+    let format = FileFormat::try_from(file_name)?;
+    // Staged modifications are saved, so they must be part of the saved revision
+    program_state.canvas.commit_staged();
+    program_state.canvas.clean();
+    let color_theme = &program_state.config.color_theme().canvas;
+    // Animations are only exported to ANSI files, other formats get the active frame
+    let animation = format == FileFormat::Ansi && program_state.canvas.frames().len() > 1;
+    let mut frames = if animation {
+        program_state.canvas.flattened_frames()
+    } else {
+        vec![program_state.canvas.flattened()]
+    };
+    if let Some(page) = program_state.canvas.page() {
+        if format != FileFormat::Upaint {
+            for frame in &mut frames {
+                frame.to_mut().fit_to(page);
+            }
+        }
+    }
+    let mut quantized_cells = 0;
+    // Project files keep every color, and text files have none
+    let has_reduced_colors = !matches!(format, FileFormat::Upaint | FileFormat::Txt);
+    if has_reduced_colors && options.color_depth != ColorDepth::TrueColor {
+        quantized_cells = frames
+            .iter_mut()
+            .map(|frame| {
+                frame
+                    .to_mut()
+                    .quantize(options.color_depth, options.dithering)
+            })
+            .sum();
+        if quantized_cells > 0 && !lossy {
+            anyhow::bail!(
+                "Reducing to {} changes {quantized_cells} cells. Use :w! to save anyways.",
+                options.color_depth
+            );
+        }
+    }
+    let canvas = &frames[0];
+    let mut output = match format {
+        FileFormat::Ansi if animation => Canvas::export_ansi_animation(&frames)?.into_bytes(),
+        FileFormat::Upaint => UpaintFile::from_program_state(program_state)
+            .to_ron()?
+            .into_bytes(),
+        FileFormat::Html => canvas.export_html(color_theme).into_bytes(),
+        FileFormat::Svg => canvas.export_svg(color_theme).into_bytes(),
+        FileFormat::Png => canvas.export_png(color_theme)?,
+        FileFormat::Irc => canvas
+            .export_irc(program_state.config.irc_palette, lossy)?
+            .into_bytes(),
+        format if lossy => canvas.export_lossy(format)?.into_bytes(),
+        format => canvas.export(format)?.into_bytes(),
+    };
+    let metadata = program_state.canvas.metadata();
+    if format == FileFormat::Ansi && !metadata.is_empty() {
+        let area = Canvas::animation_area(&frames);
+        let (rows, columns) = (area.rows, area.columns);
+        Sauce::ansi(metadata, columns, rows, output.len() as u32, sauce::today())
+            .for_utf8(&output)
+            .append_to(&mut output);
+    }
+    match std::fs::write(file_name, &output) {
+        Err(e) => anyhow::bail!("Could not save file: {e}"),
+        _ => (),
+    }
+    program_state.last_saved_revision = program_state.canvas.get_current_revision();
+    if program_state.config.undo_file {
+        if let Err(err) = UndoFile::save(&program_state.canvas, file_name, &output) {
+            program_state.new_messages.push_back(err.to_string());
+        }
+    }
+    if quantized_cells > 0 {
+        program_state.new_messages.push_back(format!(
+            "Reduced {quantized_cells} cells to {}",
+            options.color_depth
+        ));
+    }
+    Ok(())
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Presetable)]
 #[presetable(config_type = "ProgramState", preset_type = "Self")]
 pub struct Quit {}
@@ -34,18 +156,15 @@ impl Action for ForceQuit {
 pub struct Save {}
 impl FallibleAction for Save {
     fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
-        let Some(file_name) = &program_state.open_file else {
+        let Some(file_name) = program_state.open_file.clone() else {
             anyhow::bail!("No file open. Use \"save as\" instead (:w <filename>)");
         };
-        let format = FileFormat::try_from(file_name.as_str())?;
-        program_state.canvas.clean();
-        let output = program_state.canvas.export(format)?;
-        match std::fs::write(file_name, output) {
-            Err(e) => anyhow::bail!("Could not save file: {e}"),
-            _ => (),
-        }
-        program_state.last_saved_revision = program_state.canvas.get_current_revision();
-        Ok(())
+        save_to_file(
+            program_state,
+            file_name.as_str(),
+            false,
+            ExportOptions::from_config(&program_state.config),
+        )
     }
 }
 
@@ -54,53 +173,57 @@ impl FallibleAction for Save {
 pub struct LossySave {}
 impl FallibleAction for LossySave {
     fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
-        let Some(file_name) = &program_state.open_file else {
+        let Some(file_name) = program_state.open_file.clone() else {
             anyhow::bail!("No file open. Use \"save as\" instead (:w <filename>)");
         };
-        let format = FileFormat::try_from(file_name.as_str())?;
-        program_state.canvas.clean();
-        let output = program_state.canvas.export_lossy(format)?;
-        match std::fs::write(file_name, output) {
-            Err(e) => anyhow::bail!("Could not save file: {e}"),
-            _ => (),
-        }
-        program_state.last_saved_revision = program_state.canvas.get_current_revision();
-        Ok(())
+        save_to_file(
+            program_state,
+            file_name.as_str(),
+            true,
+            ExportOptions::from_config(&program_state.config),
+        )
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct SaveAs {
     pub filename: String,
+    pub options: ExportOptions,
 }
 impl FallibleAction for SaveAs {
     fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
-        let format = FileFormat::try_from(self.filename.as_str())?;
-        program_state.canvas.clean();
-        let output = program_state.canvas.export(format)?;
-        match std::fs::write(&self.filename, output) {
-            Err(e) => anyhow::bail!("Could not save file: {e}"),
-            _ => (),
-        }
-        program_state.last_saved_revision = program_state.canvas.get_current_revision();
-        Ok(())
+        save_to_file(program_state, self.filename.as_str(), false, self.options)
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct LossySaveAs {
     pub filename: String,
+    pub options: ExportOptions,
 }
 impl FallibleAction for LossySaveAs {
     fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
-        let format = FileFormat::try_from(self.filename.as_str())?;
-        program_state.canvas.clean();
-        let output = program_state.canvas.export_lossy(format)?;
-        match std::fs::write(&self.filename, output) {
-            Err(e) => return Err(anyhow::anyhow!("Could not save file: {e}")),
-            _ => (),
-        }
-        program_state.last_saved_revision = program_state.canvas.get_current_revision();
+        save_to_file(program_state, self.filename.as_str(), true, self.options)
+    }
+}
+
+/// Imports a PNG or PPM image as half-block pixel art at the cursor, in a single commit.
+#[derive(Clone, Debug)]
+pub struct ReadImage {
+    pub filename: String,
+    pub options: ImageImportOptions,
+}
+impl FallibleAction for ReadImage {
+    fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
+        let bytes = std::fs::read(&self.filename)
+            .map_err(|err| anyhow::anyhow!("Couldn't read {}: {err}", self.filename))?;
+        let image = Canvas::from_image(&Image::decode(&bytes)?, self.options);
+        program_state
+            .canvas
+            .create_commit(vec![CanvasModification::Paste(
+                program_state.cursor_position,
+                CanvasYank::from_canvas(&image),
+            )]);
         Ok(())
     }
 }
@@ -110,17 +233,15 @@ impl FallibleAction for LossySaveAs {
 pub struct SaveQuit {}
 impl FallibleAction for SaveQuit {
     fn try_execute(&self, program_state: &mut ProgramState) -> ExecuteActionResult {
-        let Some(file_name) = &program_state.open_file else {
+        let Some(file_name) = program_state.open_file.clone() else {
             anyhow::bail!("No file open. Use \"save as\" instead (:w <filename>)");
         };
-        let format = FileFormat::try_from(file_name.as_str())?;
-        program_state.canvas.clean();
-        let output = program_state.canvas.export(format)?;
-        match std::fs::write(file_name, output) {
-            Err(e) => anyhow::bail!("Could not save file: {e}"),
-            _ => (),
-        }
-        program_state.last_saved_revision = program_state.canvas.get_current_revision();
+        save_to_file(
+            program_state,
+            file_name.as_str(),
+            false,
+            ExportOptions::from_config(&program_state.config),
+        )?;
         program_state.exit = true;
         Ok(())
     }
@@ -1,4 +1,6 @@
+pub mod frames;
 pub mod history;
+pub mod layers;
 pub mod raw;
 pub mod rect;
 
@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+use derive_more::{Display, Error};
+use serde::{Deserialize, Serialize};
+
+use super::layers::{CanvasLayer, LayerProperties};
+use super::raw::Canvas;
+
+#[cfg(test)]
+mod test;
+
+/// How long a new frame is shown, in milliseconds.
+pub const DEFAULT_FRAME_DURATION: u32 = 100;
+
+/// A frame of an animation, with its own stack of layers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Frame {
+    /// How long the frame is shown, in milliseconds.
+    pub duration: u32,
+    /// Layers from the bottom to the top, there is always at least one.
+    pub layers: Vec<CanvasLayer>,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::new(vec![CanvasLayer::new("Layer 1", Canvas::default())])
+    }
+}
+
+impl Frame {
+    pub fn new(layers: Vec<CanvasLayer>) -> Self {
+        Self {
+            duration: DEFAULT_FRAME_DURATION,
+            layers,
+        }
+    }
+
+    /// An empty frame with the same layers as this one, but without their cells.
+    pub fn empty_like(&self) -> Self {
+        Self {
+            duration: self.duration,
+            layers: self
+                .layers
+                .iter()
+                .map(|layer| CanvasLayer {
+                    properties: LayerProperties {
+                        locked: false,
+                        ..layer.properties.clone()
+                    },
+                    canvas: Canvas::default(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Display, Error, PartialEq)]
+pub enum FrameError {
+    #[display("There is no frame {}", _0 + 1)]
+    NoSuchFrame(#[error(ignore)] usize),
+    #[display("Can't delete the only frame")]
+    OnlyFrame,
+    #[display("A frame must be shown for at least 1 ms")]
+    ZeroDuration,
+}
+
+/// A change to the list of frames, other than to the layers of a frame.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum FramesDiff {
+    Insert(usize, Box<Frame>),
+    Remove(usize),
+    SetDuration(usize, u32),
+}
+
+impl FramesDiff {
+    /// Applies the diff to `frames` and reverses the diff in place, like `LayersDiff::apply`.
+    /// Returns the index of the frame the diff affected.
+    pub fn apply(&mut self, frames: &mut Vec<Frame>) -> usize {
+        match self {
+            Self::Insert(index, frame) => {
+                let index = *index;
+                frames.insert(index, std::mem::take(frame.as_mut()));
+                *self = Self::Remove(index);
+                index
+            }
+            Self::Remove(index) => {
+                let index = *index;
+                *self = Self::Insert(index, Box::new(frames.remove(index)));
+                index
+            }
+            Self::SetDuration(index, duration) => {
+                std::mem::swap(&mut frames[*index].duration, duration);
+                *index
+            }
+        }
+    }
+}
+
+/// The frame shown after `elapsed` time of looping playback, and how long it is still shown.
+pub fn frame_at(frames: &[Frame], elapsed: Duration) -> (usize, Duration) {
+    let durations = frames
+        .iter()
+        .map(|frame| frame.duration.max(1) as u128)
+        .collect::<Vec<_>>();
+    let mut time = elapsed.as_millis() % durations.iter().sum::<u128>();
+    for (index, duration) in durations.into_iter().enumerate() {
+        if time < duration {
+            return (index, Duration::from_millis((duration - time) as u64));
+        }
+        time -= duration;
+    }
+    unreachable!("the elapsed time is shorter than the loop")
+}
@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use crate::canvas::frames::{frame_at, Frame, FramesDiff};
+
+fn frames(durations: &[u32]) -> Vec<Frame> {
+    durations
+        .iter()
+        .map(|duration| Frame {
+            duration: *duration,
+            ..Default::default()
+        })
+        .collect()
+}
+
+#[test]
+fn frame_at_loops() {
+    let frames = frames(&[100, 50, 200]);
+    assert_eq!(
+        frame_at(&frames, Duration::ZERO),
+        (0, Duration::from_millis(100))
+    );
+    assert_eq!(
+        frame_at(&frames, Duration::from_millis(120)),
+        (1, Duration::from_millis(30))
+    );
+    assert_eq!(
+        frame_at(&frames, Duration::from_millis(150)),
+        (2, Duration::from_millis(200))
+    );
+    assert_eq!(
+        frame_at(&frames, Duration::from_millis(350 * 3 + 10)),
+        (0, Duration::from_millis(90))
+    );
+}
+
+#[test]
+fn apply_reverses_diff() {
+    let mut frames = frames(&[100, 200]);
+    let mut diff = FramesDiff::SetDuration(1, 300);
+    diff.apply(&mut frames);
+    assert_eq!(frames[1].duration, 300);
+    diff.apply(&mut frames);
+    assert_eq!(frames[1].duration, 200);
+
+    let mut diff = FramesDiff::Remove(0);
+    assert_eq!(diff.apply(&mut frames), 0);
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].duration, 200);
+    assert_eq!(diff.apply(&mut frames), 0);
+    assert_eq!(frames[0].duration, 100);
+}
+
+#[test]
+fn empty_like_keeps_layers() {
+    let mut frame = Frame::default();
+    frame.layers[0].properties.name = "Background".to_string();
+    frame.layers[0].properties.locked = true;
+    frame.layers[0].canvas.set_character((0, 0), 'a');
+
+    let empty = frame.empty_like();
+
+    assert_eq!(empty.layers[0].properties.name, "Background");
+    assert!(!empty.layers[0].properties.locked);
+    assert_eq!(empty.layers[0].canvas.character((0, 0)), ' ');
+}
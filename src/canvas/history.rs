@@ -1,125 +1,716 @@
-use std::collections::LinkedList;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::mem;
+use std::time::{Duration, SystemTime};
 
+use serde::{Deserialize, Serialize};
+
+use crate::canvas::raw::continuous_region::MatchCellSame;
 use crate::canvas::raw::operations::CanvasDiff;
 use crate::canvas::raw::operations::CanvasDiffBuilder;
+use crate::selections::Selection;
 use crate::{config::Config, file_formats::FileFormat};
 
+use super::frames::{self, Frame, FrameError, FramesDiff};
+use super::layers::{self, CanvasLayer, LayerError, LayerProperties, LayersDiff};
 use super::raw::{
     ansi_import::AnsiImportError, operations::CanvasModification, rendering::CanvasWidget, Canvas,
+    CanvasIndex, CanvasMetadata,
 };
+use super::rect::CanvasRect;
+
+#[cfg(test)]
+mod test;
+
+/// What a commit changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum CommitChange {
+    /// Changes the cells or metadata of the layer at index `layer` of the frame at `frame`.
+    Diff {
+        frame: usize,
+        layer: usize,
+        diff: CanvasDiff,
+    },
+    Layers {
+        frame: usize,
+        diff: LayersDiff,
+    },
+    Frames(FramesDiff),
+    /// Swaps the page size with the one of the canvas.
+    Page(Option<CanvasRect>),
+    /// Swaps the metadata of the artwork with the one of the canvas.
+    Metadata(CanvasMetadata),
+}
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CanvasCommit {
     revision: u64,
-    diff: CanvasDiff,
+    /// Revision the commit was made on top of, 0 for the initial canvas.
+    parent: u64,
+    time: SystemTime,
+    change: CommitChange,
 }
 
-#[derive(Debug, Default, Clone)]
+impl CanvasCommit {
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+    pub fn parent(&self) -> u64 {
+        self.parent
+    }
+    /// When the commit was made.
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+    /// Number of cells changed by the commit, 0 if it changes the layers, frames, page or
+    /// metadata.
+    pub fn changed_cells(&self) -> usize {
+        match &self.change {
+            CommitChange::Diff { diff, .. } => diff.len(),
+            _ => 0,
+        }
+    }
+    /// Indices of the cells changed by the commit.
+    pub fn changed_indices(&self) -> Selection {
+        match &self.change {
+            CommitChange::Diff { diff, .. } => diff.indices().collect(),
+            _ => Selection::new(),
+        }
+    }
+    /// Index of the frame changed by the commit, if it doesn't change the frames, page or
+    /// metadata.
+    pub fn frame(&self) -> Option<usize> {
+        match &self.change {
+            CommitChange::Diff { frame, .. } | CommitChange::Layers { frame, .. } => Some(*frame),
+            _ => None,
+        }
+    }
+}
+
+/// The whole history of a canvas, from which it can be restored at any revision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanvasHistory {
+    /// Frames of the initial canvas, before any commit.
+    frames: Vec<Frame>,
+    page: Option<CanvasRect>,
+    #[serde(default)]
+    metadata: CanvasMetadata,
+    /// Every commit, none of which are applied to `frames`.
+    commits: Vec<CanvasCommit>,
+    redo_children: HashMap<u64, u64>,
+    revision_counter: u64,
+    /// The revision the canvas is restored at.
+    revision: u64,
+    active_frame: usize,
+    active_layer: usize,
+    #[serde(default)]
+    checkpoints: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone)]
 pub struct VersionControlledCanvas {
-    initial: Canvas,
-    current: Canvas,
-    commits: LinkedList<CanvasCommit>,
-    commits_unapplied: LinkedList<CanvasCommit>,
+    initial: Vec<Frame>,
+    /// Frames of the animation, there is always at least one.
+    frames: Vec<Frame>,
+    active_frame: usize,
+    /// Index of the active layer in the active frame.
+    active_layer: usize,
+    /// Fixed area of the artwork, which the cursor is kept inside of and which is exported.
+    page: Option<CanvasRect>,
+    /// Metadata of the artwork, which belongs to no frame or layer.
+    metadata: CanvasMetadata,
+    /// Every commit by revision. The commits form a tree, where the commits from the initial
+    /// canvas to the current revision are applied. Making a commit after undoing starts a new
+    /// branch, so that the undone commits are kept.
+    commits: BTreeMap<u64, CanvasCommit>,
+    /// Revision of the last applied commit, 0 if none is applied.
+    revision: u64,
+    /// The child of each revision which redo applies, the one most recently undone or made.
+    redo_children: HashMap<u64, u64>,
     staging_area: CanvasDiffBuilder,
+    staging_frame: usize,
+    staging_layer: usize,
     revision_counter: u64,
+    locked_layer_modified: bool,
+    /// Revisions saved under a name, which are never merged with other commits.
+    checkpoints: BTreeMap<String, u64>,
+    /// Counts every change to the canvas, see `changes`.
+    changes: u64,
+}
+
+impl CanvasHistory {
+    /// Returns whether the canvas can be restored from the history, which a history read from a
+    /// file may not allow. There must be at least one frame with at least one layer, and every
+    /// commit must be made on top of an earlier one, only change frames and layers existing at
+    /// its parent and not delete the only frame or layer.
+    pub fn is_valid(&self) -> bool {
+        if self.frames.iter().any(|frame| frame.layers.is_empty()) {
+            return false;
+        }
+        // Number of layers of every frame at each revision
+        let mut shapes: HashMap<u64, Vec<usize>> = HashMap::new();
+        shapes.insert(
+            0,
+            self.frames.iter().map(|frame| frame.layers.len()).collect(),
+        );
+        let mut commits: Vec<&CanvasCommit> = self.commits.iter().collect();
+        commits.sort_by_key(|commit| commit.revision);
+        for commit in commits {
+            if commit.parent >= commit.revision || shapes.contains_key(&commit.revision) {
+                return false;
+            }
+            let Some(mut shape) = shapes.get(&commit.parent).cloned() else {
+                return false;
+            };
+            let valid = match &commit.change {
+                CommitChange::Diff { frame, layer, .. } => {
+                    shape.get(*frame).is_some_and(|layers| layer < layers)
+                }
+                CommitChange::Layers { frame, diff } => match shape.get_mut(*frame) {
+                    Some(layers) => match diff {
+                        LayersDiff::Insert(index, _) if *index <= *layers => {
+                            *layers += 1;
+                            true
+                        }
+                        LayersDiff::Remove(index) if index < layers && *layers > 1 => {
+                            *layers -= 1;
+                            true
+                        }
+                        LayersDiff::Move { from, to } => from < layers && to < layers,
+                        LayersDiff::SetProperties(index, _) => index < layers,
+                        _ => false,
+                    },
+                    None => false,
+                },
+                CommitChange::Frames(diff) => match diff {
+                    FramesDiff::Insert(index, frame)
+                        if *index <= shape.len() && !frame.layers.is_empty() =>
+                    {
+                        shape.insert(*index, frame.layers.len());
+                        true
+                    }
+                    FramesDiff::Remove(index) if *index < shape.len() && shape.len() > 1 => {
+                        shape.remove(*index);
+                        true
+                    }
+                    FramesDiff::SetDuration(index, _) => *index < shape.len(),
+                    _ => false,
+                },
+                CommitChange::Page(_) | CommitChange::Metadata(_) => true,
+            };
+            if !valid {
+                return false;
+            }
+            shapes.insert(commit.revision, shape);
+        }
+        let redo_children_valid = self.redo_children.iter().all(|(parent, child)| {
+            self.commits
+                .iter()
+                .any(|commit| commit.revision == *child && commit.parent == *parent)
+        });
+        let newest = shapes.keys().copied().max().unwrap_or(0);
+        !self.frames.is_empty()
+            && redo_children_valid
+            && shapes.contains_key(&self.revision)
+            && self.revision_counter >= newest
+    }
+}
+
+impl Default for VersionControlledCanvas {
+    fn default() -> Self {
+        Self::from_canvas(Canvas::default())
+    }
 }
 
 impl VersionControlledCanvas {
     pub fn delete_history(&mut self) -> &mut Self {
-        self.initial = self.current.clone();
-        self.commits = LinkedList::new();
-        self.commits_unapplied = LinkedList::new();
+        self.initial = self.frames.clone();
+        self.commits = BTreeMap::new();
+        self.revision = 0;
+        self.redo_children = HashMap::new();
         self.revision_counter = 0;
         self
     }
 
     /// Creates a commit from the provided modifications, but doesn't apply it, as it is assumed
     /// that the modifications are already applied to the current canvas.
-    fn create_applied_commit(&mut self, diff: CanvasDiff) {
+    fn create_applied_commit(&mut self, change: CommitChange) {
         self.revision_counter += 1;
         let commit = CanvasCommit {
             revision: self.revision_counter,
-            diff,
+            parent: self.revision,
+            time: SystemTime::now(),
+            change,
         };
-        self.commits.push_back(commit);
-        self.commits_unapplied = LinkedList::new();
+        self.redo_children.insert(self.revision, commit.revision);
+        self.revision = commit.revision;
+        self.commits.insert(commit.revision, commit);
+        self.changes += 1;
+    }
+
+    /// Returns whether the active layer is locked. If so, the attempt to modify it is recorded,
+    /// see `take_locked_layer_modified`.
+    fn reject_locked(&mut self) -> bool {
+        let locked = self.active().properties.locked;
+        self.locked_layer_modified |= locked;
+        locked
+    }
+
+    /// Returns whether modifications have been discarded because the active layer was locked
+    /// since the last call.
+    pub fn take_locked_layer_modified(&mut self) -> bool {
+        mem::take(&mut self.locked_layer_modified)
+    }
+
+    /// Creates and applies a new commit from provided modifications to the layer at `layer`.
+    /// Modifications in staging area will be committed first if any.
+    fn create_layer_commit(
+        &mut self,
+        frame: usize,
+        layer: usize,
+        modifications: impl IntoIterator<Item = CanvasModification>,
+    ) {
+        self.commit_staged();
+        let canvas = &mut self.frames[frame].layers[layer].canvas;
+        let mut diff = CanvasDiffBuilder::from_modifications(modifications, canvas).serialize();
+        canvas.apply_diff(&mut diff);
+        self.create_applied_commit(CommitChange::Diff { frame, layer, diff });
     }
 
-    /// Creates and applies a new commit from provided modifications.
+    /// Creates and applies a new commit from provided modifications to the active layer.
     /// Modifications in staging area will be committed first if any.
+    ///
+    /// Nothing is modified if the active layer is locked.
     pub fn create_commit(
         &mut self,
         modifications: impl IntoIterator<Item = CanvasModification>,
     ) -> &mut Self {
-        self.commit_staged();
-        let mut diff =
-            CanvasDiffBuilder::from_modifications(modifications, &self.current).serialize();
-        self.current.apply_diff(&mut diff);
-        self.create_applied_commit(diff);
+        if !self.reject_locked() {
+            self.create_layer_commit(self.active_frame, self.active_layer, modifications);
+        }
         self
     }
 
-    /// Adds a modification to the staging area
+    /// Commits new metadata for the artwork. Unlike cells, metadata can be changed while the
+    /// active layer is locked.
+    pub fn set_metadata(&mut self, metadata: CanvasMetadata) {
+        self.commit_staged();
+        let mut change = CommitChange::Metadata(metadata);
+        self.apply_change(&mut change);
+        self.create_applied_commit(change);
+    }
+
+    /// Adds a modification of the active layer to the staging area
     pub fn stage(&mut self, modification: CanvasModification) {
-        let mut diff = CanvasDiffBuilder::from_modifications(vec![modification], &self.current);
-        self.current.apply_diff_builder(&mut diff);
+        if self.reject_locked() {
+            return;
+        }
+        if (self.staging_frame, self.staging_layer) != (self.active_frame, self.active_layer) {
+            self.commit_staged();
+            self.staging_frame = self.active_frame;
+            self.staging_layer = self.active_layer;
+        }
+        let canvas = &mut self.frames[self.staging_frame].layers[self.staging_layer].canvas;
+        let mut diff = CanvasDiffBuilder::from_modifications(vec![modification], canvas);
+        canvas.apply_diff_builder(&mut diff);
+        self.changes += 1;
         // overwrite must be false, because the staging area contains the reversing diffs, which
         // must be preserved to revert to the original state.
         self.staging_area.add_diff(diff, false);
     }
 
+    /// Number of changes made to the canvas so far, including staged modifications, undoing and
+    /// temporary commits. It differs whenever the canvas may look different, unlike the revision,
+    /// which is reused after a temporary commit is removed.
+    pub fn changes(&self) -> u64 {
+        self.changes
+    }
+
     pub fn clear_staged(&mut self) {
-        self.current.apply_diff_builder(&mut self.staging_area);
+        self.frames[self.staging_frame].layers[self.staging_layer]
+            .canvas
+            .apply_diff_builder(&mut self.staging_area);
         self.staging_area = CanvasDiffBuilder::default();
+        self.changes += 1;
     }
 
     /// Creates a commit from modifications in staging area
     pub fn commit_staged(&mut self) {
-        if !self.staging_area.entries.is_empty() {
+        if !self.staging_area.is_empty() {
             let staged = mem::take(&mut self.staging_area).serialize();
-            self.create_applied_commit(staged);
+            self.create_applied_commit(CommitChange::Diff {
+                frame: self.staging_frame,
+                layer: self.staging_layer,
+                diff: staged,
+            });
+        }
+    }
+
+    /// Activates the frame at `frame`, keeping the index of the active layer if the frame has
+    /// as many layers.
+    fn activate_frame(&mut self, frame: usize) {
+        self.active_frame = frame.min(self.frames.len() - 1);
+        self.active_layer = self.active_layer.min(self.layers().len() - 1);
+    }
+
+    /// Applies `change` and reverses it in place. Every change activates the affected frame, and
+    /// changes to the layer stack activate the affected layer.
+    fn apply_change(&mut self, change: &mut CommitChange) {
+        self.changes += 1;
+        match change {
+            CommitChange::Diff { frame, layer, diff } => {
+                self.frames[*frame].layers[*layer].canvas.apply_diff(diff);
+                self.activate_frame(*frame);
+            }
+            CommitChange::Layers { frame, diff } => {
+                let index = diff.apply(&mut self.frames[*frame].layers);
+                self.active_frame = *frame;
+                self.active_layer = index.min(self.layers().len() - 1);
+                self.staging_frame = self.active_frame;
+                self.staging_layer = self.active_layer;
+            }
+            CommitChange::Frames(diff) => {
+                let index = diff.apply(&mut self.frames);
+                self.activate_frame(index);
+                self.staging_frame = self.active_frame;
+                self.staging_layer = self.active_layer;
+            }
+            CommitChange::Page(page) => mem::swap(&mut self.page, page),
+            CommitChange::Metadata(metadata) => mem::swap(&mut self.metadata, metadata),
         }
     }
 
     pub fn undo(&mut self) {
-        if let Some(mut last_commit) = self.commits.pop_back() {
-            self.current.apply_diff(&mut last_commit.diff);
-            self.commits_unapplied.push_front(last_commit);
+        if let Some(mut commit) = self.commits.remove(&self.revision) {
+            self.apply_change(&mut commit.change);
+            self.redo_children.insert(commit.parent, commit.revision);
+            self.revision = commit.parent;
+            self.commits.insert(commit.revision, commit);
         }
     }
 
-    pub fn reset_hard(&mut self, target: u64) {
-        while self.get_current_revision() > target {
+    pub fn redo(&mut self) {
+        if let Some(child) = self.redo_children.get(&self.revision).copied() {
+            self.apply_child(child);
+        }
+    }
+
+    /// Applies the commit at `child`, which must have been made on top of the current revision.
+    fn apply_child(&mut self, child: u64) {
+        let mut commit = self.commits.remove(&child).unwrap();
+        debug_assert_eq!(commit.parent, self.revision);
+        self.apply_change(&mut commit.change);
+        self.redo_children.insert(commit.parent, child);
+        self.revision = child;
+        self.commits.insert(child, commit);
+    }
+
+    /// Moves to the revision `target` through the tree, by undoing commits up to the last
+    /// revision it shares with `target` and redoing commits from there. Undone commits made after
+    /// `target` that have no commits made on top of them are deleted if `discard` is set.
+    fn move_to_revision(&mut self, target: u64, discard: bool) {
+        if target != 0 && !self.commits.contains_key(&target) {
+            return;
+        }
+        let path = self.ancestors(target);
+        let on_path: HashSet<u64> = path.iter().copied().collect();
+        while !on_path.contains(&self.revision) {
+            let undone = self.revision;
             self.undo();
+            if discard && undone > target && self.children(undone).next().is_none() {
+                self.delete_commit(undone);
+            }
+        }
+        let shared = path.iter().position(|revision| *revision == self.revision);
+        for child in path[..shared.unwrap()].iter().rev() {
+            self.apply_child(*child);
         }
     }
 
-    pub fn redo(&mut self) {
-        if let Some(mut next_commit) = self.commits_unapplied.pop_front() {
-            self.current.apply_diff(&mut next_commit.diff);
-            self.commits.push_back(next_commit);
+    /// Deletes the unapplied commit at `revision`, which must have no children.
+    fn delete_commit(&mut self, revision: u64) {
+        let Some(commit) = self.commits.remove(&revision) else {
+            return;
+        };
+        if self.redo_children.get(&commit.parent) == Some(&revision) {
+            match self.children(commit.parent).last() {
+                Some(child) => self.redo_children.insert(commit.parent, child),
+                None => self.redo_children.remove(&commit.parent),
+            };
+        }
+        // Revisions are only reused after deleting the newest one, which is never the saved one
+        if revision == self.revision_counter {
+            self.revision_counter -= 1;
+        }
+        self.checkpoints
+            .retain(|_name, checkpoint| *checkpoint != revision);
+    }
+
+    /// Merges the commit at the current revision into its parent, so that both are undone at once.
+    /// The merged commit gets a new revision.
+    ///
+    /// Returns whether the commits were merged, which requires both to change the cells of the
+    /// same layer, neither to be a checkpoint, and no other commits to be made on top of them.
+    pub fn merge_with_parent(&mut self) -> bool {
+        let Some(commit) = self.commits.get(&self.revision) else {
+            return false;
+        };
+        let Some(parent) = self.commits.get(&commit.parent) else {
+            return false;
+        };
+        let (
+            CommitChange::Diff { frame, layer, .. },
+            CommitChange::Diff {
+                frame: parent_frame,
+                layer: parent_layer,
+                ..
+            },
+        ) = (&commit.change, &parent.change)
+        else {
+            return false;
+        };
+        if (frame, layer) != (parent_frame, parent_layer)
+            || self.children(commit.revision).next().is_some()
+            || self.children(parent.revision).nth(1).is_some()
+            || self
+                .checkpoints
+                .values()
+                .any(|checkpoint| [commit.revision, parent.revision].contains(checkpoint))
+        {
+            return false;
+        }
+        let commit = self.commits.remove(&self.revision).unwrap();
+        let parent = self.commits.remove(&commit.parent).unwrap();
+        let (
+            CommitChange::Diff { frame, layer, diff },
+            CommitChange::Diff {
+                diff: parent_diff, ..
+            },
+        ) = (commit.change, parent.change)
+        else {
+            unreachable!();
+        };
+        self.redo_children.remove(&parent.revision);
+        self.revision = parent.parent;
+        self.create_applied_commit(CommitChange::Diff {
+            frame,
+            layer,
+            diff: parent_diff.merge_reversed(diff),
+        });
+        self.commits.get_mut(&self.revision).unwrap().time = commit.time;
+        true
+    }
+
+    /// Merges the commits made on top of `revision`, up to the current revision, into as few
+    /// commits as possible, see `merge_with_parent`.
+    pub fn merge_commits_since(&mut self, revision: u64) {
+        if !self.ancestors(self.revision).contains(&revision) {
+            return;
         }
+        while self.revision != revision
+            && self.commits[&self.revision].parent != revision
+            && self.merge_with_parent()
+        {}
+    }
+
+    /// Revisions of the commits made on top of `revision`, oldest first.
+    pub fn children(&self, revision: u64) -> impl Iterator<Item = u64> + '_ {
+        self.commits
+            .values()
+            .filter(move |commit| commit.parent == revision)
+            .map(|commit| commit.revision)
+    }
+
+    /// Moves to the revision `target`, anywhere in the tree, without losing any commits.
+    pub fn go_to_revision(&mut self, target: u64) {
+        self.move_to_revision(target, false);
+    }
+
+    /// Moves back to the revision `target`, deleting the commits made after it which were undone
+    /// on the way and have no commits made on top of them, like temporary commits.
+    pub fn reset_hard(&mut self, target: u64) {
+        self.move_to_revision(target, true);
+    }
+
+    /// Moves to the revision made before the current one, regardless of the branch it is on.
+    pub fn go_to_earlier_revision(&mut self) {
+        let earlier = self.commits.range(..self.revision).next_back();
+        self.go_to_revision(earlier.map_or(0, |(revision, _commit)| *revision));
+    }
+
+    /// Moves to the revision made after the current one, regardless of the branch it is on.
+    pub fn go_to_later_revision(&mut self) {
+        if let Some((later, _commit)) = self.commits.range(self.revision + 1..).next() {
+            self.go_to_revision(*later);
+        }
+    }
+
+    /// Every commit, in the order they were made.
+    pub fn commits(&self) -> impl Iterator<Item = &CanvasCommit> {
+        self.commits.values()
+    }
+
+    /// Saves the current revision under `name`, replacing any revision saved under it before.
+    /// Staged modifications are committed first.
+    pub fn set_checkpoint(&mut self, name: String) {
+        self.commit_staged();
+        self.checkpoints.insert(name, self.revision);
+    }
+
+    /// The revisions saved under a name, by name.
+    pub fn checkpoints(&self) -> &BTreeMap<String, u64> {
+        &self.checkpoints
+    }
+
+    /// The commit at `revision`, wherever it is in the tree.
+    pub fn commit(&self, revision: u64) -> Option<&CanvasCommit> {
+        self.commits.get(&revision)
+    }
+
+    /// Revisions from `revision` back to the initial canvas, both included.
+    pub fn ancestors(&self, revision: u64) -> Vec<u64> {
+        let mut result = vec![revision];
+        while let Some(commit) = self.commits.get(result.last().unwrap()) {
+            result.push(commit.parent);
+        }
+        result
     }
 
     pub fn get_current_revision(&self) -> u64 {
-        if let Some(last_commit) = self.commits.back() {
-            last_commit.revision
-        } else {
-            0
+        self.revision
+    }
+
+    /// Cells of the active frame which look different than at `revision`, including the staged
+    /// modifications. The canvas at `revision` is found by applying only the commits between
+    /// both revisions to a copy of the frames, and only the cells changed by those commits are
+    /// compared, unless they change the layers or frames.
+    pub fn cells_changed_since(&self, revision: u64) -> Selection {
+        if revision != 0 && !self.commits.contains_key(&revision) {
+            return Selection::new();
+        }
+        let current_path: HashSet<u64> = self.ancestors(self.revision).into_iter().collect();
+        let target_path: HashSet<u64> = self.ancestors(revision).into_iter().collect();
+        let mut commits: BTreeMap<u64, CanvasCommit> = current_path
+            .symmetric_difference(&target_path)
+            .map(|revision| (*revision, self.commits[revision].clone()))
+            .collect();
+        // Changing the layers or frames can change how any cell looks
+        let changed_cells = commits
+            .values()
+            .all(|commit| {
+                matches!(
+                    commit.change,
+                    CommitChange::Diff { .. } | CommitChange::Metadata(_)
+                )
+            })
+            .then(|| {
+                let staged = (self.staging_frame == self.active_frame)
+                    .then_some(self.staging_area.entries.keys().copied())
+                    .into_iter()
+                    .flatten();
+                commits
+                    .values()
+                    .filter(|commit| commit.frame() == Some(self.active_frame))
+                    .flat_map(CanvasCommit::changed_indices)
+                    .chain(staged)
+                    .collect::<Selection>()
+            });
+        // Moving to a revision requires its commit, even if it is already applied
+        if let Some(commit) = self.commits.get(&revision) {
+            commits.entry(revision).or_insert_with(|| commit.clone());
         }
+
+        let mut target = Self {
+            initial: Vec::new(),
+            frames: self.frames.clone(),
+            commits,
+            redo_children: HashMap::new(),
+            staging_area: self.staging_area.clone(),
+            checkpoints: BTreeMap::new(),
+            metadata: CanvasMetadata::new(),
+            ..*self
+        };
+        target.clear_staged();
+        target.go_to_revision(revision);
+
+        let current = self.flattened();
+        let target = match target.frames.get(self.active_frame) {
+            Some(_frame) => target.flattened_frame(self.active_frame).into_owned(),
+            None => Canvas::default(),
+        };
+        let candidates = changed_cells.unwrap_or_else(|| {
+            let all = MatchCellSame::default();
+            let mut cells = current.cells_matching(all.clone());
+            cells.extend(target.cells_matching(all));
+            cells
+        });
+        candidates
+            .into_iter()
+            .filter(|index| current.cell(*index) != target.cell(*index))
+            .collect()
+    }
+
+    /// The highest revision given to a commit so far. Commits made later get higher revisions.
+    pub fn latest_revision(&self) -> u64 {
+        self.revision_counter
     }
 
     pub fn export(&self, format: FileFormat) -> anyhow::Result<String> {
-        self.current.export(format)
+        self.flattened().export(format)
     }
 
     pub fn export_lossy(&self, format: FileFormat) -> anyhow::Result<String> {
-        self.current.export_lossy(format)
+        self.flattened().export_lossy(format)
     }
 
     pub fn clean(&mut self) {
-        self.current.clean();
+        for frame in &mut self.frames {
+            for layer in &mut frame.layers {
+                layer.canvas.clean();
+            }
+        }
+    }
+
+    pub fn from_canvas(canvas: Canvas) -> Self {
+        Self::from_layers(vec![CanvasLayer::new("Layer 1", canvas)], 0)
+    }
+
+    /// Creates a canvas from `layers`, which must contain at least one layer, with the layer at
+    /// `active_layer` active.
+    pub fn from_layers(layers: Vec<CanvasLayer>, active_layer: usize) -> Self {
+        Self::from_frames(vec![Frame::new(layers)], 0, active_layer)
+    }
+
+    /// Creates a canvas from `frames`, which must contain at least one frame, each with at least
+    /// one layer. The layer at `active_layer` of the frame at `active_frame` is active. The
+    /// metadata of the first layer of the first frame becomes the metadata of the artwork.
+    pub fn from_frames(mut frames: Vec<Frame>, active_frame: usize, active_layer: usize) -> Self {
+        assert!(!frames.is_empty(), "a canvas has at least one frame");
+        assert!(
+            frames.iter().all(|frame| !frame.layers.is_empty()),
+            "a frame has at least one layer"
+        );
+        let metadata = mem::take(&mut frames[0].layers[0].canvas.metadata);
+        let mut result = Self {
+            initial: frames.clone(),
+            frames,
+            active_frame: 0,
+            active_layer,
+            page: None,
+            metadata,
+            commits: BTreeMap::new(),
+            revision: 0,
+            redo_children: HashMap::new(),
+            staging_area: CanvasDiffBuilder::default(),
+            staging_frame: 0,
+            staging_layer: 0,
+            revision_counter: 0,
+            locked_layer_modified: false,
+            checkpoints: BTreeMap::new(),
+            changes: 0,
+        };
+        result.activate_frame(active_frame);
+        result
     }
 
     pub fn from_ansi(ansi: String) -> Result<Self, AnsiImportError>
@@ -127,21 +718,282 @@ impl VersionControlledCanvas {
         Self: Sized,
     {
         match Canvas::from_ansi(ansi) {
-            Ok(canvas) => {
-                let mut result = Self::default();
-                result.initial = canvas.clone();
-                result.current = canvas;
-                Ok(result)
-            }
+            Ok(canvas) => Ok(Self::from_canvas(canvas)),
             Err(e) => Err(e),
         }
     }
 
+    /// The history of the canvas, at the current revision. Staged modifications aren't part of
+    /// it.
+    pub fn history(&self) -> CanvasHistory {
+        let mut initial = self.clone();
+        initial.clear_staged();
+        initial.go_to_revision(0);
+        CanvasHistory {
+            frames: initial.frames,
+            page: initial.page,
+            metadata: initial.metadata,
+            commits: initial.commits.into_values().collect(),
+            redo_children: initial.redo_children,
+            revision_counter: initial.revision_counter,
+            revision: self.revision,
+            active_frame: self.active_frame,
+            active_layer: self.active_layer,
+            checkpoints: initial.checkpoints,
+        }
+    }
+
+    /// Restores a canvas at the revision of `history`, with all of its commits.
+    pub fn from_history(history: CanvasHistory) -> Self {
+        let mut result = Self::from_frames(history.frames, 0, 0)
+            .with_page(history.page)
+            .with_metadata(history.metadata);
+        result.commits = history
+            .commits
+            .into_iter()
+            .map(|commit| (commit.revision, commit))
+            .collect();
+        result.redo_children = history.redo_children;
+        result.revision_counter = history.revision_counter;
+        result.checkpoints = history.checkpoints;
+        result.go_to_revision(history.revision);
+        result.activate_frame(history.active_frame);
+        result.active_layer = history.active_layer.min(result.layers().len() - 1);
+        result.staging_frame = result.active_frame;
+        result.staging_layer = result.active_layer;
+        result
+    }
+
     pub fn widget<'a>(&'a self, config: &'a Config) -> CanvasWidget<'a> {
-        CanvasWidget::from_canvas(&self.current, config)
+        let mut widget = CanvasWidget::from_canvas(self.flattened(), config);
+        widget.page = self.page;
+        widget
     }
 
+    /// The active layer, which actions read and modify.
     pub fn raw(&self) -> &Canvas {
-        &self.current
+        &self.active().canvas
+    }
+
+    /// The visible layers of the active frame drawn on top of each other, as shown and exported.
+    pub fn flattened(&self) -> Cow<'_, Canvas> {
+        self.flattened_frame(self.active_frame)
+    }
+
+    /// The visible layers of the frame at `frame` drawn on top of each other.
+    pub fn flattened_frame(&self, frame: usize) -> Cow<'_, Canvas> {
+        layers::flatten(&self.frames[frame].layers)
+    }
+
+    /// Every frame flattened, as exported to an animation.
+    pub fn flattened_frames(&self) -> Vec<Cow<'_, Canvas>> {
+        (0..self.frames.len())
+            .map(|frame| self.flattened_frame(frame))
+            .collect()
+    }
+
+    /// Metadata of the artwork, which is kept when any frame or layer is deleted or moved.
+    pub fn metadata(&self) -> &CanvasMetadata {
+        &self.metadata
+    }
+
+    /// Sets the metadata of an unedited canvas, without creating a commit.
+    pub fn with_metadata(mut self, metadata: CanvasMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Layers of the active frame.
+    pub fn layers(&self) -> &[CanvasLayer] {
+        &self.frames[self.active_frame].layers
+    }
+
+    pub fn active(&self) -> &CanvasLayer {
+        &self.layers()[self.active_layer]
+    }
+
+    pub fn active_layer(&self) -> usize {
+        self.active_layer
+    }
+
+    fn layer_index(&self, index: usize) -> Result<usize, LayerError> {
+        if index < self.layers().len() {
+            Ok(index)
+        } else {
+            Err(LayerError::NoSuchLayer(index))
+        }
+    }
+
+    pub fn set_active_layer(&mut self, index: usize) -> Result<(), LayerError> {
+        self.commit_staged();
+        self.active_layer = self.layer_index(index)?;
+        Ok(())
+    }
+
+    fn create_layers_commit(&mut self, diff: LayersDiff) {
+        self.commit_staged();
+        let mut change = CommitChange::Layers {
+            frame: self.active_frame,
+            diff,
+        };
+        self.apply_change(&mut change);
+        self.create_applied_commit(change);
+    }
+
+    /// Adds an empty layer above the active layer of the active frame, and activates it.
+    pub fn add_layer(&mut self, name: Option<String>) {
+        let name = name.unwrap_or_else(|| layers::unused_layer_name(self.layers()));
+        self.create_layers_commit(LayersDiff::Insert(
+            self.active_layer + 1,
+            Box::new(CanvasLayer::new(name, Canvas::default())),
+        ));
+    }
+
+    /// Deletes the active layer, activating the layer below it.
+    pub fn delete_layer(&mut self) -> Result<(), LayerError> {
+        if self.layers().len() == 1 {
+            return Err(LayerError::OnlyLayer);
+        }
+        if self.active().properties.locked {
+            return Err(LayerError::Locked(self.active().properties.name.clone()));
+        }
+        let below = self.active_layer.saturating_sub(1);
+        self.create_layers_commit(LayersDiff::Remove(self.active_layer));
+        self.active_layer = below;
+        self.staging_layer = below;
+        Ok(())
+    }
+
+    /// Moves the active layer to index `to`, changing which layers it covers.
+    pub fn move_layer(&mut self, to: usize) -> Result<(), LayerError> {
+        let to = self.layer_index(to)?;
+        if to != self.active_layer {
+            self.create_layers_commit(LayersDiff::Move {
+                from: self.active_layer,
+                to,
+            });
+        }
+        Ok(())
+    }
+
+    /// Changes the properties of the active layer.
+    pub fn set_layer_properties(&mut self, properties: LayerProperties) {
+        if properties != self.active().properties {
+            self.create_layers_commit(LayersDiff::SetProperties(self.active_layer, properties));
+        }
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn active_frame(&self) -> usize {
+        self.active_frame
+    }
+
+    pub fn set_active_frame(&mut self, index: usize) -> Result<(), FrameError> {
+        if index >= self.frames.len() {
+            return Err(FrameError::NoSuchFrame(index));
+        }
+        self.commit_staged();
+        self.activate_frame(index);
+        Ok(())
+    }
+
+    fn create_frames_commit(&mut self, diff: FramesDiff) {
+        self.commit_staged();
+        let mut change = CommitChange::Frames(diff);
+        self.apply_change(&mut change);
+        self.create_applied_commit(change);
+    }
+
+    /// Adds a frame after the active frame and activates it. The new frame has the layers of the
+    /// active frame, but no cells.
+    pub fn add_frame(&mut self) {
+        let frame = self.frames[self.active_frame].empty_like();
+        self.create_frames_commit(FramesDiff::Insert(self.active_frame + 1, Box::new(frame)));
+    }
+
+    /// Adds a copy of the active frame after it and activates the copy.
+    pub fn duplicate_frame(&mut self) {
+        let frame = self.frames[self.active_frame].clone();
+        self.create_frames_commit(FramesDiff::Insert(self.active_frame + 1, Box::new(frame)));
+    }
+
+    /// Deletes the active frame, activating the frame before it.
+    pub fn delete_frame(&mut self) -> Result<(), FrameError> {
+        if self.frames.len() == 1 {
+            return Err(FrameError::OnlyFrame);
+        }
+        let previous = self.active_frame.saturating_sub(1);
+        self.create_frames_commit(FramesDiff::Remove(self.active_frame));
+        self.activate_frame(previous);
+        self.staging_frame = self.active_frame;
+        self.staging_layer = self.active_layer;
+        Ok(())
+    }
+
+    /// Sets how long the active frame is shown, in milliseconds.
+    pub fn set_frame_duration(&mut self, duration: u32) -> Result<(), FrameError> {
+        if duration == 0 {
+            return Err(FrameError::ZeroDuration);
+        }
+        if duration != self.frames[self.active_frame].duration {
+            self.create_frames_commit(FramesDiff::SetDuration(self.active_frame, duration));
+        }
+        Ok(())
+    }
+
+    /// The frame shown after `elapsed` time of looping playback, and how long it is still shown.
+    pub fn frame_at(&self, elapsed: Duration) -> (usize, Duration) {
+        frames::frame_at(&self.frames, elapsed)
+    }
+
+    /// Sets the page of an unedited canvas, without creating a commit.
+    pub fn with_page(mut self, page: Option<CanvasRect>) -> Self {
+        self.page = page;
+        self
+    }
+
+    pub fn page(&self) -> Option<CanvasRect> {
+        self.page
+    }
+
+    /// Sets the fixed area of the artwork, or lets it grow with the cells if `None`.
+    pub fn set_page(&mut self, page: Option<CanvasRect>) {
+        if page != self.page {
+            self.commit_staged();
+            let mut change = CommitChange::Page(page);
+            self.apply_change(&mut change);
+            self.create_applied_commit(change);
+        }
+    }
+
+    /// `index` moved inside the page, if there is one.
+    pub fn clamp_to_page(&self, index: CanvasIndex) -> CanvasIndex {
+        match self.page {
+            Some(page) => page.clamp_index(index),
+            None => index,
+        }
+    }
+
+    /// Returns whether `index` is inside the page, which every index is if there is none.
+    pub fn in_page(&self, index: CanvasIndex) -> bool {
+        match self.page {
+            Some(page) => page.includes_index(index),
+            None => true,
+        }
+    }
+
+    /// The smallest rectangle containing every cell which isn't the default cell, in any layer
+    /// of any frame.
+    pub fn content_area(&self) -> CanvasRect {
+        let mut area = CanvasRect::default();
+        for frame in &self.frames {
+            for layer in &frame.layers {
+                area.include_rect(layer.canvas.content_area());
+            }
+        }
+        area
     }
 }
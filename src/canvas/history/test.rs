@@ -0,0 +1,513 @@
+use ratatui::style::Color;
+
+use crate::canvas::frames::DEFAULT_FRAME_DURATION;
+use crate::canvas::raw::{CanvasMetadata, CellContentType, WIDE_CHARACTER_CONTINUATION};
+use crate::canvas::{CanvasModification, VersionControlledCanvas};
+use crate::selections::Selection;
+
+#[test]
+fn undo_metadata() {
+    let mut canvas = VersionControlledCanvas::default();
+    let metadata = CanvasMetadata {
+        title: "Title".to_string(),
+        ..Default::default()
+    };
+    canvas.create_commit(vec![
+        CanvasModification::SetCharacter((0, 0), 'a'),
+        CanvasModification::SetMetadata(metadata.clone()),
+    ]);
+    assert_eq!(canvas.raw().metadata, metadata);
+    assert_eq!(canvas.get_current_revision(), 1);
+
+    canvas.undo();
+    assert!(canvas.raw().metadata.is_empty());
+    assert_eq!(canvas.raw().character((0, 0)), ' ');
+
+    canvas.redo();
+    assert_eq!(canvas.raw().metadata, metadata);
+    assert_eq!(canvas.raw().character((0, 0)), 'a');
+}
+
+#[test]
+fn staged_metadata() {
+    let mut canvas = VersionControlledCanvas::default();
+    let metadata = CanvasMetadata {
+        author: "Author".to_string(),
+        ..Default::default()
+    };
+    canvas.stage(CanvasModification::SetMetadata(metadata.clone()));
+    canvas.stage(CanvasModification::SetMetadata(CanvasMetadata::default()));
+    canvas.stage(CanvasModification::SetMetadata(metadata.clone()));
+    assert_eq!(canvas.raw().metadata, metadata);
+
+    canvas.commit_staged();
+    assert_eq!(canvas.get_current_revision(), 1);
+    canvas.undo();
+    assert!(canvas.raw().metadata.is_empty());
+}
+
+#[test]
+fn wide_character_continuation() {
+    let mut canvas = VersionControlledCanvas::default();
+    canvas.create_commit(vec![
+        CanvasModification::SetCharacter((0, 0), 'a'),
+        CanvasModification::SetCharacter((0, 1), 'b'),
+    ]);
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 0), '漢')]);
+    assert_eq!(canvas.raw().character((0, 0)), '漢');
+    assert_eq!(canvas.raw().character((0, 1)), WIDE_CHARACTER_CONTINUATION);
+
+    canvas.undo();
+    assert_eq!(canvas.raw().character((0, 0)), 'a');
+    assert_eq!(canvas.raw().character((0, 1)), 'b');
+}
+
+#[test]
+fn wide_character_overwritten() {
+    let mut canvas = VersionControlledCanvas::default();
+    canvas.create_commit(vec![
+        CanvasModification::SetCharacter((0, 0), '漢'),
+        CanvasModification::SetCharacter((0, 2), '字'),
+    ]);
+    // Overwriting the continuation cell removes the wide character
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 1), 'x')]);
+    assert_eq!(canvas.raw().character((0, 0)), ' ');
+    assert_eq!(canvas.raw().character((0, 1)), 'x');
+    // Overwriting the wide character removes the continuation cell
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 2), 'y')]);
+    assert_eq!(canvas.raw().character((0, 2)), 'y');
+    assert_eq!(canvas.raw().character((0, 3)), ' ');
+    // A wide character covering the start of another one removes it
+    canvas.create_commit(vec![CanvasModification::SetCharacter((1, 0), '漢')]);
+    canvas.create_commit(vec![CanvasModification::SetCharacter((1, 2), '字')]);
+    canvas.create_commit(vec![CanvasModification::SetCharacter((1, 1), '🎨')]);
+    assert_eq!(canvas.raw().character((1, 0)), ' ');
+    assert_eq!(canvas.raw().character((1, 1)), '🎨');
+    assert_eq!(canvas.raw().character((1, 2)), WIDE_CHARACTER_CONTINUATION);
+    assert_eq!(canvas.raw().character((1, 3)), ' ');
+
+    canvas.undo();
+    assert_eq!(canvas.raw().character((1, 0)), '漢');
+    assert_eq!(canvas.raw().character((1, 2)), '字');
+    assert_eq!(canvas.raw().character((1, 3)), WIDE_CHARACTER_CONTINUATION);
+}
+
+#[test]
+fn wide_character_style() {
+    let mut canvas = VersionControlledCanvas::default();
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 0), '漢')]);
+    canvas.create_commit(vec![CanvasModification::SetBgColor((0, 0), Color::Red)]);
+    assert_eq!(canvas.raw().bg((0, 1)), Color::Red);
+}
+
+#[test]
+fn yank_and_paste_wide_character() {
+    let mut canvas = VersionControlledCanvas::default();
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 0), '漢')]);
+    // Yanking only the continuation cell yanks the whole character
+    let yank = canvas
+        .raw()
+        .yank(vec![(0, 1)], CellContentType::ALL, (0, 0));
+    assert_eq!(yank.cells.len(), 2);
+
+    canvas.create_commit(vec![CanvasModification::Paste((2, 5), yank)]);
+    assert_eq!(canvas.raw().character((2, 5)), '漢');
+    assert_eq!(canvas.raw().character((2, 6)), WIDE_CHARACTER_CONTINUATION);
+}
+
+#[test]
+fn combining_characters() {
+    let mut canvas = VersionControlledCanvas::default();
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 0), 'e')]);
+    canvas.create_commit(vec![CanvasModification::AddCombiningCharacter(
+        (0, 0),
+        '\u{301}',
+    )]);
+    assert_eq!(canvas.raw().get(&(0, 0)).grapheme(), "e\u{301}");
+
+    // Setting the character replaces the whole grapheme cluster
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 0), 'a')]);
+    assert_eq!(canvas.raw().get(&(0, 0)).grapheme(), "a");
+
+    canvas.undo();
+    assert_eq!(canvas.raw().get(&(0, 0)).grapheme(), "e\u{301}");
+    canvas.undo();
+    assert_eq!(canvas.raw().get(&(0, 0)).grapheme(), "e");
+    canvas.redo();
+    canvas.redo();
+    assert_eq!(canvas.raw().get(&(0, 0)).grapheme(), "a");
+}
+
+#[test]
+fn layers() {
+    let mut canvas = VersionControlledCanvas::default();
+    canvas.create_commit(vec![
+        CanvasModification::SetCharacter((0, 0), 'a'),
+        CanvasModification::SetCharacter((0, 1), 'b'),
+    ]);
+    canvas.add_layer(Some("Text".to_string()));
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 1), 'x')]);
+    assert_eq!(canvas.active_layer(), 1);
+    assert_eq!(canvas.raw().character((0, 0)), ' ');
+    assert_eq!(canvas.flattened().character((0, 0)), 'a');
+    assert_eq!(canvas.flattened().character((0, 1)), 'x');
+
+    // Undo reverts the commit on the layer it was made on, regardless of the active layer
+    canvas.set_active_layer(0).unwrap();
+    canvas.undo();
+    assert_eq!(canvas.flattened().character((0, 1)), 'b');
+    assert_eq!(canvas.layers()[0].canvas.character((0, 1)), 'b');
+
+    canvas.undo();
+    assert_eq!(canvas.layers().len(), 1);
+    canvas.redo();
+    canvas.redo();
+    assert_eq!(canvas.layers().len(), 2);
+    assert_eq!(canvas.layers()[1].properties.name, "Text");
+    assert_eq!(canvas.flattened().character((0, 1)), 'x');
+}
+
+#[test]
+fn locked_layer() {
+    let mut canvas = VersionControlledCanvas::default();
+    let mut properties = canvas.active().properties.clone();
+    properties.locked = true;
+    canvas.set_layer_properties(properties);
+    let revision = canvas.get_current_revision();
+
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 0), 'a')]);
+    canvas.stage(CanvasModification::SetCharacter((0, 1), 'b'));
+    canvas.commit_staged();
+
+    assert_eq!(canvas.raw().character((0, 0)), ' ');
+    assert_eq!(canvas.raw().character((0, 1)), ' ');
+    assert_eq!(canvas.get_current_revision(), revision);
+    assert!(canvas.take_locked_layer_modified());
+    assert!(!canvas.take_locked_layer_modified());
+    assert!(canvas.delete_layer().is_err());
+}
+
+#[test]
+fn delete_layer() {
+    let mut canvas = VersionControlledCanvas::default();
+    assert!(canvas.delete_layer().is_err());
+    canvas.add_layer(None);
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 0), 'a')]);
+    canvas.delete_layer().unwrap();
+    assert_eq!(canvas.layers().len(), 1);
+    assert_eq!(canvas.flattened().character((0, 0)), ' ');
+
+    canvas.undo();
+    assert_eq!(canvas.active_layer(), 1);
+    assert_eq!(canvas.flattened().character((0, 0)), 'a');
+}
+
+#[test]
+fn metadata_kept_when_first_layer_changes() {
+    let mut canvas = VersionControlledCanvas::default();
+    let metadata = CanvasMetadata {
+        title: "Title".to_string(),
+        ..Default::default()
+    };
+    canvas.set_metadata(metadata.clone());
+    canvas.add_layer(None);
+    canvas.set_active_layer(0).unwrap();
+    canvas.move_layer(1).unwrap();
+    assert_eq!(canvas.metadata(), &metadata);
+    canvas.delete_layer().unwrap();
+    assert_eq!(canvas.layers().len(), 1);
+    assert_eq!(canvas.metadata(), &metadata);
+
+    let restored = VersionControlledCanvas::from_history(canvas.history());
+    assert_eq!(restored.metadata(), &metadata);
+    canvas.go_to_revision(0);
+    assert!(canvas.metadata().is_empty());
+}
+
+#[test]
+fn frames() {
+    let mut canvas = VersionControlledCanvas::default();
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 0), 'a')]);
+    canvas.duplicate_frame();
+    assert_eq!(canvas.active_frame(), 1);
+    assert_eq!(canvas.raw().character((0, 0)), 'a');
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 0), 'b')]);
+    canvas.add_frame();
+    assert_eq!(canvas.frames().len(), 3);
+    assert_eq!(canvas.raw().character((0, 0)), ' ');
+    assert_eq!(canvas.flattened_frame(0).character((0, 0)), 'a');
+    assert_eq!(canvas.flattened_frame(1).character((0, 0)), 'b');
+
+    // Undo activates the frame the commit was made on
+    canvas.undo();
+    assert_eq!(canvas.frames().len(), 2);
+    canvas.set_active_frame(0).unwrap();
+    canvas.undo();
+    assert_eq!(canvas.active_frame(), 1);
+    assert_eq!(canvas.raw().character((0, 0)), 'a');
+
+    canvas.delete_frame().unwrap();
+    assert_eq!(canvas.active_frame(), 0);
+    assert!(canvas.delete_frame().is_err());
+    canvas.undo();
+    assert_eq!(canvas.frames().len(), 2);
+    assert_eq!(canvas.active_frame(), 1);
+}
+
+#[test]
+fn metadata_kept_when_first_frame_deleted() {
+    let mut canvas = VersionControlledCanvas::default();
+    let metadata = CanvasMetadata {
+        author: "Author".to_string(),
+        ..Default::default()
+    };
+    canvas.set_metadata(metadata.clone());
+    canvas.add_frame();
+    canvas.set_active_frame(0).unwrap();
+    canvas.delete_frame().unwrap();
+    assert_eq!(canvas.frames().len(), 1);
+    assert_eq!(canvas.metadata(), &metadata);
+
+    canvas.undo();
+    assert_eq!(canvas.frames().len(), 2);
+    assert_eq!(canvas.metadata(), &metadata);
+}
+
+#[test]
+fn frame_layers() {
+    let mut canvas = VersionControlledCanvas::default();
+    canvas.add_layer(Some("Text".to_string()));
+    canvas.add_frame();
+    assert_eq!(canvas.layers().len(), 2);
+    assert_eq!(canvas.active_layer(), 1);
+    canvas.delete_layer().unwrap();
+    assert_eq!(canvas.layers().len(), 1);
+
+    // Changes to the layers of a frame don't affect the other frames
+    canvas.set_active_frame(0).unwrap();
+    assert_eq!(canvas.layers().len(), 2);
+    canvas.undo();
+    assert_eq!(canvas.active_frame(), 1);
+    assert_eq!(canvas.layers().len(), 2);
+}
+
+#[test]
+fn frame_duration() {
+    let mut canvas = VersionControlledCanvas::default();
+    assert!(canvas.set_frame_duration(0).is_err());
+    canvas.set_frame_duration(40).unwrap();
+    assert_eq!(canvas.frames()[0].duration, 40);
+    canvas.undo();
+    assert_eq!(canvas.frames()[0].duration, DEFAULT_FRAME_DURATION);
+}
+
+#[test]
+fn page() {
+    let mut canvas = VersionControlledCanvas::default();
+    canvas.create_commit(vec![
+        CanvasModification::SetCharacter((1, 2), 'a'),
+        CanvasModification::SetCharacter((3, 4), 'b'),
+        CanvasModification::SetCharacter((5, 5), ' '),
+    ]);
+    assert_eq!(canvas.clamp_to_page((-10, 10)), (-10, 10));
+    assert!(canvas.in_page((-10, 10)));
+    let content = canvas.content_area();
+    assert_eq!((content.row, content.column), (1, 2));
+    assert_eq!((content.rows, content.columns), (3, 3));
+
+    canvas.set_page(Some(content));
+    assert_eq!(canvas.clamp_to_page((-10, 10)), (1, 4));
+    assert!(canvas.in_page((3, 4)));
+    assert!(!canvas.in_page((0, 2)));
+    assert!(!canvas.in_page((3, 5)));
+    canvas.undo();
+    assert_eq!(canvas.page(), None);
+    canvas.redo();
+    assert_eq!(canvas.page(), Some(content));
+}
+
+fn set_character(canvas: &mut VersionControlledCanvas, character: char) {
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 0), character)]);
+}
+
+#[test]
+fn undo_tree_keeps_branches() {
+    let mut canvas = VersionControlledCanvas::default();
+    set_character(&mut canvas, 'a');
+    set_character(&mut canvas, 'b');
+    canvas.undo();
+    set_character(&mut canvas, 'c');
+    assert_eq!(canvas.get_current_revision(), 3);
+    assert_eq!(canvas.children(1).collect::<Vec<_>>(), vec![2, 3]);
+
+    canvas.undo();
+    canvas.redo();
+    assert_eq!(canvas.raw().character((0, 0)), 'c');
+
+    canvas.go_to_revision(2);
+    assert_eq!(canvas.raw().character((0, 0)), 'b');
+    assert_eq!(canvas.ancestors(2), vec![2, 1, 0]);
+    canvas.undo();
+    canvas.redo();
+    assert_eq!(canvas.get_current_revision(), 2);
+
+    canvas.go_to_later_revision();
+    assert_eq!(canvas.raw().character((0, 0)), 'c');
+    canvas.go_to_earlier_revision();
+    assert_eq!(canvas.raw().character((0, 0)), 'b');
+    canvas.go_to_earlier_revision();
+    assert_eq!(canvas.raw().character((0, 0)), 'a');
+    canvas.go_to_earlier_revision();
+    assert_eq!(canvas.raw().character((0, 0)), ' ');
+    assert_eq!(canvas.get_current_revision(), 0);
+}
+
+#[test]
+fn reset_hard_discards_temporary_commits() {
+    let mut canvas = VersionControlledCanvas::default();
+    set_character(&mut canvas, 'a');
+    set_character(&mut canvas, 'b');
+    canvas.undo();
+
+    set_character(&mut canvas, 'x');
+    canvas.reset_hard(1);
+    assert_eq!(canvas.raw().character((0, 0)), 'a');
+    assert_eq!(canvas.commits().count(), 2);
+    canvas.redo();
+    assert_eq!(canvas.raw().character((0, 0)), 'b');
+
+    set_character(&mut canvas, 'c');
+    canvas.undo();
+    set_character(&mut canvas, 'd');
+    assert_eq!(canvas.get_current_revision(), 4);
+    canvas.reset_hard(2);
+    assert_eq!(canvas.commits().count(), 3);
+    canvas.redo();
+    assert_eq!(canvas.raw().character((0, 0)), 'c');
+}
+
+#[test]
+fn merged_commits_are_undone_at_once() {
+    let mut canvas = VersionControlledCanvas::default();
+    set_character(&mut canvas, 'a');
+    canvas.create_commit(vec![
+        CanvasModification::SetCharacter((0, 0), 'b'),
+        CanvasModification::SetFgColor((0, 1), Color::Red),
+    ]);
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 1), 'c')]);
+    canvas.merge_commits_since(1);
+    assert_eq!(canvas.get_current_revision(), 4);
+    assert_eq!(canvas.commits().count(), 2);
+    assert_eq!(canvas.commit(4).unwrap().changed_cells(), 2);
+
+    canvas.undo();
+    assert_eq!(canvas.get_current_revision(), 1);
+    assert_eq!(canvas.raw().character((0, 0)), 'a');
+    assert_eq!(canvas.raw().character((0, 1)), ' ');
+    assert_eq!(canvas.raw().fg((0, 1)), Color::Reset);
+    canvas.redo();
+    assert_eq!(canvas.raw().character((0, 0)), 'b');
+    assert_eq!(canvas.raw().character((0, 1)), 'c');
+    assert_eq!(canvas.raw().fg((0, 1)), Color::Red);
+}
+
+#[test]
+fn checkpoints_are_not_merged() {
+    let mut canvas = VersionControlledCanvas::default();
+    set_character(&mut canvas, 'a');
+    canvas.set_checkpoint("first".to_string());
+    set_character(&mut canvas, 'b');
+    assert!(!canvas.merge_with_parent());
+    set_character(&mut canvas, 'c');
+    assert!(canvas.merge_with_parent());
+
+    // Commits with other commits made on top of them aren't merged either
+    canvas.undo();
+    set_character(&mut canvas, 'x');
+    assert!(!canvas.merge_with_parent());
+
+    let restored = VersionControlledCanvas::from_history(canvas.history());
+    assert_eq!(restored.checkpoints().get("first"), Some(&1));
+    canvas.go_to_revision(1);
+    assert_eq!(canvas.raw().character((0, 0)), 'a');
+}
+
+#[test]
+fn cells_changed_since_revision() {
+    let mut canvas = VersionControlledCanvas::default();
+    set_character(&mut canvas, 'a');
+    canvas.create_commit(vec![
+        CanvasModification::SetCharacter((0, 1), 'b'),
+        CanvasModification::SetCharacter((0, 2), 'c'),
+    ]);
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 2), ' ')]);
+    canvas.stage(CanvasModification::SetFgColor((1, 0), Color::Red));
+    let changes = canvas.changes();
+    canvas.stage(CanvasModification::SetFgColor((1, 0), Color::Red));
+    assert!(canvas.changes() > changes);
+    assert_eq!(
+        canvas.cells_changed_since(1),
+        Selection::from([(0, 1), (1, 0)])
+    );
+
+    // Across branches of the undo tree
+    canvas.commit_staged();
+    canvas.go_to_revision(1);
+    set_character(&mut canvas, 'x');
+    assert_eq!(
+        canvas.cells_changed_since(4),
+        Selection::from([(0, 0), (0, 1), (1, 0)])
+    );
+    assert_eq!(canvas.cells_changed_since(5), Selection::new());
+
+    // A temporary commit reuses the revision, but not the number of changes
+    let (revision, changes) = (canvas.get_current_revision(), canvas.changes());
+    set_character(&mut canvas, 'z');
+    canvas.reset_hard(revision);
+    let temporary_changes = canvas.changes();
+    set_character(&mut canvas, 'w');
+    assert_eq!(canvas.get_current_revision(), revision + 1);
+    assert!(canvas.changes() > temporary_changes && temporary_changes > changes);
+    canvas.reset_hard(revision);
+
+    // Adding a layer can change how any cell looks
+    canvas.add_layer(None);
+    canvas.create_commit(vec![CanvasModification::SetCharacter((2, 2), 'y')]);
+    assert_eq!(canvas.cells_changed_since(5), Selection::from([(2, 2)]));
+}
+
+#[test]
+fn malformed_history_is_invalid() {
+    let mut canvas = VersionControlledCanvas::default();
+    set_character(&mut canvas, 'a');
+    canvas.add_layer(None);
+    set_character(&mut canvas, 'b');
+    let history = canvas.history();
+    assert!(history.is_valid());
+
+    let mut no_frames = history.clone();
+    no_frames.frames.clear();
+    assert!(!no_frames.is_valid());
+
+    let mut no_layers = history.clone();
+    no_layers.frames[0].layers.clear();
+    assert!(!no_layers.is_valid());
+
+    // The second layer is only added by the second commit
+    let mut layer_out_of_range = history.clone();
+    layer_out_of_range
+        .commits
+        .retain(|commit| commit.revision != 2);
+    layer_out_of_range.commits[1].parent = 1;
+    layer_out_of_range.revision = 1;
+    layer_out_of_range.redo_children.clear();
+    assert!(!layer_out_of_range.is_valid());
+
+    let mut missing_parent = history.clone();
+    missing_parent.commits[1].parent = 7;
+    assert!(!missing_parent.is_valid());
+
+    let mut missing_revision = history;
+    missing_revision.revision = 42;
+    assert!(!missing_revision.is_valid());
+}
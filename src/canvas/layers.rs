@@ -0,0 +1,131 @@
+use std::borrow::Cow;
+
+use derive_more::{Display, Error};
+use serde::{Deserialize, Serialize};
+
+use super::raw::composite::OPACITY_OPAQUE;
+use super::raw::Canvas;
+
+#[cfg(test)]
+mod test;
+
+/// Properties of a layer, which don't affect its cells.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayerProperties {
+    pub name: String,
+    /// Hidden layers are neither shown nor exported.
+    pub visible: bool,
+    /// Locked layers can't be drawn on.
+    pub locked: bool,
+    /// Opacity in percent, see `Canvas::draw_layer`.
+    pub opacity: u8,
+}
+
+impl Default for LayerProperties {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            visible: true,
+            locked: false,
+            opacity: OPACITY_OPAQUE,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CanvasLayer {
+    pub properties: LayerProperties,
+    pub canvas: Canvas,
+}
+
+impl CanvasLayer {
+    pub fn new(name: impl Into<String>, canvas: Canvas) -> Self {
+        Self {
+            properties: LayerProperties {
+                name: name.into(),
+                ..Default::default()
+            },
+            canvas,
+        }
+    }
+}
+
+#[derive(Debug, Display, Error, PartialEq)]
+pub enum LayerError {
+    #[display("There is no layer {}", _0 + 1)]
+    NoSuchLayer(#[error(ignore)] usize),
+    #[display("Layer {_0:?} is locked")]
+    Locked(#[error(ignore)] String),
+    #[display("Can't delete the only layer")]
+    OnlyLayer,
+}
+
+/// A change to the stack of layers, other than to the cells of a layer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum LayersDiff {
+    Insert(usize, Box<CanvasLayer>),
+    Remove(usize),
+    Move { from: usize, to: usize },
+    SetProperties(usize, LayerProperties),
+}
+
+impl LayersDiff {
+    /// Applies the diff to `layers` and reverses the diff in place, like `Canvas::apply_diff`.
+    /// Returns the index of the layer the diff affected.
+    pub fn apply(&mut self, layers: &mut Vec<CanvasLayer>) -> usize {
+        match self {
+            Self::Insert(index, layer) => {
+                let index = *index;
+                layers.insert(index, std::mem::take(layer.as_mut()));
+                *self = Self::Remove(index);
+                index
+            }
+            Self::Remove(index) => {
+                let index = *index;
+                *self = Self::Insert(index, Box::new(layers.remove(index)));
+                index
+            }
+            Self::Move { from, to } => {
+                let (from, to) = (*from, *to);
+                let layer = layers.remove(from);
+                layers.insert(to, layer);
+                *self = Self::Move { from: to, to: from };
+                to
+            }
+            Self::SetProperties(index, properties) => {
+                std::mem::swap(&mut layers[*index].properties, properties);
+                *index
+            }
+        }
+    }
+}
+
+/// Draws the visible layers on top of each other, from the first to the last one. Cells left at
+/// the default cell are transparent. The metadata is taken from the first layer.
+///
+/// A single opaque layer is returned as it is.
+pub fn flatten(layers: &[CanvasLayer]) -> Cow<'_, Canvas> {
+    if let [layer] = layers {
+        if layer.properties.visible && layer.properties.opacity == OPACITY_OPAQUE {
+            return Cow::Borrowed(&layer.canvas);
+        }
+    }
+    let mut result = Canvas::default();
+    if let Some(first) = layers.first() {
+        result.metadata = first.canvas.metadata.clone();
+    }
+    for layer in layers.iter().filter(|layer| layer.properties.visible) {
+        result.draw_layer(&layer.canvas, layer.properties.opacity);
+    }
+    Cow::Owned(result)
+}
+
+/// Name for a new layer, which isn't used by any of `layers`.
+pub fn unused_layer_name(layers: &[CanvasLayer]) -> String {
+    (layers.len() + 1..)
+        .map(|number| format!("Layer {number}"))
+        .find(|name| layers.iter().all(|layer| layer.properties.name != *name))
+        .unwrap()
+}
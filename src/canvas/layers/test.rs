@@ -0,0 +1,69 @@
+use std::borrow::Cow;
+
+use crate::canvas::layers::{flatten, unused_layer_name, CanvasLayer, LayersDiff};
+use crate::canvas::raw::Canvas;
+
+fn layer(name: &str, cells: &[((i16, i16), char)]) -> CanvasLayer {
+    let mut canvas = Canvas::default();
+    for (index, character) in cells {
+        canvas.set_character(*index, *character);
+    }
+    CanvasLayer::new(name, canvas)
+}
+
+fn names(layers: &[CanvasLayer]) -> Vec<&str> {
+    layers
+        .iter()
+        .map(|layer| layer.properties.name.as_str())
+        .collect()
+}
+
+#[test]
+fn flatten_visible_layers() {
+    let mut layers = vec![
+        layer("Background", &[((0, 0), 'a'), ((0, 1), 'b')]),
+        layer("Text", &[((0, 1), 'x')]),
+        layer("Hidden", &[((0, 0), 'h')]),
+    ];
+    layers[0].canvas.metadata.title = "Title".to_string();
+    layers[2].properties.visible = false;
+
+    let flattened = flatten(&layers);
+
+    assert_eq!(flattened.character((0, 0)), 'a');
+    assert_eq!(flattened.character((0, 1)), 'x');
+    assert_eq!(flattened.metadata.title, "Title");
+}
+
+#[test]
+fn flatten_single_layer() {
+    let layers = vec![layer("Background", &[((0, 0), 'a')])];
+
+    assert!(matches!(flatten(&layers), Cow::Borrowed(_)));
+}
+
+#[test]
+fn layers_diff_reverses() {
+    let mut layers = vec![layer("A", &[]), layer("B", &[])];
+
+    let mut diff = LayersDiff::Insert(1, Box::new(layer("C", &[])));
+    assert_eq!(diff.apply(&mut layers), 1);
+    assert_eq!(names(&layers), ["A", "C", "B"]);
+    diff.apply(&mut layers);
+    assert_eq!(names(&layers), ["A", "B"]);
+    diff.apply(&mut layers);
+    assert_eq!(names(&layers), ["A", "C", "B"]);
+
+    let mut diff = LayersDiff::Move { from: 0, to: 2 };
+    assert_eq!(diff.apply(&mut layers), 2);
+    assert_eq!(names(&layers), ["C", "B", "A"]);
+    diff.apply(&mut layers);
+    assert_eq!(names(&layers), ["A", "C", "B"]);
+}
+
+#[test]
+fn unused_name() {
+    let layers = vec![layer("Layer 1", &[]), layer("Layer 3", &[])];
+
+    assert_eq!(unused_layer_name(&layers), "Layer 4");
+}
@@ -2,6 +2,8 @@ use crate::ProgramState;
 use bitflags::bitflags;
 use keystrokes_parsing::Presetable;
 use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
 
 use ratatui::style::{Color, Modifier};
 
@@ -13,14 +15,25 @@ use super::rect::CanvasRect;
 pub mod ansi_export;
 pub mod ansi_import;
 pub mod cell_map;
+pub mod composite;
 pub mod continuous_region;
+pub mod html_export;
+pub mod image_import;
+pub mod irc_export;
+pub mod irc_import;
 pub mod iter;
+pub mod legacy_ansi_import;
 pub mod operations;
 pub mod paste;
+pub mod png_export;
+pub mod quantize;
 pub mod rendering;
+pub mod svg_export;
+pub mod terminal_ansi_import;
 pub mod transform;
 pub mod yank;
 
+use cell_map::BTreeCellMap;
 use cell_map::CellMap;
 use continuous_region::MatchValue;
 
@@ -50,9 +63,13 @@ impl Default for CellContentType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CanvasCell {
     pub character: char,
+    /// Characters following `character` in the same grapheme cluster, like combining accents,
+    /// variation selectors and characters joined by zero width joiners.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub combining: String,
     pub fg: Color,
     pub bg: Color,
     pub modifiers: Modifier,
@@ -67,18 +84,71 @@ impl CanvasCell {
         cell
     }
 
+    /// The grapheme cluster of the cell, which is `character` followed by `combining`.
+    pub fn grapheme(&self) -> String {
+        let mut grapheme = String::from(self.character);
+        grapheme.push_str(&self.combining);
+        grapheme
+    }
+
     fn has_sgr_effects(&self) -> bool {
         self.fg != Color::Reset || self.bg != Color::Reset || self.modifiers != Modifier::empty()
     }
 }
 
+/// A grapheme cluster, which is the content of a cell: a character followed by the characters
+/// combining with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grapheme(String);
+
+impl Grapheme {
+    /// Returns whether `character` continues the grapheme cluster, like a combining accent does.
+    pub fn continued_by(&self, character: char) -> bool {
+        let mut grapheme = self.0.clone();
+        grapheme.push(character);
+        grapheme.graphemes(true).count() == 1
+    }
+    pub fn push(&mut self, character: char) {
+        self.0.push(character);
+    }
+    pub fn character(&self) -> char {
+        self.0.chars().next().unwrap()
+    }
+    /// The characters after the first one.
+    pub fn combining(&self) -> &str {
+        &self.0[self.character().len_utf8()..]
+    }
+    /// Returns whether the grapheme cluster of `cell` is this one, so `e` doesn't match an `e`
+    /// with a combining accent.
+    pub fn matches(&self, cell: &CanvasCell) -> bool {
+        cell.grapheme() == self.0
+    }
+}
+
+impl From<char> for Grapheme {
+    fn from(character: char) -> Self {
+        Self(String::from(character))
+    }
+}
+
+/// Character of the cell to the right of a wide character, which is covered by the wide
+/// character. The continuation cell always has the same colors and modifiers as the wide
+/// character.
+pub const WIDE_CHARACTER_CONTINUATION: char = '\u{0}';
+
+/// Returns whether `character` occupies two columns, like most CJK characters and emojis.
+pub fn is_wide(character: char) -> bool {
+    character != WIDE_CHARACTER_CONTINUATION && character.width() == Some(2)
+}
+
 const DEFAULT_CHARACTER: char = ' ';
 const DEFAULT_FG: Color = Color::Reset;
 const DEFAULT_BG: Color = Color::Reset;
 const DEFAULT_MODIFIERS: Modifier = Modifier::empty();
 
-const DEFAULT_CELL: CanvasCell = CanvasCell {
+static DEFAULT_CELL: CanvasCell = CanvasCell {
     character: DEFAULT_CHARACTER,
+    combining: String::new(),
     fg: DEFAULT_FG,
     bg: DEFAULT_BG,
     modifiers: DEFAULT_MODIFIERS,
@@ -96,11 +166,70 @@ impl Default for CanvasCell {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(from = "CanvasSerde", into = "CanvasSerde")]
 pub struct Canvas {
     area: CanvasRect,
     // cells: HashMap<CanvasIndex, CanvasCell>,
     cells: CellMap,
+    pub metadata: CanvasMetadata,
+}
+
+/// Descriptive information about the artwork, which doesn't affect how it looks.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CanvasMetadata {
+    pub title: String,
+    pub author: String,
+    pub group: String,
+    /// Free-form comment lines.
+    pub comments: Vec<String>,
+    /// Name of the font the artwork is meant to be displayed with, for example `IBM VGA`.
+    pub font: String,
+}
+
+impl CanvasMetadata {
+    pub const fn new() -> Self {
+        Self {
+            title: String::new(),
+            author: String::new(),
+            group: String::new(),
+            comments: Vec::new(),
+            font: String::new(),
+        }
+    }
+
+    /// Returns true if no metadata is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::new()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CanvasSerde {
+    cells: BTreeCellMap,
+    #[serde(default)]
+    metadata: CanvasMetadata,
+}
+
+impl From<CanvasSerde> for Canvas {
+    fn from(serde: CanvasSerde) -> Self {
+        let mut canvas = Canvas::new();
+        for (index, cell) in serde.cells {
+            canvas._set(index, cell);
+        }
+        canvas.metadata = serde.metadata;
+        canvas
+    }
+}
+
+impl From<Canvas> for CanvasSerde {
+    fn from(canvas: Canvas) -> Self {
+        Self {
+            cells: canvas.cells.into_iter().collect(),
+            metadata: canvas.metadata,
+        }
+    }
 }
 
 pub const EMPTY_CANVAS: Canvas = Canvas::new();
@@ -123,6 +252,42 @@ impl<'a> Canvas {
         self.area.include_index(index);
     }
 
+    /// The smallest rectangle containing every cell which isn't the default cell.
+    pub fn content_area(&self) -> CanvasRect {
+        CanvasRect::from_cells(
+            self.cells
+                .iter()
+                .filter(|(_index, cell)| **cell != DEFAULT_CELL)
+                .map(|(index, _cell)| *index),
+        )
+    }
+
+    /// Fills the cells of `area` that aren't set with spaces, so that they are exported too.
+    pub fn pad_to(&mut self, area: CanvasRect) {
+        for index in area.indices_contained() {
+            if self.cells.get(&index).is_none() {
+                self._set(index, DEFAULT_CELL.clone());
+            }
+        }
+    }
+
+    /// Removes the cells outside of `area` and pads the canvas to it, so that exactly `area` is
+    /// exported. Wide characters cut in half by the edge of `area` are replaced by spaces.
+    pub fn fit_to(&mut self, area: CanvasRect) {
+        self.cells
+            .retain(|index, _cell| area.includes_index(*index));
+        self.area = area;
+        for row in area.first_row()..=area.last_row() {
+            if self.character((row, area.first_column())) == WIDE_CHARACTER_CONTINUATION {
+                self.set_character((row, area.first_column()), ' ');
+            }
+            if is_wide(self.character((row, area.last_column()))) {
+                self.set_character((row, area.last_column()), ' ');
+            }
+        }
+        self.pad_to(area);
+    }
+
     /// Removes empty cells and reduces area to actual size.
     pub fn clean(&mut self) {
         self.cells.retain(|_index, cell| *cell != DEFAULT_CELL);
@@ -134,6 +299,7 @@ impl<'a> Canvas {
         Self {
             area: CanvasRect::new(),
             cells: CellMap::new(),
+            metadata: CanvasMetadata::new(),
         }
     }
 
@@ -151,6 +317,12 @@ impl<'a> Canvas {
             None => DEFAULT_CHARACTER,
         }
     }
+    pub fn combining(&self, index: CanvasIndex) -> &str {
+        match self.cells.get(&index) {
+            Some(cell) => &cell.combining,
+            None => "",
+        }
+    }
     pub fn fg(&self, index: CanvasIndex) -> Color {
         match self.cells.get(&index) {
             Some(cell) => cell.fg,
@@ -189,10 +361,29 @@ impl<'a> Canvas {
         (self.area.rows, self.area.columns)
     }
 
+    /// Sets the character of the cell at `index`, removing any characters combined with the old
+    /// one.
     pub fn set_character(&mut self, index: CanvasIndex, character: char) -> &mut Self {
-        self.get_mut(&index).character = character;
+        let cell = self.get_mut(&index);
+        cell.character = character;
+        cell.combining.clear();
+        self
+    }
+    pub fn set_combining(&mut self, index: CanvasIndex, combining: String) -> &mut Self {
+        self.get_mut(&index).combining = combining;
         self
     }
+    pub fn add_combining_character(&mut self, index: CanvasIndex, character: char) -> &mut Self {
+        self.get_mut(&index).combining.push(character);
+        self
+    }
+    /// Returns whether `character` continues the grapheme cluster of the cell at `index`, like
+    /// a combining accent or a character following a zero width joiner does.
+    pub fn continues_grapheme(&self, index: CanvasIndex, character: char) -> bool {
+        let cell = self.get(&index);
+        cell.character != WIDE_CHARACTER_CONTINUATION
+            && Grapheme(cell.grapheme()).continued_by(character)
+    }
     pub fn set_fg(&mut self, index: CanvasIndex, color: Color) -> &mut Self {
         self.get_mut(&index).fg = color;
         self
@@ -212,7 +403,7 @@ impl<'a> Canvas {
         self
     }
     pub fn set_cell(&mut self, index: CanvasIndex, cell: CanvasCell) -> &mut Self {
-        self.cells.insert(index, cell);
+        self._set(index, cell);
         self
     }
     pub fn remove_modifier(&mut self, index: CanvasIndex, modifier: Modifier) -> &mut Self {
@@ -223,6 +414,30 @@ impl<'a> Canvas {
         self.get_mut(&index).modifiers = modifiers;
         self
     }
+    /// Covers the cell to the right of `index` with a continuation cell if `index` contains a
+    /// wide character. Returns the number of columns the character occupies.
+    pub fn pair_wide_character(&mut self, index: CanvasIndex) -> i16 {
+        let cell = self.cell(index);
+        if !is_wide(cell.character) {
+            return 1;
+        }
+        let continuation = CanvasCell {
+            character: WIDE_CHARACTER_CONTINUATION,
+            combining: String::new(),
+            ..cell
+        };
+        self._set((index.0, index.1 + 1), continuation);
+        2
+    }
+    /// Moves `index` off a continuation cell, onto the wide character covering it, or past it if
+    /// `rightwards` is set. Other indices are returned as they are.
+    pub fn skip_continuation(&self, index: CanvasIndex, rightwards: bool) -> CanvasIndex {
+        match self.character(index) {
+            WIDE_CHARACTER_CONTINUATION if rightwards => (index.0, index.1 + 1),
+            WIDE_CHARACTER_CONTINUATION => (index.0, index.1 - 1),
+            _ => index,
+        }
+    }
     pub fn cells_matching(&self, match_cell: impl MatchValue<CanvasCell>) -> Selection {
         let mut result = Selection::new();
         for (index, cell) in &self.cells {
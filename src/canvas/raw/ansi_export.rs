@@ -1,4 +1,7 @@
+use std::borrow::Borrow;
+
 use crossterm::{
+    cursor::MoveTo,
     style::{
         Attribute as CAttribute, Color as CColor, ResetColor, SetAttribute, SetBackgroundColor,
         SetForegroundColor,
@@ -8,9 +11,13 @@ use crossterm::{
 use derive_more::{Display, Error};
 use ratatui::style::{Color, Modifier};
 
+use crate::file_formats::irc::IrcPalette;
+use crate::file_formats::upaint::UpaintFile;
 use crate::file_formats::FileFormat;
 
-use super::{Canvas, CanvasCell, CanvasIndex};
+use crate::canvas::rect::CanvasRect;
+
+use super::{Canvas, CanvasCell, CanvasIndex, WIDE_CHARACTER_CONTINUATION};
 
 #[cfg(test)]
 mod test;
@@ -69,6 +76,7 @@ impl Canvas {
         };
         let default_cell = CanvasCell {
             character: ' ',
+            combining: String::new(),
             fg: Color::Reset,
             bg: Color::Reset,
             modifiers: Modifier::empty(),
@@ -117,7 +125,11 @@ impl Canvas {
                 apply_modifiers(cell, &mut result)?;
             }
 
-            result.push(cell.character);
+            // The wide character already covers its continuation cell
+            if cell.character != WIDE_CHARACTER_CONTINUATION {
+                result.push(cell.character);
+                result.push_str(&cell.combining);
+            }
             previous_cell = cell;
             (previous_row, previous_column) = (row, column);
         }
@@ -131,6 +143,35 @@ impl Canvas {
     pub fn export_ansi(&self) -> anyhow::Result<String> {
         self.to_ansi(true)
     }
+
+    /// Exports `frames` as one ANSI stream, which moves the cursor home before each frame after
+    /// the first one, to show them one after another when printed to a terminal.
+    ///
+    /// Every frame is padded with spaces to the area of all frames, so that it covers the previous
+    /// frame completely.
+    pub fn export_ansi_animation(frames: &[impl Borrow<Canvas>]) -> anyhow::Result<String> {
+        let area = Self::animation_area(frames);
+        let mut result = String::new();
+        for (index, frame) in frames.iter().enumerate() {
+            let mut frame = frame.borrow().clone();
+            frame.pad_to(area);
+            if index > 0 {
+                MoveTo(0, 0).write_ansi(&mut result)?;
+            }
+            result.push_str(&frame.to_ansi(true)?);
+        }
+        Ok(result)
+    }
+
+    /// The area covered by any of `frames`.
+    pub fn animation_area(frames: &[impl Borrow<Canvas>]) -> CanvasRect {
+        let mut frames = frames.iter().map(|frame| frame.borrow().area());
+        let mut area = frames.next().unwrap_or_default();
+        for frame in frames {
+            area.include_rect(frame);
+        }
+        area
+    }
 }
 
 #[derive(Debug, Display, Error)]
@@ -180,6 +221,11 @@ impl Canvas {
         match format {
             FileFormat::Ansi => self.export_ansi(),
             FileFormat::Txt => Ok(self.export_txt_preserve()?),
+            FileFormat::Irc => Ok(self.export_irc(IrcPalette::default(), false)?),
+            FileFormat::Html | FileFormat::Svg | FileFormat::Png => Err(anyhow::anyhow!(
+                "Exporting {format:?} requires a color theme"
+            )),
+            FileFormat::Upaint => UpaintFile::from_canvas(self.clone()).to_ron(),
         }
     }
 
@@ -187,6 +233,11 @@ impl Canvas {
         match format {
             FileFormat::Ansi => self.export_ansi(),
             FileFormat::Txt => self.export_txt_decolorize(),
+            FileFormat::Irc => Ok(self.export_irc(IrcPalette::default(), true)?),
+            FileFormat::Html | FileFormat::Svg | FileFormat::Png => Err(anyhow::anyhow!(
+                "Exporting {format:?} requires a color theme"
+            )),
+            FileFormat::Upaint => UpaintFile::from_canvas(self.clone()).to_ron(),
         }
     }
 }
@@ -4,7 +4,7 @@ use crate::canvas::raw::{ansi_export::TxtExportError, Canvas, CanvasCell};
 
 const RESET_ALL: &str = "\u{1b}[0m";
 const RESET_FG: &str = "\u{1b}[39m";
-const _RESET_BG: &str = "\u{1b}[49m";
+const RESET_BG: &str = "\u{1b}[49m";
 
 #[allow(non_snake_case)]
 fn FG_INDEXED(index: u8) -> String {
@@ -360,3 +360,18 @@ fn txt_decolorize() {
 
     assert_eq!(txt, format!("   a\nb\n"));
 }
+
+#[test]
+fn animation() {
+    let mut first = Canvas::default();
+    first.set_character((0, 0), 'a');
+    first.set_character((0, 1), 'b');
+    let mut second = Canvas::default();
+    second.set_character((0, 0), 'c');
+    // The second frame is padded to cover the 'b' of the first frame
+    let expected = format!("{RESET_ALL}ab\n\u{1b}[1;1H{RESET_ALL}c \n");
+    assert_eq!(
+        Canvas::export_ansi_animation(&[first, second]).unwrap(),
+        expected
+    );
+}
@@ -3,8 +3,9 @@ use derive_more::{Display, Error};
 use ratatui::style::{Color, Modifier};
 
 use crate::canvas::raw::CanvasIndex;
+use crate::file_formats::sauce::Sauce;
 
-use super::Canvas;
+use super::{is_wide, Canvas};
 
 #[cfg(test)]
 mod test;
@@ -56,217 +57,261 @@ pub enum SgrSetColorError {
     InvalidParameterSecondValue(#[error(ignore)] u64),
 }
 
-impl Canvas {
-    pub fn from_ansi_or_txt(
-        ansi: String,
-        allow_sgr_sequences: bool,
-    ) -> Result<Self, AnsiImportError>
-    where
-        Self: Sized,
-    {
-        fn escape_sequence(
-            index: CanvasIndex,
-            characters: &mut std::iter::Enumerate<std::str::Chars>,
-            fg_color: &mut Color,
-            bg_color: &mut Color,
-            modifiers: &mut Modifier,
-        ) -> Result<(), AnsiImportError> {
-            fn sgr_set_color(
-                values: &mut impl Iterator<Item = u64>,
-            ) -> Result<Color, SgrSetColorError> {
-                let Some(second_value) = values.next() else {
-                    return Err(SgrSetColorError::MissingParameterSecondValue);
-                };
-                match second_value {
-                    5 => {
-                        let Some(index) = values.next() else {
-                            return Err(SgrSetColorError::MissingParameterIndex);
-                        };
-                        let Ok(index) = u8::try_from(index) else {
-                            return Err(SgrSetColorError::InvalidParameterNotU8(index));
-                        };
-                        return Ok(Color::Indexed(index));
-                    }
-                    2 => {
-                        let (Some(r), Some(g), Some(b)) =
-                            (values.next(), values.next(), values.next())
-                        else {
-                            return Err(SgrSetColorError::MissingParametersRGB);
-                        };
-                        let Ok(r) = u8::try_from(r) else {
-                            return Err(SgrSetColorError::InvalidParameterNotU8(r));
-                        };
-                        let Ok(g) = u8::try_from(g) else {
-                            return Err(SgrSetColorError::InvalidParameterNotU8(g));
-                        };
-                        let Ok(b) = u8::try_from(b) else {
-                            return Err(SgrSetColorError::InvalidParameterNotU8(b));
-                        };
-                        return Ok(Color::Rgb(r, g, b));
-                    }
-                    invalid => {
-                        return Err(SgrSetColorError::InvalidParameterSecondValue(invalid));
+/// A CSI sequence, `ESC [ <parameters> <final_character>`.
+pub(super) struct CsiSequence {
+    pub parameters: String,
+    pub final_character: char,
+}
+
+impl CsiSequence {
+    /// Reads the rest of an escape sequence after `ESC`, which must be a CSI sequence with only
+    /// digits and `;` as parameters.
+    pub fn read(
+        index: CanvasIndex,
+        characters: &mut impl Iterator<Item = char>,
+    ) -> Result<Self, AnsiImportError> {
+        match characters.next() {
+            // Only allow CSI sequences
+            Some('[') => (),
+            Some(ch) => {
+                let sequence = format!("\x1b{ch}...");
+                return Err(AnsiImportError::BadEscapeSequence { index, sequence });
+            }
+            None => return Err(AnsiImportError::UnfinishedEscapeSequence(index)),
+        }
+        let mut parameters = String::new();
+        loop {
+            match characters.next() {
+                Some(character) => {
+                    if ('@'..='~').contains(&character) {
+                        // CSI sequence terminated
+                        return Ok(Self {
+                            parameters,
+                            final_character: character,
+                        });
+                    } else if character.is_digit(10) || character == ';' {
+                        // Add legal character to `parameters`
+                        parameters.push(character);
+                    } else {
+                        let sequence = format!("\x1b[{parameters}{character}...");
+                        return Err(AnsiImportError::BadEscapeSequence { index, sequence });
                     }
+                }
+                None => return Err(AnsiImportError::UnfinishedEscapeSequence(index)),
+            }
+        }
+    }
+
+    pub fn full_sequence(&self) -> String {
+        format!("\x1b[{}{}", self.parameters, self.final_character)
+    }
+
+    /// Returns the numeric parameters, where empty parameters are 0.
+    pub fn values(&self) -> impl Iterator<Item = u64> + '_ {
+        self.parameters.split(';').map(|v| {
+            if v == "" {
+                // According to wikipedia (https://en.wikipedia.org/wiki/ANSI_escape_code):
+                // If no codes are given, `CSI m` is treated as `CSI 0 m` (reset / normal)
+                0
+            } else {
+                v.parse::<u64>().unwrap_or_else(|_| {
+                    // All characters should be digits or ';' at this point
+                    unreachable!(
+                        "Could not parse value {v:?} of CSI sequence {:?}",
+                        self.parameters
+                    );
+                })
+            }
+        })
+    }
+
+    pub fn bad_escape_sequence(&self, index: CanvasIndex) -> AnsiImportError {
+        AnsiImportError::BadEscapeSequence {
+            index,
+            sequence: self.full_sequence(),
+        }
+    }
+}
+
+/// Applies the SGR sequence `sequence` to the current style.
+pub(super) fn apply_sgr(
+    index: CanvasIndex,
+    sequence: &CsiSequence,
+    fg_color: &mut Color,
+    bg_color: &mut Color,
+    modifiers: &mut Modifier,
+) -> Result<(), AnsiImportError> {
+    fn sgr_set_color(values: &mut impl Iterator<Item = u64>) -> Result<Color, SgrSetColorError> {
+        let Some(second_value) = values.next() else {
+            return Err(SgrSetColorError::MissingParameterSecondValue);
+        };
+        match second_value {
+            5 => {
+                let Some(index) = values.next() else {
+                    return Err(SgrSetColorError::MissingParameterIndex);
+                };
+                let Ok(index) = u8::try_from(index) else {
+                    return Err(SgrSetColorError::InvalidParameterNotU8(index));
+                };
+                return Ok(Color::Indexed(index));
+            }
+            2 => {
+                let (Some(r), Some(g), Some(b)) = (values.next(), values.next(), values.next())
+                else {
+                    return Err(SgrSetColorError::MissingParametersRGB);
+                };
+                let Ok(r) = u8::try_from(r) else {
+                    return Err(SgrSetColorError::InvalidParameterNotU8(r));
+                };
+                let Ok(g) = u8::try_from(g) else {
+                    return Err(SgrSetColorError::InvalidParameterNotU8(g));
+                };
+                let Ok(b) = u8::try_from(b) else {
+                    return Err(SgrSetColorError::InvalidParameterNotU8(b));
                 };
+                return Ok(Color::Rgb(r, g, b));
             }
+            invalid => {
+                return Err(SgrSetColorError::InvalidParameterSecondValue(invalid));
+            }
+        };
+    }
 
-            let result = characters.next();
-            match result {
-                // Only allow CSI sequences
-                Some((_i, '[')) => (),
-                Some((_i, ch)) => {
-                    let sequence = format!("\x1b{ch}...");
-                    return Err(AnsiImportError::BadEscapeSequence { index, sequence });
-                }
-                None => return Err(AnsiImportError::UnfinishedEscapeSequence(index)),
+    let mut values_parsed = sequence.values();
+    while let Some(attribute) = values_parsed.next() {
+        match attribute {
+            0 => {
+                *fg_color = Color::Reset;
+                *bg_color = Color::Reset;
+                *modifiers = Modifier::default();
             }
-            let mut sgr_sequence = String::new();
-            let termination_character;
-            loop {
-                let result = characters.next();
-                match result {
-                    Some((_i, character)) => {
-                        if character == 'm' {
-                            // CSI sequence terminated
-                            termination_character = character;
-                            break;
-                        } else if character.is_digit(10) || character == ';' {
-                            // Add legal character to `sgr_sequence`
-                            sgr_sequence.push(character);
-                        } else {
-                            let sequence = format!("\x1b[{sgr_sequence}{character}...");
-                            return Err(AnsiImportError::BadEscapeSequence { index, sequence });
-                        }
+            1 => *modifiers |= Modifier::BOLD,
+            2 => *modifiers |= Modifier::DIM,
+            3 => *modifiers |= Modifier::ITALIC,
+            4 => *modifiers |= Modifier::UNDERLINED,
+            5 => *modifiers |= Modifier::SLOW_BLINK,
+            6 => *modifiers |= Modifier::RAPID_BLINK,
+            7 => *modifiers |= Modifier::REVERSED,
+            8 => *modifiers |= Modifier::HIDDEN,
+            9 => *modifiers |= Modifier::CROSSED_OUT,
+            22 => modifiers.remove(Modifier::BOLD | Modifier::DIM),
+            23 => modifiers.remove(Modifier::ITALIC),
+            24 => modifiers.remove(Modifier::UNDERLINED),
+            25 => modifiers.remove(Modifier::SLOW_BLINK | Modifier::RAPID_BLINK),
+            27 => modifiers.remove(Modifier::REVERSED),
+            28 => modifiers.remove(Modifier::HIDDEN),
+            29 => modifiers.remove(Modifier::CROSSED_OUT),
+            30 => *fg_color = Color::Black,
+            31 => *fg_color = Color::Red,
+            32 => *fg_color = Color::Green,
+            33 => *fg_color = Color::Yellow,
+            34 => *fg_color = Color::Blue,
+            35 => *fg_color = Color::Magenta,
+            36 => *fg_color = Color::Cyan,
+            37 => *fg_color = Color::Gray,
+            40 => *bg_color = Color::Black,
+            41 => *bg_color = Color::Red,
+            42 => *bg_color = Color::Green,
+            43 => *bg_color = Color::Yellow,
+            44 => *bg_color = Color::Blue,
+            45 => *bg_color = Color::Magenta,
+            46 => *bg_color = Color::Cyan,
+            47 => *bg_color = Color::Gray,
+            90 => *fg_color = Color::DarkGray,
+            91 => *fg_color = Color::LightRed,
+            92 => *fg_color = Color::LightGreen,
+            93 => *fg_color = Color::LightYellow,
+            94 => *fg_color = Color::LightBlue,
+            95 => *fg_color = Color::LightMagenta,
+            96 => *fg_color = Color::LightCyan,
+            97 => *fg_color = Color::White,
+            100 => *bg_color = Color::DarkGray,
+            101 => *bg_color = Color::LightRed,
+            102 => *bg_color = Color::LightGreen,
+            103 => *bg_color = Color::LightYellow,
+            104 => *bg_color = Color::LightBlue,
+            105 => *bg_color = Color::LightMagenta,
+            106 => *bg_color = Color::LightCyan,
+            107 => *bg_color = Color::White,
+            38 => {
+                *fg_color = match sgr_set_color(&mut values_parsed) {
+                    Ok(new) => new,
+                    Err(source) => {
+                        return Err(AnsiImportError::BadSgrSetColorParameters {
+                            index,
+                            sequence: sequence.full_sequence(),
+                            source,
+                        });
                     }
-                    None => return Err(AnsiImportError::UnfinishedEscapeSequence(index)),
-                }
+                };
             }
-            let mut values_parsed = sgr_sequence.split(';').map(|v| {
-                if v == "" {
-                    // According to wikipedia (https://en.wikipedia.org/wiki/ANSI_escape_code):
-                    // If no codes are given, `CSI m` is treated as `CSI 0 m` (reset / normal)
-                    0
-                } else {
-                    v.parse::<u64>().unwrap_or_else(|_| {
-                        // All characters should be digits or ';' at this point
-                        unreachable!(
-                            "Could not parse first value {v:?} of SGR sequence {sgr_sequence:?}"
-                        );
-                    })
-                }
-            });
-            let full_sequence = || format!("\x1b[{sgr_sequence}{termination_character}");
-            while let Some(attribute) = values_parsed.next() {
-                match attribute {
-                    0 => {
-                        *fg_color = Color::Reset;
-                        *bg_color = Color::Reset;
-                        *modifiers = Modifier::default();
-                    }
-                    1 => *modifiers |= Modifier::BOLD,
-                    2 => *modifiers |= Modifier::DIM,
-                    3 => *modifiers |= Modifier::ITALIC,
-                    4 => *modifiers |= Modifier::UNDERLINED,
-                    5 => *modifiers |= Modifier::SLOW_BLINK,
-                    6 => *modifiers |= Modifier::RAPID_BLINK,
-                    7 => *modifiers |= Modifier::REVERSED,
-                    8 => *modifiers |= Modifier::HIDDEN,
-                    9 => *modifiers |= Modifier::CROSSED_OUT,
-                    30 => *fg_color = Color::Black,
-                    31 => *fg_color = Color::Red,
-                    32 => *fg_color = Color::Green,
-                    33 => *fg_color = Color::Yellow,
-                    34 => *fg_color = Color::Blue,
-                    35 => *fg_color = Color::Magenta,
-                    36 => *fg_color = Color::Cyan,
-                    37 => *fg_color = Color::Gray,
-                    40 => *bg_color = Color::Black,
-                    41 => *bg_color = Color::Red,
-                    42 => *bg_color = Color::Green,
-                    43 => *bg_color = Color::Yellow,
-                    44 => *bg_color = Color::Blue,
-                    45 => *bg_color = Color::Magenta,
-                    46 => *bg_color = Color::Cyan,
-                    47 => *bg_color = Color::Gray,
-                    90 => *fg_color = Color::DarkGray,
-                    91 => *fg_color = Color::LightRed,
-                    92 => *fg_color = Color::LightGreen,
-                    93 => *fg_color = Color::LightYellow,
-                    94 => *fg_color = Color::LightBlue,
-                    95 => *fg_color = Color::LightMagenta,
-                    96 => *fg_color = Color::LightCyan,
-                    97 => *fg_color = Color::White,
-                    100 => *bg_color = Color::DarkGray,
-                    101 => *bg_color = Color::LightRed,
-                    102 => *bg_color = Color::LightGreen,
-                    103 => *bg_color = Color::LightYellow,
-                    104 => *bg_color = Color::LightBlue,
-                    105 => *bg_color = Color::LightMagenta,
-                    106 => *bg_color = Color::LightCyan,
-                    107 => *bg_color = Color::White,
-                    38 => {
-                        *fg_color = match sgr_set_color(&mut values_parsed) {
-                            Ok(new) => new,
-                            Err(source) => {
-                                return Err(AnsiImportError::BadSgrSetColorParameters {
-                                    index,
-                                    sequence: full_sequence(),
-                                    source,
-                                });
-                            }
-                        };
-                    }
-                    39 => *fg_color = Color::Reset,
-                    48 => {
-                        *bg_color = match sgr_set_color(&mut values_parsed) {
-                            Ok(new) => new,
-                            Err(source) => {
-                                return Err(AnsiImportError::BadSgrSetColorParameters {
-                                    index,
-                                    sequence: full_sequence(),
-                                    source,
-                                });
-                            }
-                        }
-                    }
-                    49 => *bg_color = Color::Reset,
-                    _ => {
-                        return Err(AnsiImportError::BadSgrAttribute {
+            39 => *fg_color = Color::Reset,
+            48 => {
+                *bg_color = match sgr_set_color(&mut values_parsed) {
+                    Ok(new) => new,
+                    Err(source) => {
+                        return Err(AnsiImportError::BadSgrSetColorParameters {
                             index,
-                            attribute,
-                            sequence: full_sequence(),
+                            sequence: sequence.full_sequence(),
+                            source,
                         });
                     }
-                };
+                }
             }
-            Ok(())
-        }
+            49 => *bg_color = Color::Reset,
+            _ => {
+                return Err(AnsiImportError::BadSgrAttribute {
+                    index,
+                    attribute,
+                    sequence: sequence.full_sequence(),
+                });
+            }
+        };
+    }
+    Ok(())
+}
 
+impl Canvas {
+    pub fn from_ansi_or_txt(
+        ansi: String,
+        allow_sgr_sequences: bool,
+    ) -> Result<Self, AnsiImportError>
+    where
+        Self: Sized,
+    {
         let mut canvas = Self::default();
         let mut fg_color = Color::Reset;
         let mut bg_color = Color::Reset;
         let mut modifiers = Modifier::default();
         let mut canvas_index: CanvasIndex = (0, 0);
-        let mut characters = ansi.chars().enumerate();
-        while let Some((_i, character)) = characters.next() {
+        // Index of the last character, to which combining characters are added
+        let mut previous: Option<CanvasIndex> = None;
+        let mut characters = ansi.chars();
+        while let Some(character) = characters.next() {
             if character.is_control() {
                 match character {
                     '\u{0d}' => {
                         // Carriage return, go to beginning of current line
                         canvas_index.1 = 0;
+                        previous = None;
                         continue;
                     }
                     '\u{0a}' => {
                         // Line feed, go to beginning of next line
                         canvas_index.1 = 0;
                         canvas_index.0 += 1;
+                        previous = None;
                         continue;
                     }
                     '\u{1b}' if allow_sgr_sequences => {
                         // Escape sequence
-                        escape_sequence(
+                        let sequence = CsiSequence::read(canvas_index, &mut characters)?;
+                        if sequence.final_character != 'm' {
+                            return Err(sequence.bad_escape_sequence(canvas_index));
+                        }
+                        apply_sgr(
                             canvas_index,
-                            &mut characters,
+                            &sequence,
                             &mut fg_color,
                             &mut bg_color,
                             &mut modifiers,
@@ -279,6 +324,13 @@ impl Canvas {
                 continue;
             }
 
+            if let Some(previous) = previous {
+                if canvas.continues_grapheme(previous, character) {
+                    canvas.add_combining_character(previous, character);
+                    continue;
+                }
+            }
+
             if !(character == ' '
                 && fg_color == Color::Reset
                 && bg_color == Color::Reset
@@ -288,9 +340,11 @@ impl Canvas {
                 canvas.set_fg(canvas_index, fg_color);
                 canvas.set_bg(canvas_index, bg_color);
                 canvas.set_modifiers(canvas_index, modifiers);
+                canvas.pair_wide_character(canvas_index);
             }
 
-            canvas_index.1 += 1;
+            previous = Some(canvas_index);
+            canvas_index.1 += if is_wide(character) { 2 } else { 1 };
         }
         Ok(canvas)
     }
@@ -301,6 +355,17 @@ impl Canvas {
     {
         Self::from_ansi_or_txt(ansi, true)
     }
+
+    /// Imports an ANSI file encoded as UTF-8. A SAUCE record at the end of the file is removed
+    /// from the artwork and stored in the canvas metadata.
+    pub fn from_ansi_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let (contents, sauce) = Sauce::split(bytes);
+        let mut canvas = Self::from_ansi(String::from_utf8(contents.to_vec())?)?;
+        if let Some(sauce) = sauce {
+            canvas.metadata = sauce.into();
+        }
+        Ok(canvas)
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -1,6 +1,8 @@
 use ratatui::style::{Color, Modifier};
 
+use crate::canvas::raw::legacy_ansi_import::is_legacy_ansi;
 use crate::canvas::raw::{ansi_import::TxtImportError, Canvas, CanvasCell};
+use crate::file_formats::sauce::test::sauce_record;
 
 #[test]
 fn basic() {
@@ -19,6 +21,7 @@ fn basic() {
         *cell,
         CanvasCell {
             character: 'a',
+            combining: String::new(),
             fg: Color::Reset,
             bg: Color::Reset,
             modifiers: Modifier::default(),
@@ -33,6 +36,7 @@ fn basic() {
         *cell,
         CanvasCell {
             character: 'b',
+            combining: String::new(),
             fg: Color::Reset,
             bg: Color::Reset,
             modifiers: Modifier::default(),
@@ -47,6 +51,7 @@ fn basic() {
         *cell,
         CanvasCell {
             character: 'c',
+            combining: String::new(),
             fg: Color::Reset,
             bg: Color::Reset,
             modifiers: Modifier::default(),
@@ -71,6 +76,7 @@ fn indents() {
         *cell,
         CanvasCell {
             character: 'a',
+            combining: String::new(),
             fg: Color::Reset,
             bg: Color::Reset,
             modifiers: Modifier::default(),
@@ -85,6 +91,7 @@ fn indents() {
         *cell,
         CanvasCell {
             character: 'b',
+            combining: String::new(),
             fg: Color::Reset,
             bg: Color::Reset,
             modifiers: Modifier::default(),
@@ -108,6 +115,7 @@ fn fg() {
             &(0, 0),
             &CanvasCell {
                 character: 'a',
+                combining: String::new(),
                 fg: Color::Red,
                 bg: Color::Reset,
                 modifiers: Modifier::default(),
@@ -132,6 +140,7 @@ fn bg() {
             &(0, 0),
             &CanvasCell {
                 character: 'a',
+                combining: String::new(),
                 fg: Color::Reset,
                 bg: Color::Red,
                 modifiers: Modifier::default(),
@@ -155,6 +164,7 @@ fn modifiers() {
             &(0, 0),
             &CanvasCell {
                 character: 'a',
+                combining: String::new(),
                 fg: Color::Reset,
                 bg: Color::Reset,
                 modifiers: Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED,
@@ -178,6 +188,7 @@ fn reset() {
             &(0, 0),
             &CanvasCell {
                 character: 'a',
+                combining: String::new(),
                 fg: Color::Red,
                 bg: Color::Red,
                 modifiers: Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED,
@@ -191,6 +202,7 @@ fn reset() {
             &(0, 1),
             &CanvasCell {
                 character: 'b',
+                combining: String::new(),
                 fg: Color::Reset,
                 bg: Color::Reset,
                 modifiers: Modifier::default(),
@@ -214,6 +226,7 @@ fn reset_fg() {
             &(0, 0),
             &CanvasCell {
                 character: 'a',
+                combining: String::new(),
                 fg: Color::Red,
                 bg: Color::Red,
                 modifiers: Modifier::BOLD,
@@ -227,6 +240,7 @@ fn reset_fg() {
             &(0, 1),
             &CanvasCell {
                 character: 'b',
+                combining: String::new(),
                 fg: Color::Reset,
                 bg: Color::Red,
                 modifiers: Modifier::BOLD,
@@ -250,6 +264,7 @@ fn reset_bg() {
             &(0, 0),
             &CanvasCell {
                 character: 'a',
+                combining: String::new(),
                 fg: Color::Red,
                 bg: Color::Red,
                 modifiers: Modifier::BOLD,
@@ -263,6 +278,7 @@ fn reset_bg() {
             &(0, 1),
             &CanvasCell {
                 character: 'b',
+                combining: String::new(),
                 fg: Color::Red,
                 bg: Color::Reset,
                 modifiers: Modifier::BOLD,
@@ -293,6 +309,7 @@ fn fg_indexed() {
             &(0, 0),
             &CanvasCell {
                 character: 'a',
+                combining: String::new(),
                 fg: Color::Indexed(123),
                 bg: Color::Reset,
                 modifiers: Modifier::default(),
@@ -316,6 +333,7 @@ fn fg_rgb() {
             &(0, 0),
             &CanvasCell {
                 character: 'a',
+                combining: String::new(),
                 fg: Color::Rgb(1, 12, 123),
                 bg: Color::Reset,
                 modifiers: Modifier::default(),
@@ -339,6 +357,7 @@ fn bg_indexed() {
             &(0, 0),
             &CanvasCell {
                 character: 'a',
+                combining: String::new(),
                 fg: Color::Reset,
                 bg: Color::Indexed(123),
                 modifiers: Modifier::default(),
@@ -362,6 +381,7 @@ fn bg_rgb() {
             &(0, 0),
             &CanvasCell {
                 character: 'a',
+                combining: String::new(),
                 fg: Color::Reset,
                 bg: Color::Rgb(1, 12, 123),
                 modifiers: Modifier::default(),
@@ -411,6 +431,7 @@ fn empty_sgr_sequence() {
             &(0, 0),
             &CanvasCell {
                 character: 'a',
+                combining: String::new(),
                 fg: Color::Red,
                 bg: Color::Reset,
                 modifiers: Modifier::default(),
@@ -424,6 +445,7 @@ fn empty_sgr_sequence() {
             &(0, 1),
             &CanvasCell {
                 character: 'b',
+                combining: String::new(),
                 fg: Color::Reset,
                 bg: Color::Reset,
                 modifiers: Modifier::default(),
@@ -448,6 +470,7 @@ fn multiple_attributes() {
             &(0, 0),
             &CanvasCell {
                 character: 'a',
+                combining: String::new(),
                 fg: Color::Red,
                 bg: Color::Green,
                 modifiers: Modifier::BOLD,
@@ -461,6 +484,7 @@ fn multiple_attributes() {
             &(0, 1),
             &CanvasCell {
                 character: 'b',
+                combining: String::new(),
                 fg: Color::Indexed(93),
                 bg: Color::Reset,
                 modifiers: Modifier::default(),
@@ -474,6 +498,7 @@ fn multiple_attributes() {
             &(0, 2),
             &CanvasCell {
                 character: 'c',
+                combining: String::new(),
                 fg: Color::Reset,
                 bg: Color::Green,
                 modifiers: Modifier::default(),
@@ -481,3 +506,46 @@ fn multiple_attributes() {
         ))
     );
 }
+
+#[test]
+fn sauce() {
+    let mut bytes = "a\u{1b}[31mé".as_bytes().to_vec();
+    bytes.push(0x1a);
+    bytes.extend(sauce_record("Title", "Author", "Group", &["comment"]));
+    let canvas = Canvas::from_ansi_bytes(&bytes).unwrap();
+
+    assert!(!is_legacy_ansi(&bytes));
+    assert_eq!(canvas.get(&(0, 1)).character, 'é');
+    assert_eq!(canvas.get(&(0, 1)).fg, Color::Red);
+    assert_eq!(canvas.area().columns, 2);
+    assert_eq!(canvas.metadata.title, "Title");
+    assert_eq!(canvas.metadata.comments, ["comment"]);
+    assert_eq!(canvas.metadata.font, "IBM VGA");
+}
+
+#[test]
+fn sgr_attributes_off() {
+    let ansi = "\u{1b}[1;3;4;9ma\u{1b}[22;23mb\u{1b}[24;29mc".to_string();
+    let canvas = Canvas::from_ansi(ansi).unwrap();
+
+    assert_eq!(
+        canvas.get(&(0, 0)).modifiers,
+        Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED | Modifier::CROSSED_OUT
+    );
+    assert_eq!(
+        canvas.get(&(0, 1)).modifiers,
+        Modifier::UNDERLINED | Modifier::CROSSED_OUT
+    );
+    assert_eq!(canvas.get(&(0, 2)).modifiers, Modifier::empty());
+}
+
+#[test]
+fn grapheme_clusters() {
+    let ansi = "e\u{301}\u{1b}[31mx\u{1b}[0m👨\u{200d}👩\u{200d}👧y".to_string();
+    let canvas = Canvas::from_ansi(ansi).unwrap();
+
+    assert_eq!(canvas.get(&(0, 0)).grapheme(), "e\u{301}");
+    assert_eq!(canvas.get(&(0, 1)).character, 'x');
+    assert_eq!(canvas.get(&(0, 2)).grapheme(), "👨\u{200d}👩\u{200d}👧");
+    assert_eq!(canvas.get(&(0, 4)).character, 'y');
+}
@@ -1,7 +1,11 @@
-// mod array;
+#[cfg(any(test, feature = "array-cell-map"))]
+mod array;
 mod btree_map;
 mod hash_map;
 
+#[cfg(feature = "array-cell-map")]
+pub use array::CellMap;
+#[cfg(not(feature = "array-cell-map"))]
 pub use btree_map::CellMap;
 
 pub use btree_map::BTreeCellMap;
@@ -1,51 +1,206 @@
 use crate::canvas::raw::CanvasCell;
 use crate::canvas::raw::CanvasIndex;
-use derive_more::IntoIterator;
-use std::collections::btree_map::IterMut as HashMapIterMut;
+use std::fmt::Debug;
 
-// type Entry<'a> = HashMapEntry<'a, CanvasIndex, CanvasCell>;
-// type Iter<'a> = HashMapIter<'a, CanvasIndex, CanvasCell>;
-type IterMut<'a> = HashMapIterMut<'a, CanvasIndex, CanvasCell>;
+#[cfg(test)]
+mod test;
 
-const CHUNK_ROWS: usize = 16;
-const CHUNK_COLUMNS: usize = 16;
+/// Rows and columns of a chunk.
+const CHUNK_SIZE: usize = 16;
+const CHUNK_CELLS: usize = CHUNK_SIZE * CHUNK_SIZE;
+/// Levels of chunks, which together cover every `i16` coordinate, as `16^4 == 2^16`.
+const LEVELS: usize = 4;
 
-type Chunk<T> = [[T; 16]; 16];
-type ChunkEntry<T> = Option<Box<Chunk<T>>>;
-// type CellArray = [CanvasCell; 16];
-// type CellMatrix = [CellArray; 16];
-#[derive(Clone, Debug, Default, IntoIterator)]
-pub struct CellMap(Chunk<ChunkEntry<ChunkEntry<ChunkEntry<CanvasCell>>>>);
+/// Position of a coordinate in each level of chunks, from the outermost level.
+type Nibbles = [usize; LEVELS];
+
+/// Key of `coordinate` in the chunks, where the sign bit is flipped so that negative coordinates
+/// are ordered before positive ones, like they are in `BTreeCellMap`.
+fn key(coordinate: i16) -> usize {
+    (coordinate as u16 ^ 0x8000) as usize
+}
+
+fn nibbles(coordinate: i16) -> Nibbles {
+    let key = key(coordinate);
+    let mut result = [0; LEVELS];
+    for (level, nibble) in result.iter_mut().enumerate() {
+        *nibble = (key >> (4 * (LEVELS - 1 - level))) % CHUNK_SIZE;
+    }
+    result
+}
+
+/// Index of the chunk slot at `level` for the cell at `rows` and `columns`.
+fn slot(rows: &Nibbles, columns: &Nibbles, level: usize) -> usize {
+    rows[level] * CHUNK_SIZE + columns[level]
+}
+
+/// Increments `columns` at `level`, resetting the levels below it. Returns false if the
+/// increment overflows, meaning that the whole row has been passed.
+fn increment(columns: &mut Nibbles, level: usize) -> bool {
+    for nibble in columns[level + 1..].iter_mut() {
+        *nibble = 0;
+    }
+    for level in (0..=level).rev() {
+        columns[level] += 1;
+        if columns[level] < CHUNK_SIZE {
+            return true;
+        }
+        columns[level] = 0;
+    }
+    false
+}
+
+/// A chunk of 16x16 slots. Chunks of the innermost level contain the cells, other chunks contain
+/// the chunks of the next level.
+#[derive(Clone)]
+enum Chunk {
+    Branch {
+        chunks: Box<[Option<Chunk>; CHUNK_CELLS]>,
+        /// Number of cells in all chunks below.
+        len: usize,
+    },
+    Leaf {
+        /// The index is stored next to the cell, so that iterators can lend it.
+        cells: Box<[Option<(CanvasIndex, CanvasCell)>; CHUNK_CELLS]>,
+        len: usize,
+    },
+}
+
+impl Chunk {
+    fn new(level: usize) -> Self {
+        if level == LEVELS - 1 {
+            Self::Leaf {
+                cells: Box::new(std::array::from_fn(|_| None)),
+                len: 0,
+            }
+        } else {
+            Self::Branch {
+                chunks: Box::new(std::array::from_fn(|_| None)),
+                len: 0,
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::Branch { len, .. } | Self::Leaf { len, .. } => *len,
+        }
+    }
+
+    fn get(
+        &self,
+        rows: &Nibbles,
+        columns: &Nibbles,
+        level: usize,
+    ) -> Option<&(CanvasIndex, CanvasCell)> {
+        let slot = slot(rows, columns, level);
+        match self {
+            Self::Branch { chunks, .. } => chunks[slot].as_ref()?.get(rows, columns, level + 1),
+            Self::Leaf { cells, .. } => cells[slot].as_ref(),
+        }
+    }
+
+    fn get_mut(
+        &mut self,
+        rows: &Nibbles,
+        columns: &Nibbles,
+        level: usize,
+    ) -> Option<&mut (CanvasIndex, CanvasCell)> {
+        let slot = slot(rows, columns, level);
+        match self {
+            Self::Branch { chunks, .. } => chunks[slot].as_mut()?.get_mut(rows, columns, level + 1),
+            Self::Leaf { cells, .. } => cells[slot].as_mut(),
+        }
+    }
+
+    fn insert(
+        &mut self,
+        rows: &Nibbles,
+        columns: &Nibbles,
+        level: usize,
+        entry: (CanvasIndex, CanvasCell),
+    ) -> Option<CanvasCell> {
+        let slot = slot(rows, columns, level);
+        let (previous, len) = match self {
+            Self::Branch { chunks, len } => (
+                chunks[slot]
+                    .get_or_insert_with(|| Chunk::new(level + 1))
+                    .insert(rows, columns, level + 1, entry),
+                len,
+            ),
+            Self::Leaf { cells, len } => (cells[slot].replace(entry).map(|(_, cell)| cell), len),
+        };
+        if previous.is_none() {
+            *len += 1;
+        }
+        previous
+    }
+
+    /// Removes the cell, and chunks left empty by it.
+    fn remove(&mut self, rows: &Nibbles, columns: &Nibbles, level: usize) -> Option<CanvasCell> {
+        let slot = slot(rows, columns, level);
+        let (removed, len) = match self {
+            Self::Branch { chunks, len } => {
+                let chunk = chunks[slot].as_mut()?;
+                let removed = chunk.remove(rows, columns, level + 1);
+                if chunk.len() == 0 {
+                    chunks[slot] = None;
+                }
+                (removed, len)
+            }
+            Self::Leaf { cells, len } => (cells[slot].take().map(|(_, cell)| cell), len),
+        };
+        if removed.is_some() {
+            *len -= 1;
+        }
+        removed
+    }
+
+    /// The first row at or after `from` containing a cell of the chunk, which is at `level`.
+    /// The positions of the row in the levels above are those of `from`.
+    fn first_row(&self, from: &Nibbles, level: usize) -> Option<Nibbles> {
+        let mut from = *from;
+        loop {
+            let row = from[level] * CHUNK_SIZE..(from[level] + 1) * CHUNK_SIZE;
+            let found = match self {
+                Self::Branch { chunks, .. } => chunks[row]
+                    .iter()
+                    .flatten()
+                    .filter_map(|chunk| chunk.first_row(&from, level + 1))
+                    .min(),
+                Self::Leaf { cells, .. } => cells[row].iter().any(Option::is_some).then_some(from),
+            };
+            if found.is_some() {
+                return found;
+            }
+            // Continue with the first row of the next row of slots
+            from[level] += 1;
+            if from[level] == CHUNK_SIZE {
+                return None;
+            }
+            for nibble in from[level + 1..].iter_mut() {
+                *nibble = 0;
+            }
+        }
+    }
+}
+
+/// Cell map storing cells in a tree of 16x16 chunks, where looking up a cell takes four array
+/// lookups, independently of the number of cells. Cells are iterated in the same order as by
+/// `BTreeCellMap`, row by row from the top.
+#[derive(Clone, Default)]
+pub struct CellMap(Option<Chunk>);
 
 pub struct Entry<'a> {
     map: &'a mut CellMap,
     index: CanvasIndex,
 }
-// pub enum Entry<'a> {
-//     Vacant(&'a mut CellMap, CanvasIndex),
-//     Occupied(&'a mut CellMap, CanvasIndex),
-// }
 
 impl<'a> Entry<'a> {
     pub fn or_insert(self, cell: CanvasCell) -> &'a mut CanvasCell {
-        // // TODO: Why doesn't this work?
-        // if let Some(cell) = self.map.get_mut(&self.index) {
-        //     return cell;
-        // } else {
-        //     self.map.insert(self.index, cell);
-        //     return self.map.get_mut(&self.index).unwrap();
-        // }
-        // // Or this?
-        // if true {
-        //     let hm = self.map.get_mut(&self.index);
-        //     if hm.is_some() {
-        //         return hm.unwrap();
-        //     } else {
-        //         // panic!();
-        //     }
-        // }
-        // return self.map.get_mut(&self.index).unwrap();
-        if self.map.get_mut(&self.index).is_none() {
+        // Checking with `get_mut` first doesn't pass the borrow checker, as the returned reference
+        // would have to live for 'a in both branches.
+        if self.map.get(&self.index).is_none() {
             self.map.insert(self.index, cell);
         }
         self.map.get_mut(&self.index).unwrap()
@@ -55,185 +210,285 @@ impl<'a> Entry<'a> {
     }
 }
 
-type IndexUsize = (usize, usize);
-fn calculate_indices(index: CanvasIndex) -> (IndexUsize, IndexUsize, IndexUsize, IndexUsize) {
-    // Cast to 2's complement u16 and then to usize for indexing array.
-    let row = (index.0 as u16) as usize;
-    let column = (index.1 as u16) as usize;
-    (
-        ((row / 16 ^ 3) % 16, (column / 16 ^ 3) % 16),
-        ((row / 16 ^ 2) % 16, (column / 16 ^ 2) % 16),
-        ((row / 16) % 16, (column / 16) % 16),
-        (row % 16, column % 16),
-    )
-}
-
 impl<'a> CellMap {
-    #[rustfmt::skip]
+    pub const fn new() -> Self {
+        Self(None)
+    }
     pub fn get(&'a self, index: &CanvasIndex) -> Option<&'a CanvasCell> {
-        let indices = calculate_indices(*index);
-        let a = &self.0;
-        let a = &(a[indices.0 .0][indices.0 .1]); if a.is_none() { return None; }; let a = a.as_ref().unwrap();
-        let a = &(a[indices.1 .0][indices.1 .1]); if a.is_none() { return None; }; let a = a.as_ref().unwrap();
-        let a = &(a[indices.2 .0][indices.2 .1]); if a.is_none() { return None; }; let a = a.as_ref().unwrap();
-        let a = &(a[indices.3 .0][indices.3 .1]);
-        Some(a)
-    }
-    #[rustfmt::skip]
-    pub fn get_mut(&'a mut self, index: &CanvasIndex) -> Option<&'a mut CanvasCell> {
-        let indices = calculate_indices(*index);
-        let a = &mut self.0;
-        let a = &mut (a[indices.0 .0][indices.0 .1]); if a.is_none() { return None; }; let a = a.as_mut().unwrap();
-        let a = &mut (a[indices.1 .0][indices.1 .1]); if a.is_none() { return None; }; let a = a.as_mut().unwrap();
-        let a = &mut (a[indices.2 .0][indices.2 .1]); if a.is_none() { return None; }; let a = a.as_mut().unwrap();
-        let a = &mut (a[indices.3 .0][indices.3 .1]);
-        Some(a)
+        let (rows, columns) = (nibbles(index.0), nibbles(index.1));
+        self.0
+            .as_ref()?
+            .get(&rows, &columns, 0)
+            .map(|(_index, cell)| cell)
     }
-    pub fn insert(&'a mut self, index: CanvasIndex, cell: CanvasCell) -> Option<CanvasCell> {
-        let indices = calculate_indices(index);
-        let a = &mut self.0;
-        let a = (a[indices.0 .0][indices.0 .1]).get_or_insert_default();
-        let a = (a[indices.1 .0][indices.1 .1]).get_or_insert_default();
-        let a = (a[indices.2 .0][indices.2 .1]).get_or_insert_default();
-        let a = &mut (a[indices.3 .0][indices.3 .1]);
-        *a = cell;
-        None
-    }
-    pub fn iter(&'a self) -> Iter2<'a> {
-        Iter2::new(self)
+    pub fn get_mut(&'a mut self, index: &CanvasIndex) -> Option<&'a mut CanvasCell> {
+        let (rows, columns) = (nibbles(index.0), nibbles(index.1));
+        self.0
+            .as_mut()?
+            .get_mut(&rows, &columns, 0)
+            .map(|(_index, cell)| cell)
     }
     pub fn entry(&'a mut self, index: CanvasIndex) -> Entry<'a> {
         Entry { map: self, index }
-        // self.0.entry(index)
-    }
-    // pub fn insert(&'a mut self, index: CanvasIndex, cell: CanvasCell) -> Option<CanvasCell> {
-    //     self.0.insert(index, cell)
-    // }
-    // pub fn iter(&'a self) -> Iter<'a> {
-    //     self.0.iter()
-    // }
-    // pub fn iter_mut(&'a mut self) -> IterMut<'a> {
-    //     self.0.iter_mut()
-    // }
-    // pub fn len(&'a self) -> usize {
-    //     self.0.len()
-    // }
+    }
+    pub fn insert(&'a mut self, index: CanvasIndex, cell: CanvasCell) -> Option<CanvasCell> {
+        let (rows, columns) = (nibbles(index.0), nibbles(index.1));
+        self.0
+            .get_or_insert_with(|| Chunk::new(0))
+            .insert(&rows, &columns, 0, (index, cell))
+    }
+    pub fn remove(&'a mut self, index: &CanvasIndex) -> Option<CanvasCell> {
+        let (rows, columns) = (nibbles(index.0), nibbles(index.1));
+        let root = self.0.as_mut()?;
+        let removed = root.remove(&rows, &columns, 0);
+        if root.len() == 0 {
+            self.0 = None;
+        }
+        removed
+    }
+    pub fn retain<F>(&'a mut self, mut f: F)
+    where
+        F: FnMut(&CanvasIndex, &mut CanvasCell) -> bool,
+    {
+        let mut cursor = Cursor::new();
+        while let Some(index) = cursor.next_index(self) {
+            if !f(&index, self.get_mut(&index).unwrap()) {
+                self.remove(&index);
+            }
+        }
+    }
+    pub fn iter(&'a self) -> Iter<'a> {
+        Iter {
+            cursor: Cursor::new(),
+            map: self,
+        }
+    }
+    pub fn iter_mut(&'a mut self) -> IterMut<'a> {
+        let levels = match &mut self.0 {
+            Some(root) => vec![split_rows(vec![root])],
+            None => Vec::new(),
+        };
+        IterMut {
+            levels,
+            row: Vec::new().into_iter().flatten(),
+        }
+    }
+    pub fn len(&'a self) -> usize {
+        self.0.as_ref().map_or(0, Chunk::len)
+    }
+    pub fn is_empty(&'a self) -> bool {
+        self.0.is_none()
+    }
 }
 
-pub struct Iter<'a> {
-    map: &'a CellMap,
-    index_next: CanvasIndex,
-    done: bool,
+impl Debug for CellMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Position of an ordered iteration over the cells of a `CellMap`, which doesn't borrow the map.
+struct Cursor {
+    /// Position of the row being iterated.
+    row: Nibbles,
+    /// Position of the row to continue with after the current one, or `None` after the last row.
+    next_row: Option<Nibbles>,
+    /// Position of the next column to look at in the current row, or `None` if the row is done.
+    columns: Option<Nibbles>,
 }
 
-impl<'a> Iter<'a> {
-    fn new(map: &'a CellMap) -> Self {
+impl Cursor {
+    fn new() -> Self {
         Self {
-            map,
-            index_next: (i16::MIN, i16::MIN),
-            done: false,
+            row: [0; LEVELS],
+            next_row: Some([0; LEVELS]),
+            columns: None,
         }
     }
-}
 
-impl<'a> Iterator for Iter<'a> {
-    type Item = CanvasCell;
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.done {
-            return None;
-        }
-        let _index = self.index_next;
-        self.index_next.0 = self.index_next.0.wrapping_add(1);
-        if self.index_next.0 == i16::MIN {
-            self.index_next.1.wrapping_add(1);
-            if self.index_next.1 == i16::MIN {
-                self.done = true;
+    /// Advances to the next row containing cells, returning false if there are no more.
+    fn advance_row(&mut self, root: &Chunk) -> bool {
+        let Some(row) = self
+            .next_row
+            .and_then(|next_row| root.first_row(&next_row, 0))
+        else {
+            self.next_row = None;
+            return false;
+        };
+        self.row = row;
+        let mut next_row = row;
+        self.next_row = increment(&mut next_row, LEVELS - 1).then_some(next_row);
+        self.columns = Some([0; LEVELS]);
+        true
+    }
+
+    /// Index of the next cell of `map`, which must not have had cells added since the cursor
+    /// was created.
+    fn next_index(&mut self, map: &CellMap) -> Option<CanvasIndex> {
+        let root = map.0.as_ref()?;
+        loop {
+            let Some(columns) = &mut self.columns else {
+                if !self.advance_row(root) {
+                    return None;
+                }
+                continue;
+            };
+            if let Some(index) = Self::find_in_row(root, &self.row, columns) {
+                if !increment(columns, LEVELS - 1) {
+                    self.columns = None;
+                }
+                return Some(index);
+            }
+            self.columns = None;
+        }
+    }
+
+    /// Finds the first cell at or after `columns` in the row at `rows`, and moves `columns` to
+    /// it.
+    fn find_in_row(root: &Chunk, rows: &Nibbles, columns: &mut Nibbles) -> Option<CanvasIndex> {
+        'search: loop {
+            let mut chunk = root;
+            for level in 0..LEVELS {
+                let slot = slot(rows, columns, level);
+                match chunk {
+                    Chunk::Branch { chunks, .. } => match &chunks[slot] {
+                        Some(next) => chunk = next,
+                        None => {
+                            if !increment(columns, level) {
+                                return None;
+                            }
+                            continue 'search;
+                        }
+                    },
+                    Chunk::Leaf { cells, .. } => match &cells[slot] {
+                        Some((index, _cell)) => return Some(*index),
+                        None => {
+                            if !increment(columns, level) {
+                                return None;
+                            }
+                            continue 'search;
+                        }
+                    },
+                }
             }
         }
-        None
     }
 }
 
-pub struct Iter2<'a> {
+pub struct Iter<'a> {
     map: &'a CellMap,
-    row: [usize; 4],
-    column: [usize; 4],
-    done: bool,
+    cursor: Cursor,
 }
 
-impl<'a> Iter2<'a> {
-    pub fn new(map: &'a CellMap) -> Self {
-        Self {
-            map,
-            row: [0, 0, 0, 0],
-            column: [0, 0, 0, 0],
-            done: false,
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a CanvasIndex, &'a CanvasCell);
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.cursor.next_index(self.map)?;
+        let (rows, columns) = (nibbles(index.0), nibbles(index.1));
+        let (index, cell) = self.map.0.as_ref()?.get(&rows, &columns, 0)?;
+        Some((index, cell))
+    }
+}
+
+type LeafRow<'a> = &'a mut [Option<(CanvasIndex, CanvasCell)>];
+
+/// Chunks of the same level in one row of slots of their parent chunks, in column order.
+enum RowChunks<'a> {
+    Branches(Vec<&'a mut Chunk>),
+    Leaves(Vec<LeafRow<'a>>),
+}
+
+/// Splits `chunks`, which are of the same level and in column order, into their rows of slots,
+/// which can be borrowed separately.
+fn split_rows(chunks: Vec<&mut Chunk>) -> std::vec::IntoIter<RowChunks<'_>> {
+    let mut branches: Vec<Vec<&mut Chunk>> = (0..CHUNK_SIZE).map(|_| Vec::new()).collect();
+    let mut leaves: Vec<Vec<LeafRow>> = (0..CHUNK_SIZE).map(|_| Vec::new()).collect();
+    for chunk in chunks {
+        match chunk {
+            Chunk::Branch { chunks, .. } => {
+                for (row, slots) in chunks.chunks_mut(CHUNK_SIZE).enumerate() {
+                    branches[row].extend(slots.iter_mut().flatten());
+                }
+            }
+            Chunk::Leaf { cells, .. } => {
+                for (row, slots) in cells.chunks_mut(CHUNK_SIZE).enumerate() {
+                    leaves[row].push(slots);
+                }
+            }
         }
     }
+    let rows: Vec<RowChunks> = if leaves.iter().any(|row| !row.is_empty()) {
+        leaves.into_iter().map(RowChunks::Leaves).collect()
+    } else {
+        branches.into_iter().map(RowChunks::Branches).collect()
+    };
+    rows.into_iter()
+}
+
+/// Iterates over the cells in place, in the same order as `Iter`. The rows of slots of a chunk
+/// are borrowed separately, so that all chunks in a row of chunks can be walked through row by
+/// row.
+pub struct IterMut<'a> {
+    /// For every level down to the one being iterated, the rows of slots not iterated yet.
+    levels: Vec<std::vec::IntoIter<RowChunks<'a>>>,
+    /// Slots of the row of cells being iterated.
+    row: std::iter::Flatten<std::vec::IntoIter<LeafRow<'a>>>,
 }
 
-impl<'a> Iterator for Iter2<'a> {
-    type Item = (CanvasIndex, &'a CanvasCell);
-    #[rustfmt::skip]
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (&'a CanvasIndex, &'a mut CanvasCell);
     fn next(&mut self) -> Option<Self::Item> {
-        #[inline(always)]
-        fn increment(row: &mut [usize; 4], column: &mut [usize; 4], depth: usize) {
-            let depth = depth - 0; column[depth] = (column[depth] + 1) % CHUNK_COLUMNS; if column[depth] != 0 { return; }
-                                      row[depth] = (   row[depth] + 1) % CHUNK_ROWS   ; if    row[depth] != 0 { return; }
-                                   if depth == 0 { return; }
-            let depth = depth - 1; column[depth] = (column[depth] + 1) % CHUNK_COLUMNS; if column[depth] != 0 { return; }
-                                      row[depth] = (   row[depth] + 1) % CHUNK_ROWS   ; if    row[depth] != 0 { return; }
-                                   if depth == 0 { return; }
-            let depth = depth - 1; column[depth] = (column[depth] + 1) % CHUNK_COLUMNS; if column[depth] != 0 { return; }
-                                      row[depth] = (   row[depth] + 1) % CHUNK_ROWS   ; if    row[depth] != 0 { return; }
-                                   if depth == 0 { return; }
-            let depth = depth - 1; column[depth] = (column[depth] + 1) % CHUNK_COLUMNS; if column[depth] != 0 { return; }
-                                      row[depth] = (   row[depth] + 1) % CHUNK_ROWS   ; if    row[depth] != 0 { return; }
-            // Could be skipped to save some performance
-            if depth != 0 {
-                panic!("Level is not 0 at end of cell_map::array iteration index increment");
+        loop {
+            if let Some(slot) = self.row.next() {
+                if let Some((index, cell)) = slot {
+                    return Some((&*index, cell));
+                }
+                continue;
             }
-        }
-        fn calculate_canvas_index(row: &[usize; 4], column: &[usize; 4]) -> CanvasIndex {
-            let mut result_row = 0;
-            let depth = 0; result_row += row[depth] * 16 ^ (3 - depth);
-            let depth = 1; result_row += row[depth] * 16 ^ (3 - depth);
-            let depth = 2; result_row += row[depth] * 16 ^ (3 - depth);
-            let depth = 3; result_row += row[depth] * 16 ^ (3 - depth);
-
-            let mut result_column = 0;
-            let depth = 0; result_column += column[depth] * 16 ^ (3 - depth);
-            let depth = 1; result_column += column[depth] * 16 ^ (3 - depth);
-            let depth = 2; result_column += column[depth] * 16 ^ (3 - depth);
-            let depth = 3; result_column += column[depth] * 16 ^ (3 - depth);
-
-            ((result_row as u16) as i16, (result_column as u16) as i16)
-        }
-        if self.done {
-            return None;
-        }
-        let row = &mut self.row;
-        let column = &mut self.column;
-        let cell = loop {
-            let value = &self.map.0;
-            let depth = 0; let roww = row[depth]; let columnn = column[depth]; let value = &value[roww][columnn]; let value = if let Some(value) = value { value } else { increment(row, column, depth); continue; };
-            let depth = 1; let roww = row[depth]; let columnn = column[depth]; let value = &value[roww][columnn]; let value = if let Some(value) = value { value } else { increment(row, column, depth); continue; };
-            let depth = 2; let roww = row[depth]; let columnn = column[depth]; let value = &value[roww][columnn]; let value = if let Some(value) = value { value } else { increment(row, column, depth); continue; };
-            let depth = 3; let roww = row[depth]; let columnn = column[depth]; let value = &value[roww][columnn];
-            increment(row, column, depth);
-            if *row == [0, 0, 0, 0] && *column == [0, 0, 0, 0] {
-                self.done = true;
+            let level = self.levels.last_mut()?;
+            match level.next() {
+                None => {
+                    self.levels.pop();
+                }
+                Some(RowChunks::Branches(chunks)) => {
+                    if !chunks.is_empty() {
+                        self.levels.push(split_rows(chunks));
+                    }
+                }
+                Some(RowChunks::Leaves(rows)) => self.row = rows.into_iter().flatten(),
             }
-            break value;
+        }
+    }
+}
 
-        };
-        Some((calculate_canvas_index(row, column), cell))
+pub struct IntoIter {
+    map: CellMap,
+    cursor: Cursor,
+}
+
+impl Iterator for IntoIter {
+    type Item = (CanvasIndex, CanvasCell);
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.cursor.next_index(&self.map)?;
+        let cell = self.map.remove(&index)?;
+        Some((index, cell))
+    }
+}
+
+impl IntoIterator for CellMap {
+    type Item = (CanvasIndex, CanvasCell);
+    type IntoIter = IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            cursor: Cursor::new(),
+            map: self,
+        }
     }
 }
 
 impl<'a> IntoIterator for &'a CellMap {
-    type Item = (CanvasIndex, &'a CanvasCell);
-    type IntoIter = Iter2<'a>;
+    type Item = (&'a CanvasIndex, &'a CanvasCell);
+    type IntoIter = Iter<'a>;
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
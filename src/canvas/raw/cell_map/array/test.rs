@@ -0,0 +1,122 @@
+use crate::canvas::raw::cell_map::array::CellMap;
+use crate::canvas::raw::cell_map::BTreeCellMap;
+use crate::canvas::raw::{CanvasCell, CanvasIndex};
+
+fn cell(character: char) -> CanvasCell {
+    CanvasCell {
+        character,
+        ..Default::default()
+    }
+}
+
+/// Indices spread over the whole range of coordinates, in a deterministic order.
+fn indices(count: usize) -> Vec<CanvasIndex> {
+    let mut state: u32 = 12345;
+    let mut next = move || {
+        state = state.wrapping_mul(1103515245).wrapping_add(12345);
+        (state >> 16) as i16
+    };
+    let mut result: Vec<CanvasIndex> = (0..count).map(|_| (next() / 64, next())).collect();
+    result.extend([
+        (i16::MIN, i16::MIN),
+        (i16::MAX, i16::MAX),
+        (-1, -1),
+        (0, 0),
+        (0, 15),
+        (0, 16),
+        (15, 0),
+        (16, 0),
+    ]);
+    result
+}
+
+fn assert_same(map: &CellMap, expected: &BTreeCellMap) {
+    assert_eq!(map.len(), expected.len());
+    assert!(map.iter().eq(expected.iter()));
+}
+
+#[test]
+fn insert_get_and_iterate_in_order() {
+    let mut map = CellMap::new();
+    let mut expected = BTreeCellMap::new();
+    for (i, index) in indices(2000).into_iter().enumerate() {
+        let character = char::from(b'a' + (i % 26) as u8);
+        assert_eq!(
+            map.insert(index, cell(character)),
+            expected.insert(index, cell(character))
+        );
+    }
+    assert_same(&map, &expected);
+    for (index, cell) in &expected {
+        assert_eq!(map.get(index), Some(cell));
+    }
+    assert_eq!(map.get(&(1, 2)), expected.get(&(1, 2)));
+}
+
+#[test]
+fn remove_and_retain() {
+    let mut map = CellMap::new();
+    let mut expected = BTreeCellMap::new();
+    for index in indices(500) {
+        map.insert(index, cell('x'));
+        expected.insert(index, cell('x'));
+    }
+    for index in indices(500).into_iter().step_by(3) {
+        assert_eq!(map.remove(&index), expected.remove(&index));
+    }
+    assert_same(&map, &expected);
+
+    map.retain(|(row, _column), _cell| row % 2 == 0);
+    expected.retain(|(row, _column), _cell| row % 2 == 0);
+    assert_same(&map, &expected);
+
+    map.retain(|_index, _cell| false);
+    assert_eq!(map.len(), 0);
+    assert!(map.is_empty());
+    assert_eq!(map.iter().next(), None);
+}
+
+#[test]
+fn entry_and_iter_mut() {
+    let mut map = CellMap::new();
+    map.entry((3, -4)).or_default().character = 'a';
+    map.entry((3, -4)).or_default().fg = ratatui::style::Color::Red;
+    map.entry((-3, 4)).or_insert(cell('b'));
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(&(3, -4)).unwrap().character, 'a');
+    assert_eq!(map.get(&(3, -4)).unwrap().fg, ratatui::style::Color::Red);
+
+    for (_index, cell) in map.iter_mut() {
+        cell.character = cell.character.to_ascii_uppercase();
+    }
+    let indices: Vec<_> = map.iter_mut().map(|(index, _cell)| *index).collect();
+    assert_eq!(indices, vec![(-3, 4), (3, -4)]);
+    assert_eq!(
+        map.into_iter().collect::<Vec<_>>(),
+        vec![
+            ((-3, 4), cell('B')),
+            ((3, -4), {
+                let mut cell = cell('A');
+                cell.fg = ratatui::style::Color::Red;
+                cell
+            })
+        ]
+    );
+}
+
+#[test]
+fn iter_mut_in_order() {
+    let mut map = CellMap::new();
+    let mut expected = BTreeCellMap::new();
+    for index in indices(2000) {
+        map.insert(index, cell('x'));
+        expected.insert(index, cell('x'));
+    }
+    for ((index, cell), (expected_index, expected_cell)) in map.iter_mut().zip(expected.iter_mut())
+    {
+        assert_eq!(index, expected_index);
+        cell.character = 'y';
+        expected_cell.character = 'y';
+    }
+    assert_same(&map, &expected);
+}
@@ -0,0 +1,98 @@
+use ratatui::style::Color;
+
+use crate::config::color_theme::{palette_index, xterm_rgb};
+
+use super::{is_wide, Canvas, CanvasCell, DEFAULT_CELL, WIDE_CHARACTER_CONTINUATION};
+
+#[cfg(test)]
+mod test;
+
+/// Opacity of a fully opaque layer, in percent.
+pub const OPACITY_OPAQUE: u8 = 100;
+
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        color => palette_index(color).map(xterm_rgb),
+    }
+}
+
+/// Mixes `upper` into `lower` by `opacity` percent. Colors without an RGB value, like the
+/// terminal's default color, can't be mixed, so the more opaque of the two is used.
+fn blend_color(upper: Color, lower: Color, opacity: u8) -> Color {
+    match (rgb(upper), rgb(lower)) {
+        (Some(upper), Some(lower)) => {
+            let mix = |upper: u8, lower: u8| {
+                ((upper as u16 * opacity as u16 + lower as u16 * (OPACITY_OPAQUE - opacity) as u16)
+                    / OPACITY_OPAQUE as u16) as u8
+            };
+            Color::Rgb(
+                mix(upper.0, lower.0),
+                mix(upper.1, lower.1),
+                mix(upper.2, lower.2),
+            )
+        }
+        _ if opacity * 2 >= OPACITY_OPAQUE => upper,
+        _ => lower,
+    }
+}
+
+impl Canvas {
+    /// Draws `layer` on top of the canvas. Cells of `layer` left at the default cell are
+    /// transparent, so the canvas shows through them.
+    ///
+    /// With an `opacity` below `OPACITY_OPAQUE` percent, colors are mixed with the cells below,
+    /// and the text of whichever cell is more opaque is shown.
+    pub fn draw_layer(&mut self, layer: &Canvas, opacity: u8) {
+        let opacity = opacity.min(OPACITY_OPAQUE);
+        if opacity == 0 {
+            return;
+        }
+        for (index, cell) in &layer.cells {
+            if *cell == DEFAULT_CELL {
+                continue;
+            }
+            let below = self.get(index);
+            let cell = if opacity == OPACITY_OPAQUE {
+                cell.clone()
+            } else {
+                let text = if opacity * 2 >= OPACITY_OPAQUE {
+                    cell
+                } else {
+                    below
+                };
+                CanvasCell {
+                    character: text.character,
+                    combining: text.combining.clone(),
+                    fg: blend_color(cell.fg, below.fg, opacity),
+                    bg: blend_color(cell.bg, below.bg, opacity),
+                    modifiers: text.modifiers,
+                }
+            };
+            self._set(*index, cell);
+        }
+        self.area.include_rect(layer.area);
+        self.unpair_broken_wide_characters();
+    }
+
+    /// Replaces wide characters that have been partly covered by spaces, as well as the
+    /// remaining half of them.
+    fn unpair_broken_wide_characters(&mut self) {
+        let broken: Vec<_> = self
+            .cells
+            .iter()
+            .filter(|((row, column), cell)| {
+                if is_wide(cell.character) {
+                    self.character((*row, column + 1)) != WIDE_CHARACTER_CONTINUATION
+                } else {
+                    cell.character == WIDE_CHARACTER_CONTINUATION
+                        && !is_wide(self.character((*row, column - 1)))
+                }
+            })
+            .map(|(index, _cell)| *index)
+            .collect();
+        for index in broken {
+            self.set_character(index, ' ');
+        }
+    }
+}
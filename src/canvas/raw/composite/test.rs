@@ -0,0 +1,63 @@
+use ratatui::style::Color;
+
+use crate::canvas::raw::{Canvas, WIDE_CHARACTER_CONTINUATION};
+
+#[test]
+fn transparent_cells() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_character((0, 1), 'b');
+    let mut layer = Canvas::default();
+    layer.set_character((0, 1), 'x');
+    layer.set_bg((0, 2), Color::Red);
+    layer.set_character((3, 3), ' ');
+
+    canvas.draw_layer(&layer, 100);
+
+    assert_eq!(canvas.character((0, 0)), 'a');
+    assert_eq!(canvas.character((0, 1)), 'x');
+    assert_eq!(canvas.bg((0, 2)), Color::Red);
+    assert_eq!(canvas.area().rows, 4);
+}
+
+#[test]
+fn opacity() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_bg((0, 0), Color::Rgb(0, 0, 0));
+    let mut layer = Canvas::default();
+    layer.set_character((0, 0), 'x');
+    layer.set_bg((0, 0), Color::Rgb(200, 100, 0));
+
+    let mut translucent = canvas.clone();
+    translucent.draw_layer(&layer, 75);
+    assert_eq!(translucent.character((0, 0)), 'x');
+    assert_eq!(translucent.bg((0, 0)), Color::Rgb(150, 75, 0));
+
+    let mut faint = canvas.clone();
+    faint.draw_layer(&layer, 25);
+    assert_eq!(faint.character((0, 0)), 'a');
+    assert_eq!(faint.bg((0, 0)), Color::Rgb(50, 25, 0));
+
+    let mut invisible = canvas.clone();
+    invisible.draw_layer(&layer, 0);
+    assert_eq!(invisible.cell((0, 0)), canvas.cell((0, 0)));
+}
+
+#[test]
+fn broken_wide_characters() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), '漢');
+    canvas.pair_wide_character((0, 0));
+    let mut layer = Canvas::default();
+    layer.set_character((0, 1), 'x');
+    layer.set_character((1, 1), '字');
+    layer.pair_wide_character((1, 1));
+
+    canvas.draw_layer(&layer, 100);
+
+    assert_eq!(canvas.character((0, 0)), ' ');
+    assert_eq!(canvas.character((0, 1)), 'x');
+    assert_eq!(canvas.character((1, 1)), '字');
+    assert_eq!(canvas.character((1, 2)), WIDE_CHARACTER_CONTINUATION);
+}
@@ -2,8 +2,10 @@ use super::Canvas;
 use super::CanvasCell;
 use super::CanvasIndex;
 use crate::canvas::raw::CellContentType;
+use crate::canvas::rect::CanvasRect;
 use crate::selections::Selection;
 use crate::ProgramState;
+use derive_more::{Display, Error};
 use keystrokes_parsing::Presetable;
 use ratatui::style::Color;
 use ratatui::style::Modifier;
@@ -11,9 +13,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::hash::Hash;
 
-// Todo: Fikse tester
-// #[cfg(test)]
-// mod test;
+#[cfg(test)]
+mod test;
 
 #[derive(Debug, Clone)]
 pub enum AllowedDisallowed<T> {
@@ -66,6 +67,7 @@ impl MatchValue<Modifier> for Modifier {
 impl MatchValue<CanvasCell> for &CanvasCell {
     fn matches(&self, target: &CanvasCell) -> bool {
         self.character.matches(&target.character)
+            && self.combining == target.combining
             && self.fg.matches(&target.fg)
             && self.bg.matches(&target.bg)
             && self.modifiers.matches(&target.modifiers)
@@ -74,6 +76,7 @@ impl MatchValue<CanvasCell> for &CanvasCell {
 #[derive(Debug, Clone, Default)]
 pub struct MatchCellSame {
     ch: Option<char>,
+    combining: Option<String>,
     fg: Option<Color>,
     bg: Option<Color>,
     modifier: Option<Modifier>,
@@ -81,6 +84,10 @@ pub struct MatchCellSame {
 impl MatchValue<CanvasCell> for MatchCellSame {
     fn matches(&self, target: &CanvasCell) -> bool {
         self.ch.matches(&target.character)
+            && self
+                .combining
+                .as_ref()
+                .is_none_or(|combining| *combining == target.combining)
             && self.fg.matches(&target.fg)
             && self.bg.matches(&target.bg)
             && self.modifier.matches(&target.modifiers)
@@ -92,6 +99,7 @@ impl From<(&CanvasCell, CellContentType)> for MatchCellSame {
         let mut result = MatchCellSame::default();
         if content_type.contains(CellContentType::TEXT) {
             result.ch = Some(cell.character);
+            result.combining = Some(cell.combining.clone());
         }
         if content_type.contains(CellContentType::FG) {
             result.fg = Some(cell.fg);
@@ -187,35 +195,67 @@ impl From<(&CanvasCell, ContinuousRegionRelativeType)> for MatchCell {
 //     modifier: Modifier,
 // }
 
+#[derive(Debug, Display, Error, PartialEq)]
+pub enum ContinuousRegionError {
+    #[display("The region has more than {max_cells} cells, try limiting it to a visual rect")]
+    TooLarge {
+        #[error(ignore)]
+        max_cells: usize,
+    },
+}
+
+/// Offsets to the neighbouring cells, where the first four don't cross corners.
+const OFFSETS: [(i16, i16); 8] = [
+    (1, 0),
+    (0, 1),
+    (-1, 0),
+    (0, -1),
+    (1, 1),
+    (-1, 1),
+    (-1, -1),
+    (1, -1),
+];
+
+/// Finds the cells inside `bound` connected to `start` by cells matching `match_cell`, also
+/// through corners if `diagonals_allowed`. Fails if the region has more than `max_cells` cells.
 pub fn find_continuous_region(
     canvas: &Canvas,
     start: CanvasIndex,
     match_cell: impl MatchValue<CanvasCell>,
     diagonals_allowed: bool,
-) -> Selection {
-    fn recurse(
-        canvas: &Canvas,
-        index: CanvasIndex,
-        match_cell: &impl MatchValue<CanvasCell>,
-        diagonals_allowed: bool,
-        result: &mut Selection,
-    ) {
-        if !result.contains(&index)
-            && match_cell.matches(&canvas.get(&index))
-            && canvas.area.includes_index(index)
+    bound: CanvasRect,
+    max_cells: usize,
+) -> Result<Selection, ContinuousRegionError> {
+    let offsets = if diagonals_allowed {
+        &OFFSETS[..]
+    } else {
+        &OFFSETS[..4]
+    };
+    let mut result = Selection::new();
+    let mut pending = vec![start];
+    while let Some(index) = pending.pop() {
+        if result.contains(&index)
+            || !bound.includes_index(index)
+            || !match_cell.matches(&canvas.get(&index))
         {
-            result.insert(index);
-            let mut offsets = vec![(1, 0), (0, 1), (-1, 0), (0, -1)];
-            if diagonals_allowed {
-                offsets.append(&mut vec![(1, 1), (-1, 1), (-1, -1), (1, -1)]);
-            }
-            for offset in offsets {
-                let index_next = (index.0 + offset.0, index.1 + offset.1);
-                recurse(canvas, index_next, match_cell, diagonals_allowed, result);
+            continue;
+        }
+        if result.len() == max_cells {
+            return Err(ContinuousRegionError::TooLarge { max_cells });
+        }
+        result.insert(index);
+        for (row_offset, column_offset) in offsets {
+            // Indices past the edge of the coordinates are never inside the bound
+            let (Some(row), Some(column)) = (
+                index.0.checked_add(*row_offset),
+                index.1.checked_add(*column_offset),
+            ) else {
+                continue;
+            };
+            if !result.contains(&(row, column)) {
+                pending.push((row, column));
             }
         }
     }
-    let mut result = Selection::new();
-    recurse(canvas, start, &match_cell, diagonals_allowed, &mut result);
-    result
+    Ok(result)
 }
@@ -1,16 +1,15 @@
 use super::find_continuous_region;
-use super::AllowedDisallowed;
-use super::CanvasCell;
-use super::CanvasIndex;
+use super::ContinuousRegionError;
 use super::MatchCellSame;
-use super::RawCanvas;
-use std::collections::HashSet;
-// use super::MatchValue;
-use crate::keystrokes::operators::Operator;
-use crate::keystrokes::operators::Replace;
-use crate::selections::Selection;
-use ratatui::style::Color;
-use ratatui::style::Modifier;
+use crate::canvas::raw::Canvas;
+use crate::canvas::rect::CanvasRect;
+
+fn match_s() -> MatchCellSame {
+    MatchCellSame {
+        ch: Some('S'),
+        ..Default::default()
+    }
+}
 
 #[test]
 fn test_find_continuous_region() {
@@ -23,12 +22,7 @@ SSS SS S
 SS   S S
 ",
             (3, 5),
-            MatchCellSame {
-                ch: AllowedDisallowed::Allowed(HashSet::from(['S'])),
-                fg: None,
-                bg: None,
-                modifier: None,
-            },
+            match_s(),
             false,
             "
    SSSSS
@@ -45,12 +39,7 @@ SSS SS S
 SS   S S
 ",
             (3, 6),
-            MatchCellSame {
-                ch: AllowedDisallowed::Allowed(HashSet::from(['S'])),
-                fg: None,
-                bg: None,
-                modifier: None,
-            },
+            match_s(),
             false,
             "
    SSSSS
@@ -67,12 +56,7 @@ SSS SS S
 SS   S S
 ",
             (3, 7),
-            MatchCellSame {
-                ch: AllowedDisallowed::Allowed(HashSet::from(['S'])),
-                fg: None,
-                bg: None,
-                modifier: None,
-            },
+            match_s(),
             false,
             "
    #####
@@ -89,12 +73,7 @@ SSS SS S
 SS   S S
 ",
             (3, 7),
-            MatchCellSame {
-                ch: AllowedDisallowed::Allowed(HashSet::from(['S'])),
-                fg: None,
-                bg: None,
-                modifier: None,
-            },
+            match_s(),
             true,
             "
    #####
@@ -105,16 +84,61 @@ SS   S S
         ),
     ];
     for (ansi, start, match_cell, diagonals_allowed, expected) in tests {
-        let mut canvas = RawCanvas::from_ansi(ansi.to_string()).unwrap();
-        let result = find_continuous_region(&canvas, start, match_cell, diagonals_allowed);
+        let mut canvas = Canvas::from_ansi(ansi.to_string()).unwrap();
+        let result = find_continuous_region(
+            &canvas,
+            start,
+            match_cell,
+            diagonals_allowed,
+            canvas.area(),
+            usize::MAX,
+        )
+        .unwrap();
         for index in result.iter() {
             canvas.set_character(*index, '#');
-            // canvas.set_bg(*index, Color::Blue);
         }
-        // canvas.set_bg(start, Color::Yellow);
-        // print!("{}", canvas.export_ansi().unwrap());
         let mut ansi_out = "\n".to_string();
         ansi_out.push_str(canvas.export_txt_preserve().unwrap().as_str());
         assert_eq!(expected, ansi_out);
     }
 }
+
+#[test]
+fn large_region_is_bounded() {
+    let mut canvas = Canvas::default();
+    for row in 0..300 {
+        for column in 0..300 {
+            canvas.set_character((row, column), 'S');
+        }
+    }
+    let result = find_continuous_region(
+        &canvas,
+        (150, 150),
+        match_s(),
+        true,
+        canvas.area(),
+        usize::MAX,
+    )
+    .unwrap();
+    assert_eq!(result.len(), 300 * 300);
+
+    let bound = CanvasRect {
+        row: 10,
+        column: 20,
+        rows: 5,
+        columns: 7,
+    };
+    let result =
+        find_continuous_region(&canvas, (12, 22), match_s(), false, bound, usize::MAX).unwrap();
+    assert_eq!(result.len(), 5 * 7);
+    assert!(result.iter().all(|index| bound.includes_index(*index)));
+
+    let result = find_continuous_region(&canvas, (0, 0), match_s(), false, bound, usize::MAX);
+    assert!(result.unwrap().is_empty());
+
+    let result = find_continuous_region(&canvas, (0, 0), match_s(), false, canvas.area(), 1000);
+    assert_eq!(
+        result,
+        Err(ContinuousRegionError::TooLarge { max_cells: 1000 })
+    );
+}
@@ -0,0 +1,141 @@
+use ratatui::style::{Color, Modifier};
+
+use crate::config::color_theme::ColorThemeCanvas;
+use crate::Ground;
+
+use super::{Canvas, CanvasCell, WIDE_CHARACTER_CONTINUATION};
+
+#[cfg(test)]
+pub(super) mod test;
+
+pub(super) fn css_color((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+pub(super) fn push_escaped(character: char, result: &mut String) {
+    match character {
+        '&' => result.push_str("&amp;"),
+        '<' => result.push_str("&lt;"),
+        '>' => result.push_str("&gt;"),
+        character => result.push(character),
+    }
+}
+
+fn css_colors(fg: Option<(u8, u8, u8)>, bg: Option<(u8, u8, u8)>, declarations: &mut Vec<String>) {
+    if let Some(fg) = fg {
+        declarations.push(format!("color:{}", css_color(fg)));
+    }
+    if let Some(bg) = bg {
+        declarations.push(format!("background-color:{}", css_color(bg)));
+    }
+}
+
+/// Returns the CSS declarations giving the look of `cell`, or an empty string if it has none.
+///
+/// Reset colors are left out, so that they are inherited from the `<pre>` element, except when
+/// the colors are reversed.
+fn css_style(cell: &CanvasCell, color_theme: &ColorThemeCanvas) -> String {
+    let reversed = cell.modifiers.contains(Modifier::REVERSED);
+    let resolve = |color, ground| match color {
+        Color::Reset if !reversed => None,
+        color => color_theme.resolve_rgb(color, ground),
+    };
+    let mut fg = resolve(cell.fg, Ground::Foreground);
+    let mut bg = resolve(cell.bg, Ground::Background);
+    if reversed {
+        (fg, bg) = (bg, fg);
+    }
+    let mut declarations = Vec::new();
+    css_colors(fg, bg, &mut declarations);
+    if cell.modifiers.contains(Modifier::BOLD) {
+        declarations.push("font-weight:bold".to_string());
+    }
+    if cell.modifiers.contains(Modifier::DIM) {
+        declarations.push("opacity:0.5".to_string());
+    }
+    if cell.modifiers.contains(Modifier::ITALIC) {
+        declarations.push("font-style:italic".to_string());
+    }
+    let mut text_decorations = Vec::new();
+    if cell.modifiers.contains(Modifier::UNDERLINED) {
+        text_decorations.push("underline");
+    }
+    if cell.modifiers.contains(Modifier::CROSSED_OUT) {
+        text_decorations.push("line-through");
+    }
+    if !text_decorations.is_empty() {
+        declarations.push(format!("text-decoration:{}", text_decorations.join(" ")));
+    }
+    if cell.modifiers.contains(Modifier::HIDDEN) {
+        declarations.push("visibility:hidden".to_string());
+    }
+    declarations.join(";")
+}
+
+impl Canvas {
+    /// Exports the canvas as an HTML `<pre>` element, with runs of identically styled cells
+    /// merged into `<span>`s with inline styles.
+    ///
+    /// Standard and indexed colors are resolved through `color_theme`, so that the result looks
+    /// like it does in upaint. Blinking is not supported.
+    pub fn export_html(&self, color_theme: &ColorThemeCanvas) -> String {
+        let mut result = String::new();
+        let mut pre_declarations = Vec::new();
+        css_colors(
+            color_theme.resolve_rgb(Color::Reset, Ground::Foreground),
+            color_theme.resolve_rgb(Color::Reset, Ground::Background),
+            &mut pre_declarations,
+        );
+        if pre_declarations.is_empty() {
+            result.push_str("<pre>");
+        } else {
+            let pre_style = pre_declarations.join(";");
+            result.push_str(format!("<pre style=\"{pre_style}\">").as_str());
+        }
+
+        let mut span_style = String::new();
+        let mut previous_row = self.area.first_row();
+        let mut previous_column = self.area.first_column() - 1;
+        for (index, cell) in self.cells.iter() {
+            let (row, column) = index.to_owned();
+
+            let linebreaks_to_add = row - previous_row;
+            let spaces_to_add = if row == previous_row {
+                column - (previous_column + 1)
+            } else {
+                column - self.area().first_column()
+            };
+
+            // Skipped cells are unstyled, so the span must be closed before them
+            let style = css_style(cell, color_theme);
+            let cells_skipped = linebreaks_to_add > 0 || spaces_to_add > 0;
+            if !span_style.is_empty() && (cells_skipped || style != span_style) {
+                result.push_str("</span>");
+                span_style.clear();
+            }
+
+            for _i in 0..linebreaks_to_add {
+                result.push('\n');
+            }
+            for _i in 0..spaces_to_add {
+                result.push(' ');
+            }
+
+            if !style.is_empty() && span_style.is_empty() {
+                result.push_str(format!("<span style=\"{style}\">").as_str());
+                span_style = style;
+            }
+
+            if cell.character != WIDE_CHARACTER_CONTINUATION {
+                push_escaped(cell.character, &mut result);
+                result.push_str(&cell.combining);
+            }
+            (previous_row, previous_column) = (row, column);
+        }
+        if !span_style.is_empty() {
+            result.push_str("</span>");
+        }
+        result.push_str("</pre>\n");
+        result
+    }
+}
@@ -0,0 +1,184 @@
+use ratatui::style::{Color, Modifier};
+
+use crate::canvas::raw::Canvas;
+use crate::config::color_theme::{ColorThemeCanvas, ColorThemeCanvasStandardColors, StyleConfig};
+
+pub fn color_theme(default_fg: Color, default_bg: Color) -> ColorThemeCanvas {
+    ColorThemeCanvas {
+        default_style: StyleConfig {
+            fg: default_fg,
+            bg: default_bg,
+            modifiers: Modifier::empty(),
+        },
+        standard_colors: ColorThemeCanvasStandardColors {
+            black: Color::Rgb(1, 1, 1).into(),
+            red: Color::Rgb(200, 0, 0).into(),
+            green: Color::Rgb(0, 200, 0).into(),
+            yellow: Color::Rgb(200, 200, 0).into(),
+            blue: Color::Rgb(0, 0, 200).into(),
+            magenta: Color::Rgb(200, 0, 200).into(),
+            cyan: Color::Rgb(0, 200, 200).into(),
+            white: Color::Rgb(200, 200, 200).into(),
+            bright_black: Color::Rgb(100, 100, 100).into(),
+            bright_red: Color::Rgb(255, 0, 0).into(),
+            bright_green: Color::Rgb(0, 255, 0).into(),
+            bright_yellow: Color::Rgb(255, 255, 0).into(),
+            bright_blue: Color::Rgb(0, 0, 255).into(),
+            bright_magenta: Color::Rgb(255, 0, 255).into(),
+            bright_cyan: Color::Rgb(0, 255, 255).into(),
+            bright_white: Color::White.into(),
+        },
+        visual_mode_highlight_bg: Color::Gray.into(),
+        selection_highlight_bg: Color::DarkGray.into(),
+        changed_highlight_bg: Color::Green.into(),
+    }
+}
+
+#[test]
+fn basic() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_character((0, 1), 'b');
+    let html = canvas.export_html(&color_theme(Color::Reset, Color::Reset));
+
+    assert_eq!(html, "<pre>ab</pre>\n");
+}
+
+#[test]
+fn spacing() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_character((0, 2), 'b');
+    canvas.set_character((2, 1), 'c');
+    let html = canvas.export_html(&color_theme(Color::Reset, Color::Reset));
+
+    assert_eq!(html, "<pre>a b\n\n c</pre>\n");
+}
+
+#[test]
+fn escaping() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), '<');
+    canvas.set_character((0, 1), '&');
+    canvas.set_character((0, 2), '>');
+    let html = canvas.export_html(&color_theme(Color::Reset, Color::Reset));
+
+    assert_eq!(html, "<pre>&lt;&amp;&gt;</pre>\n");
+}
+
+#[test]
+fn runs() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_character((0, 1), 'b');
+    canvas.set_character((0, 2), 'c');
+    canvas.set_fg((0, 0), Color::Rgb(255, 128, 0));
+    canvas.set_fg((0, 1), Color::Rgb(255, 128, 0));
+    canvas.set_bg((0, 2), Color::Rgb(0, 0, 16));
+    let html = canvas.export_html(&color_theme(Color::Reset, Color::Reset));
+
+    assert_eq!(
+        html,
+        concat!(
+            "<pre>",
+            "<span style=\"color:#ff8000\">ab</span>",
+            "<span style=\"background-color:#000010\">c</span>",
+            "</pre>\n",
+        )
+    );
+}
+
+#[test]
+fn runs_split_by_skipped_cells() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_character((0, 2), 'b');
+    canvas.set_character((1, 0), 'c');
+    for index in [(0, 0), (0, 2), (1, 0)] {
+        canvas.set_fg(index, Color::Rgb(255, 128, 0));
+    }
+    let html = canvas.export_html(&color_theme(Color::Reset, Color::Reset));
+
+    assert_eq!(
+        html,
+        concat!(
+            "<pre>",
+            "<span style=\"color:#ff8000\">a</span> ",
+            "<span style=\"color:#ff8000\">b</span>\n",
+            "<span style=\"color:#ff8000\">c</span>",
+            "</pre>\n",
+        )
+    );
+}
+
+#[test]
+fn color_theme_colors() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_character((0, 1), 'b');
+    canvas.set_character((0, 2), 'c');
+    canvas.set_character((0, 3), 'd');
+    canvas.set_fg((0, 0), Color::Red);
+    canvas.set_fg((0, 1), Color::Indexed(1));
+    canvas.set_fg((0, 2), Color::Indexed(15));
+    canvas.set_fg((0, 3), Color::Indexed(196));
+    let html = canvas.export_html(&color_theme(Color::Reset, Color::Reset));
+
+    assert_eq!(
+        html,
+        concat!(
+            "<pre>",
+            "<span style=\"color:#c80000\">ab</span>",
+            "<span style=\"color:#ffffff\">c</span>",
+            "<span style=\"color:#ff0000\">d</span>",
+            "</pre>\n",
+        )
+    );
+}
+
+#[test]
+fn default_style() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    let html = canvas.export_html(&color_theme(Color::Black, Color::Rgb(9, 9, 9)));
+
+    assert_eq!(
+        html,
+        "<pre style=\"color:#010101;background-color:#090909\">a</pre>\n"
+    );
+}
+
+#[test]
+fn modifiers() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_character((0, 1), 'b');
+    canvas.add_modifier((0, 0), Modifier::BOLD | Modifier::ITALIC);
+    canvas.add_modifier((0, 1), Modifier::UNDERLINED | Modifier::CROSSED_OUT);
+    let html = canvas.export_html(&color_theme(Color::Reset, Color::Reset));
+
+    assert_eq!(
+        html,
+        concat!(
+            "<pre>",
+            "<span style=\"font-weight:bold;font-style:italic\">a</span>",
+            "<span style=\"text-decoration:underline line-through\">b</span>",
+            "</pre>\n",
+        )
+    );
+}
+
+#[test]
+fn reversed() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_fg((0, 0), Color::Rgb(1, 2, 3));
+    canvas.set_bg((0, 0), Color::Rgb(4, 5, 6));
+    canvas.add_modifier((0, 0), Modifier::REVERSED);
+    let html = canvas.export_html(&color_theme(Color::Reset, Color::Reset));
+
+    assert_eq!(
+        html,
+        "<pre><span style=\"color:#040506;background-color:#010203\">a</span></pre>\n"
+    );
+}
@@ -0,0 +1,92 @@
+use ratatui::style::Color;
+
+use crate::file_formats::image::{Image, Pixel};
+
+use super::quantize::{ColorDepth, Dithering};
+use super::{Canvas, CanvasCell};
+
+#[cfg(test)]
+mod test;
+
+/// Number of columns images are scaled down to, unless another width is given.
+pub const IMAGE_IMPORT_DEFAULT_COLUMNS: u16 = 80;
+
+const UPPER_HALF_BLOCK: char = '▀';
+const LOWER_HALF_BLOCK: char = '▄';
+
+/// Most columns and rows of pixels an image is imported to, so that every cell fits the canvas
+/// coordinates.
+const MAX_COLUMNS: u32 = i16::MAX as u32 + 1;
+const MAX_PIXEL_ROWS: u32 = 2 * MAX_COLUMNS;
+
+/// Pixels with less opacity are left out.
+const ALPHA_THRESHOLD: u8 = 128;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ImageImportOptions {
+    /// Width of the imported image in columns. Defaults to the width of the image, up to
+    /// `IMAGE_IMPORT_DEFAULT_COLUMNS`.
+    pub columns: Option<u16>,
+    pub color_depth: ColorDepth,
+    pub dithering: Dithering,
+}
+
+fn pixel_color(pixel: Pixel) -> Option<Color> {
+    (pixel[3] >= ALPHA_THRESHOLD).then_some(Color::Rgb(pixel[0], pixel[1], pixel[2]))
+}
+
+impl Canvas {
+    /// Imports an image as pixel art, drawing two pixels per cell with half blocks, where the
+    /// foreground color is the upper pixel and the background color the lower pixel. The image
+    /// is scaled to `options.columns`, keeping its aspect ratio, and its colors are reduced to
+    /// `options.color_depth`. Images too large for the canvas coordinates are scaled down further.
+    ///
+    /// Transparent pixels are left out, so the cells below them aren't covered when pasting the
+    /// canvas.
+    pub fn from_image(image: &Image, options: ImageImportOptions) -> Self {
+        let mut canvas = Self::default();
+        if image.width == 0 || image.height == 0 {
+            return canvas;
+        }
+        let mut width = match options.columns {
+            Some(columns) => columns.max(1) as u32,
+            None => image.width.min(IMAGE_IMPORT_DEFAULT_COLUMNS as u32),
+        }
+        .min(MAX_COLUMNS);
+        let mut height = ((image.height as u64 * width as u64 + image.width as u64 / 2)
+            / image.width as u64)
+            .max(1);
+        if height > MAX_PIXEL_ROWS as u64 {
+            width = (width as u64 * MAX_PIXEL_ROWS as u64 / height).max(1) as u32;
+            height = MAX_PIXEL_ROWS as u64;
+        }
+        let height = height as u32;
+        let image = image.resize(width, height);
+        for y in (0..height).step_by(2) {
+            for x in 0..width {
+                let upper = pixel_color(image.pixel(x, y));
+                let lower = if y + 1 < height {
+                    pixel_color(image.pixel(x, y + 1))
+                } else {
+                    None
+                };
+                let (character, fg, bg) = match (upper, lower) {
+                    (None, None) => continue,
+                    (Some(upper), lower) => {
+                        (UPPER_HALF_BLOCK, upper, lower.unwrap_or(Color::Reset))
+                    }
+                    (None, Some(lower)) => (LOWER_HALF_BLOCK, lower, Color::Reset),
+                };
+                let cell = CanvasCell {
+                    character,
+                    fg,
+                    bg,
+                    ..Default::default()
+                };
+                canvas.set_cell(((y / 2) as i16, x as i16), cell);
+            }
+        }
+        canvas.quantize(options.color_depth, options.dithering);
+        canvas
+    }
+}
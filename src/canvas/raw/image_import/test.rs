@@ -0,0 +1,93 @@
+use ratatui::style::Color;
+
+use crate::canvas::raw::image_import::ImageImportOptions;
+use crate::canvas::raw::quantize::ColorDepth;
+use crate::canvas::raw::Canvas;
+use crate::file_formats::image::Image;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+#[test]
+fn half_blocks() {
+    // 3x3 pixels, the last row only fills the upper half of the second row of cells
+    let image = Image::new(
+        3,
+        3,
+        vec![RED, CLEAR, CLEAR, BLUE, BLUE, CLEAR, RED, CLEAR, BLUE],
+    );
+    let canvas = Canvas::from_image(&image, ImageImportOptions::default());
+
+    assert_eq!(canvas.character((0, 0)), '▀');
+    assert_eq!(canvas.fg((0, 0)), Color::Rgb(255, 0, 0));
+    assert_eq!(canvas.bg((0, 0)), Color::Rgb(0, 0, 255));
+    assert_eq!(canvas.character((0, 1)), '▄');
+    assert_eq!(canvas.fg((0, 1)), Color::Rgb(0, 0, 255));
+    assert_eq!(canvas.bg((0, 1)), Color::Reset);
+    assert_eq!(canvas.character((1, 0)), '▀');
+    assert_eq!(canvas.bg((1, 0)), Color::Reset);
+    // Fully transparent cells are left out
+    assert_eq!(canvas.character((0, 2)), ' ');
+    assert_eq!(canvas.character((1, 1)), ' ');
+    assert_eq!(canvas.character((1, 2)), '▀');
+    assert_eq!(canvas.area().columns, 3);
+}
+
+#[test]
+fn scaled_to_columns() {
+    let image = Image::new(8, 4, vec![RED; 32]);
+    let canvas = Canvas::from_image(
+        &image,
+        ImageImportOptions {
+            columns: Some(4),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(canvas.dimensions(), (1, 4));
+}
+
+#[test]
+fn default_columns() {
+    let image = Image::new(200, 20, vec![RED; 4000]);
+    let canvas = Canvas::from_image(&image, ImageImportOptions::default());
+
+    assert_eq!(canvas.dimensions(), (4, 80));
+}
+
+#[test]
+fn clamped_to_coordinates() {
+    let tall = Image::new(1, 70000, vec![RED; 70000]);
+    let canvas = Canvas::from_image(&tall, ImageImportOptions::default());
+
+    assert_eq!(canvas.dimensions(), (32768, 1));
+    assert_eq!(canvas.area().first_row(), 0);
+
+    let wide = Image::new(70000, 1, vec![RED; 70000]);
+    let canvas = Canvas::from_image(
+        &wide,
+        ImageImportOptions {
+            columns: Some(u16::MAX),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(canvas.dimensions(), (1, 32768));
+    assert_eq!(canvas.area().first_column(), 0);
+}
+
+#[test]
+fn quantized() {
+    let image = Image::new(1, 2, vec![RED, BLUE]);
+    let canvas = Canvas::from_image(
+        &image,
+        ImageImportOptions {
+            color_depth: ColorDepth::Indexed256,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(canvas.fg((0, 0)), Color::Indexed(196));
+    assert_eq!(canvas.bg((0, 0)), Color::Indexed(21));
+}
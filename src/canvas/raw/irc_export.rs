@@ -0,0 +1,139 @@
+use derive_more::{Display, Error};
+use ratatui::style::Modifier;
+
+use crate::file_formats::irc::{
+    code_from_color, IrcPalette, IRC_COLOR, IRC_DEFAULT_COLOR, IRC_MODIFIERS, IRC_RESET,
+};
+
+use super::{Canvas, CanvasCell, CanvasIndex, WIDE_CHARACTER_CONTINUATION};
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Display, Error)]
+pub enum IrcExportError {
+    #[display("Canvas contains a color on line {}, column {}, which IRC can only approximate. Consider saving as .ansi or use :w! to save anyways.", _0.0, _0.1)]
+    ApproximatedColor(#[error(ignore)] CanvasIndex),
+    #[display("Canvas contains modifiers on line {}, column {}, which IRC doesn't support. Consider saving as .ansi or use :w! to save anyways.", _0.0, _0.1)]
+    UnsupportedModifier(#[error(ignore)] CanvasIndex),
+}
+
+/// Formatting in effect at a point of an IRC message.
+#[derive(Clone, Copy, PartialEq)]
+struct IrcStyle {
+    fg: u8,
+    bg: u8,
+    modifiers: Modifier,
+}
+
+impl Default for IrcStyle {
+    fn default() -> Self {
+        Self {
+            fg: IRC_DEFAULT_COLOR,
+            bg: IRC_DEFAULT_COLOR,
+            modifiers: Modifier::empty(),
+        }
+    }
+}
+
+impl IrcStyle {
+    fn from_cell(
+        index: CanvasIndex,
+        cell: &CanvasCell,
+        palette: IrcPalette,
+        lossy: bool,
+    ) -> Result<Self, IrcExportError> {
+        let (fg, fg_exact) = code_from_color(cell.fg, palette);
+        let (bg, bg_exact) = code_from_color(cell.bg, palette);
+        let supported = IRC_MODIFIERS
+            .iter()
+            .fold(Modifier::empty(), |modifiers, (modifier, _code)| {
+                modifiers | *modifier
+            });
+        if !lossy {
+            if !(fg_exact && bg_exact) {
+                return Err(IrcExportError::ApproximatedColor(index));
+            }
+            if !supported.contains(cell.modifiers) {
+                return Err(IrcExportError::UnsupportedModifier(index));
+            }
+        }
+        Ok(Self {
+            fg,
+            bg,
+            modifiers: cell.modifiers & supported,
+        })
+    }
+
+    /// Writes the codes changing the formatting from `self` to `target`, where `next` is the
+    /// character following the codes.
+    fn write_change(&mut self, target: Self, next: char, result: &mut String) {
+        if *self == target {
+            return;
+        }
+        if target == Self::default() {
+            result.push(IRC_RESET);
+            *self = target;
+            return;
+        }
+        for (modifier, code) in IRC_MODIFIERS {
+            if self.modifiers.contains(modifier) != target.modifiers.contains(modifier) {
+                result.push(code);
+            }
+        }
+        if (self.fg, self.bg) != (target.fg, target.bg) {
+            // Digits and commas following the color code would be read as part of it
+            let next_is_ambiguous = next.is_ascii_digit() || next == ',';
+            result.push(IRC_COLOR);
+            if (target.fg, target.bg) == (IRC_DEFAULT_COLOR, IRC_DEFAULT_COLOR)
+                && !next_is_ambiguous
+            {
+                // A color code without digits resets the colors
+            } else if target.bg == self.bg && !next_is_ambiguous {
+                result.push_str(&format!("{:02}", target.fg));
+            } else {
+                result.push_str(&format!("{:02},{:02}", target.fg, target.bg));
+            }
+        }
+        *self = target;
+    }
+}
+
+impl Canvas {
+    /// Exports the canvas as text with IRC formatting codes, with one message per row.
+    ///
+    /// Colors are mapped to the color codes of `palette`. Unless `lossy` is set, colors that can
+    /// only be approximated, and modifiers IRC doesn't support, result in an error.
+    pub fn export_irc(&self, palette: IrcPalette, lossy: bool) -> Result<String, IrcExportError> {
+        let mut result = String::new();
+        let mut style = IrcStyle::default();
+        let (mut row, mut column) = (self.area.first_row(), self.area.first_column());
+        for (&(cell_row, cell_column), cell) in self.cells.iter() {
+            if cell_row > row {
+                // Formatting ends with each message, so it doesn't need to be reset
+                for _ in row..cell_row {
+                    result.push('\n');
+                }
+                (row, column) = (cell_row, self.area.first_column());
+                style = IrcStyle::default();
+            }
+            if cell_column > column {
+                style.write_change(IrcStyle::default(), ' ', &mut result);
+                for _ in column..cell_column {
+                    result.push(' ');
+                }
+            }
+            column = cell_column + 1;
+            // The wide character already covers its continuation cell
+            if cell.character == WIDE_CHARACTER_CONTINUATION {
+                continue;
+            }
+            let cell_style = IrcStyle::from_cell((cell_row, cell_column), cell, palette, lossy)?;
+            style.write_change(cell_style, cell.character, &mut result);
+            result.push(cell.character);
+            result.push_str(&cell.combining);
+        }
+        result.push('\n');
+        Ok(result)
+    }
+}
@@ -0,0 +1,150 @@
+use ratatui::style::{Color, Modifier};
+
+use crate::canvas::raw::irc_export::IrcExportError;
+use crate::canvas::raw::Canvas;
+use crate::file_formats::irc::IrcPalette;
+use crate::file_formats::FileFormat;
+
+#[test]
+fn from_extension() {
+    assert_eq!(FileFormat::from_extension(Some("irc")), Ok(FileFormat::Irc));
+}
+
+#[test]
+fn basic() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_character((0, 2), 'b');
+    canvas.set_character((2, 1), 'c');
+    let irc = canvas.export_irc(IrcPalette::Extended, false).unwrap();
+
+    assert_eq!(irc, "a b\n\n c\n");
+}
+
+#[test]
+fn colors() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_fg((0, 0), Color::Red);
+    canvas.set_character((0, 1), 'b');
+    canvas.set_fg((0, 1), Color::Red);
+    canvas.set_bg((0, 1), Color::Indexed(4));
+    canvas.set_character((0, 2), 'c');
+    canvas.set_fg((0, 2), Color::White);
+    canvas.set_bg((0, 2), Color::Indexed(4));
+    canvas.set_character((0, 3), 'd');
+    let irc = canvas.export_irc(IrcPalette::Basic, false).unwrap();
+
+    assert_eq!(irc, "\u{3}05a\u{3}05,02b\u{3}00c\u{f}d\n");
+}
+
+#[test]
+fn background_only() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), ' ');
+    canvas.set_bg((0, 0), Color::Black);
+    let irc = canvas.export_irc(IrcPalette::Basic, false).unwrap();
+
+    assert_eq!(irc, "\u{3}99,01 \n");
+}
+
+#[test]
+fn digit_after_color() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), '1');
+    canvas.set_fg((0, 0), Color::Green);
+    canvas.set_character((0, 1), ',');
+    canvas.set_fg((0, 1), Color::Blue);
+    let irc = canvas.export_irc(IrcPalette::Basic, false).unwrap();
+
+    assert_eq!(irc, "\u{3}03,991\u{3}02,99,\n");
+}
+
+#[test]
+fn modifiers() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_modifiers((0, 0), Modifier::BOLD | Modifier::ITALIC);
+    canvas.set_character((0, 1), 'b');
+    canvas.set_modifiers((0, 1), Modifier::ITALIC | Modifier::UNDERLINED);
+    canvas.set_character((0, 2), 'c');
+    let irc = canvas.export_irc(IrcPalette::Basic, false).unwrap();
+
+    assert_eq!(irc, "\u{2}\u{1d}a\u{2}\u{1f}b\u{f}c\n");
+}
+
+#[test]
+fn gap_resets_formatting() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_bg((0, 0), Color::Red);
+    canvas.set_character((0, 2), 'b');
+    canvas.set_bg((0, 2), Color::Red);
+    let irc = canvas.export_irc(IrcPalette::Basic, false).unwrap();
+
+    assert_eq!(irc, "\u{3}99,05a\u{f} \u{3}99,05b\n");
+}
+
+#[test]
+fn extended_colors() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_fg((0, 0), Color::Rgb(0xff, 0x8c, 0x00));
+    let irc = canvas.export_irc(IrcPalette::Extended, false).unwrap();
+
+    assert_eq!(irc, "\u{3}53a\n");
+}
+
+#[test]
+fn approximated_colors() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 1), 'a');
+    canvas.set_fg((0, 1), Color::Rgb(0xfe, 0x8c, 0x00));
+
+    assert!(matches!(
+        canvas.export_irc(IrcPalette::Extended, false),
+        Err(IrcExportError::ApproximatedColor((0, 1)))
+    ));
+    assert_eq!(
+        canvas.export_irc(IrcPalette::Extended, true).unwrap(),
+        "\u{3}53a\n"
+    );
+    assert_eq!(
+        canvas.export_irc(IrcPalette::Basic, true).unwrap(),
+        "\u{3}07a\n"
+    );
+}
+
+#[test]
+fn unsupported_modifiers() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_modifiers((0, 0), Modifier::BOLD | Modifier::DIM);
+
+    assert!(matches!(
+        canvas.export_irc(IrcPalette::Extended, false),
+        Err(IrcExportError::UnsupportedModifier((0, 0)))
+    ));
+    assert_eq!(
+        canvas.export_irc(IrcPalette::Extended, true).unwrap(),
+        "\u{2}a\n"
+    );
+}
+
+#[test]
+fn round_trip() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_fg((0, 0), Color::LightCyan);
+    canvas.set_bg((0, 0), Color::Rgb(0x47, 0x00, 0x00));
+    canvas.set_character((0, 1), '7');
+    canvas.set_modifiers((0, 1), Modifier::REVERSED | Modifier::CROSSED_OUT);
+    canvas.set_character((1, 3), 'b');
+    let irc = canvas.export_irc(IrcPalette::Extended, false).unwrap();
+    let imported = Canvas::from_irc(&irc).unwrap();
+
+    assert_eq!(imported.area(), canvas.area());
+    for (index, cell) in canvas.cells.iter() {
+        assert_eq!(imported.get(index), cell);
+    }
+}
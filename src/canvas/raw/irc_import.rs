@@ -0,0 +1,150 @@
+use derive_more::{Display, Error};
+use ratatui::style::{Color, Modifier};
+
+use crate::file_formats::irc::{
+    color_from_code, IRC_COLOR, IRC_HEX_COLOR, IRC_MODIFIERS, IRC_MONOSPACE, IRC_RESET,
+};
+
+use super::{is_wide, Canvas, CanvasIndex};
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Display, Error)]
+pub enum IrcImportError {
+    #[display("IRC text contains an illegal character on line {}, column {}", _0.0, _0.1)]
+    IllegalCharacter(#[error(ignore)] CanvasIndex),
+}
+
+/// Reads up to two decimal digits starting at `position`, advancing past them.
+fn read_color_code(characters: &[char], position: &mut usize) -> Option<u8> {
+    let mut code = None;
+    for _ in 0..2 {
+        let Some(digit) = characters.get(*position).and_then(|c| c.to_digit(10)) else {
+            break;
+        };
+        code = Some(code.unwrap_or(0) * 10 + digit as u8);
+        *position += 1;
+    }
+    code
+}
+
+/// Reads six hexadecimal digits starting at `position`, advancing past them.
+fn read_hex_color(characters: &[char], position: &mut usize) -> Option<Color> {
+    let digits: String = characters.get(*position..*position + 6)?.iter().collect();
+    let rgb = u32::from_str_radix(&digits, 16).ok()?;
+    *position += 6;
+    Some(Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
+
+/// Reads the colors following a color code, returning the foreground and background colors, if
+/// any. The background color is only read if the comma is followed by a valid color.
+fn read_colors(
+    characters: &[char],
+    position: &mut usize,
+    read_color: impl Fn(&[char], &mut usize) -> Option<Color>,
+) -> Option<(Color, Option<Color>)> {
+    let fg = read_color(characters, position)?;
+    if characters.get(*position) == Some(&',') {
+        let mut bg_position = *position + 1;
+        if let Some(bg) = read_color(characters, &mut bg_position) {
+            *position = bg_position;
+            return Some((fg, Some(bg)));
+        }
+    }
+    Some((fg, None))
+}
+
+impl Canvas {
+    /// Imports text with IRC formatting codes. Each line is a message, so the formatting is reset
+    /// at line breaks.
+    pub fn from_irc(irc: &str) -> Result<Self, IrcImportError> {
+        let mut canvas = Self::default();
+        let mut fg_color = Color::Reset;
+        let mut bg_color = Color::Reset;
+        let mut modifiers = Modifier::default();
+        let mut canvas_index: CanvasIndex = (0, 0);
+        // Index of the last character, to which combining characters are added
+        let mut previous: Option<CanvasIndex> = None;
+        let characters: Vec<char> = irc.chars().collect();
+        let mut position = 0;
+        while let Some(&character) = characters.get(position) {
+            position += 1;
+            if let Some((modifier, _code)) = IRC_MODIFIERS
+                .iter()
+                .find(|(_modifier, code)| *code == character)
+            {
+                modifiers.toggle(*modifier);
+                continue;
+            }
+            match character {
+                '\u{0d}' => {
+                    // Carriage return, go to beginning of current line
+                    canvas_index.1 = 0;
+                    previous = None;
+                    continue;
+                }
+                '\u{0a}' => {
+                    // Line feed, starts a new message
+                    canvas_index.1 = 0;
+                    canvas_index.0 += 1;
+                    previous = None;
+                    (fg_color, bg_color) = (Color::Reset, Color::Reset);
+                    modifiers = Modifier::default();
+                    continue;
+                }
+                IRC_COLOR | IRC_HEX_COLOR => {
+                    let colors = if character == IRC_COLOR {
+                        read_colors(&characters, &mut position, |characters, position| {
+                            read_color_code(characters, position).map(color_from_code)
+                        })
+                    } else {
+                        read_colors(&characters, &mut position, read_hex_color)
+                    };
+                    match colors {
+                        Some((fg, bg)) => {
+                            fg_color = fg;
+                            bg_color = bg.unwrap_or(bg_color);
+                        }
+                        None => (fg_color, bg_color) = (Color::Reset, Color::Reset),
+                    }
+                    continue;
+                }
+                IRC_RESET => {
+                    (fg_color, bg_color) = (Color::Reset, Color::Reset);
+                    modifiers = Modifier::default();
+                    continue;
+                }
+                // Monospace has no meaning on a canvas
+                IRC_MONOSPACE => continue,
+                character if character.is_control() => {
+                    return Err(IrcImportError::IllegalCharacter(canvas_index));
+                }
+                _ => (),
+            }
+
+            if let Some(previous) = previous {
+                if canvas.continues_grapheme(previous, character) {
+                    canvas.add_combining_character(previous, character);
+                    continue;
+                }
+            }
+
+            if !(character == ' '
+                && fg_color == Color::Reset
+                && bg_color == Color::Reset
+                && modifiers == Modifier::default())
+            {
+                canvas.set_character(canvas_index, character);
+                canvas.set_fg(canvas_index, fg_color);
+                canvas.set_bg(canvas_index, bg_color);
+                canvas.set_modifiers(canvas_index, modifiers);
+                canvas.pair_wide_character(canvas_index);
+            }
+
+            previous = Some(canvas_index);
+            canvas_index.1 += if is_wide(character) { 2 } else { 1 };
+        }
+        Ok(canvas)
+    }
+}
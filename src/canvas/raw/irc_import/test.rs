@@ -0,0 +1,88 @@
+use ratatui::style::{Color, Modifier};
+
+use crate::canvas::raw::irc_import::IrcImportError;
+use crate::canvas::raw::Canvas;
+
+#[test]
+fn basic() {
+    let canvas = Canvas::from_irc("ab\r\n c").unwrap();
+
+    assert_eq!(canvas.get(&(0, 0)).character, 'a');
+    assert_eq!(canvas.get(&(0, 1)).character, 'b');
+    assert_eq!(canvas.get(&(1, 1)).character, 'c');
+    assert_eq!(canvas.cells.len(), 3);
+}
+
+#[test]
+fn colors() {
+    let canvas = Canvas::from_irc("\u{3}4a\u{3}12,1b\u{3}3c\u{3}d\u{3}52,99e").unwrap();
+
+    assert_eq!(canvas.get(&(0, 0)).fg, Color::LightRed);
+    assert_eq!(canvas.get(&(0, 0)).bg, Color::Reset);
+    assert_eq!(canvas.get(&(0, 1)).fg, Color::LightBlue);
+    assert_eq!(canvas.get(&(0, 1)).bg, Color::Black);
+    assert_eq!(canvas.get(&(0, 2)).fg, Color::Green);
+    assert_eq!(canvas.get(&(0, 2)).bg, Color::Black);
+    assert_eq!(canvas.get(&(0, 3)).fg, Color::Reset);
+    assert_eq!(canvas.get(&(0, 3)).bg, Color::Reset);
+    assert_eq!(canvas.get(&(0, 4)).fg, Color::Rgb(0xff, 0x00, 0x00));
+    assert_eq!(canvas.get(&(0, 4)).bg, Color::Reset);
+}
+
+#[test]
+fn color_followed_by_digits() {
+    let canvas = Canvas::from_irc("\u{3}041\u{3}2,x").unwrap();
+
+    assert_eq!(canvas.get(&(0, 0)).character, '1');
+    assert_eq!(canvas.get(&(0, 0)).fg, Color::LightRed);
+    assert_eq!(canvas.get(&(0, 1)).character, ',');
+    assert_eq!(canvas.get(&(0, 1)).fg, Color::Blue);
+    assert_eq!(canvas.get(&(0, 2)).character, 'x');
+}
+
+#[test]
+fn hex_colors() {
+    let canvas = Canvas::from_irc("\u{4}FF8000,000010a\u{4}b").unwrap();
+
+    assert_eq!(canvas.get(&(0, 0)).fg, Color::Rgb(0xff, 0x80, 0x00));
+    assert_eq!(canvas.get(&(0, 0)).bg, Color::Rgb(0x00, 0x00, 0x10));
+    assert_eq!(canvas.get(&(0, 1)).fg, Color::Reset);
+    assert_eq!(canvas.get(&(0, 1)).bg, Color::Reset);
+}
+
+#[test]
+fn modifiers() {
+    let canvas = Canvas::from_irc("\u{2}\u{1d}a\u{2}\u{1f}b\u{f}c\u{1e}\u{16}\u{11}d").unwrap();
+
+    assert_eq!(
+        canvas.get(&(0, 0)).modifiers,
+        Modifier::BOLD | Modifier::ITALIC
+    );
+    assert_eq!(
+        canvas.get(&(0, 1)).modifiers,
+        Modifier::ITALIC | Modifier::UNDERLINED
+    );
+    assert_eq!(canvas.get(&(0, 2)).modifiers, Modifier::empty());
+    assert_eq!(
+        canvas.get(&(0, 3)).modifiers,
+        Modifier::CROSSED_OUT | Modifier::REVERSED
+    );
+}
+
+#[test]
+fn formatting_ends_with_line() {
+    let canvas = Canvas::from_irc("\u{2}\u{3}4a\nb").unwrap();
+
+    assert_eq!(canvas.get(&(1, 0)).fg, Color::Reset);
+    assert_eq!(canvas.get(&(1, 0)).modifiers, Modifier::empty());
+}
+
+#[test]
+fn illegal_character() {
+    let result = Canvas::from_irc("a\u{7}");
+
+    assert!(matches!(
+        result,
+        Err(IrcImportError::IllegalCharacter((0, 1)))
+    ));
+}
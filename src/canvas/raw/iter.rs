@@ -4,7 +4,7 @@ use std::iter::Peekable;
 use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
 
-use super::{Canvas, CanvasIndex};
+use super::{Canvas, CanvasIndex, Grapheme};
 use crate::DirectionFree;
 
 pub mod tracer;
@@ -68,7 +68,7 @@ impl Default for WordBoundaryType {
 pub enum StopConditionContent {
     CharacterChange,
     WordBoundary(WordBoundaryType),
-    CharacterMatch(char),
+    GraphemeMatch(Grapheme),
     // CellContent(fn(&CanvasCell) -> bool),
 }
 
@@ -184,12 +184,8 @@ impl<'a> Iterator for CanvasIndexIterator<'a> {
                                         false
                                     }
                                 }
-                                StopConditionContent::CharacterMatch(ch) => {
-                                    if *ch == canvas.character((row, column)) {
-                                        true
-                                    } else {
-                                        false
-                                    }
+                                StopConditionContent::GraphemeMatch(grapheme) => {
+                                    grapheme.matches(canvas.get(&(row, column)))
                                 } // StopCondition::CellContent(target) => {
                                   //     let cell = self.canvas.cell((row, column));
                                   //     target(&cell)
@@ -88,7 +88,7 @@ fn test_canvas_index_iterator() {
         ("    4567    abcd", (-3, 6), DirectionFree::from(Direction::Up), StopConditionContent::CharacterChange, 1, (-3, 6)),
         ("    4567    abcd", (-3, 6), DirectionFree::from(Direction::Down), StopConditionContent::CharacterChange, 1, (0, 6)),
         ("    4567    abcd", (-3, 3), DirectionFree::from(Direction::Down), StopConditionContent::CharacterChange, 1, (-3, 3)),
-        ("    4567    abcd", (0, 1), DirectionFree::from(Direction::Right), StopConditionContent::CharacterMatch('6'), 1, (0, 6)),
+        ("    4567    abcd", (0, 1), DirectionFree::from(Direction::Right), StopConditionContent::GraphemeMatch('6'.into()), 1, (0, 6)),
         ("0  34 678   abcd", (0, 9), DirectionFree::from(Direction::Left), StopConditionContent::WordBoundary(WordBoundaryType::START), 2, (0, 4)),
 
         // TODO: Have to ponder a bit more on how to implement this feature. Test disabled for now.
@@ -110,3 +110,27 @@ fn test_canvas_index_iterator() {
         assert_eq!(it.last(), Some(end));
     }
 }
+
+#[test]
+fn grapheme_match_needs_whole_cluster() {
+    use super::{CanvasIndexIterator, CanvasIterationJump, StopCondition, StopConditionContent};
+    use crate::canvas::raw::{Canvas, Grapheme};
+    use crate::{Direction, DirectionFree};
+
+    let canvas = Canvas::from_txt("xe\u{301}e".to_string()).unwrap();
+    let mut accented = Grapheme::from('e');
+    accented.push('\u{301}');
+    for (grapheme, end) in [(Grapheme::from('e'), (0, 2)), (accented, (0, 1))] {
+        let it = CanvasIndexIterator::new(
+            (0, 0),
+            DirectionFree::from(Direction::Right),
+            CanvasIterationJump::NoJump,
+            StopCondition::Content {
+                canvas: &canvas,
+                condition: StopConditionContent::GraphemeMatch(grapheme),
+            },
+            1,
+        );
+        assert_eq!(it.last(), Some(end));
+    }
+}
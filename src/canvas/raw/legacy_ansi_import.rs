@@ -0,0 +1,64 @@
+use ratatui::style::{Color, Modifier};
+
+use crate::file_formats::cp437;
+use crate::file_formats::sauce::Sauce;
+
+use super::ansi_import::AnsiImportError;
+use super::terminal_ansi_import::VirtualTerminal;
+use super::Canvas;
+
+#[cfg(test)]
+mod test;
+
+/// Width of the terminals legacy ANSI art was made for, at which lines wrap.
+pub const LEGACY_ANSI_COLUMNS: i16 = 80;
+
+/// Converts the style of a cell from DOS semantics, where bold means a bright foreground color,
+/// to the semantics of upaint.
+pub(super) fn bold_as_bright(fg: Color, modifiers: Modifier) -> (Color, Modifier) {
+    if !modifiers.contains(Modifier::BOLD) {
+        return (fg, modifiers);
+    }
+    let fg = match fg {
+        Color::Reset | Color::Gray => Color::White,
+        Color::Black => Color::DarkGray,
+        Color::Red => Color::LightRed,
+        Color::Green => Color::LightGreen,
+        Color::Yellow => Color::LightYellow,
+        Color::Blue => Color::LightBlue,
+        Color::Magenta => Color::LightMagenta,
+        Color::Cyan => Color::LightCyan,
+        other => return (other, modifiers),
+    };
+    (fg, modifiers - Modifier::BOLD)
+}
+
+/// Returns true if `bytes` look like a legacy ANSI file, rather than a UTF-8 one, i.e. if the
+/// contents before the SAUCE record, if any, aren't valid UTF-8.
+pub fn is_legacy_ansi(bytes: &[u8]) -> bool {
+    std::str::from_utf8(Sauce::split(bytes).0).is_err()
+}
+
+impl Canvas {
+    /// Imports a classic BBS-era ANSI file, as it would be displayed by DOS `ANSI.SYS`.
+    ///
+    /// The file is decoded as CP437 and run through a virtual terminal, so cursor movement,
+    /// saving and restoring the cursor and erasing are supported in addition to SGR sequences.
+    /// Lines wrap at `LEGACY_ANSI_COLUMNS`, bold is shown as bright foreground colors and
+    /// unsupported or private sequences are ignored. The SAUCE record is removed from the
+    /// artwork and stored in the canvas metadata.
+    pub fn from_legacy_ansi(bytes: &[u8]) -> Result<Self, AnsiImportError> {
+        let (contents, sauce) = Sauce::split(bytes);
+        let mut terminal = VirtualTerminal::dos();
+        terminal.run(contents.iter().map(|&byte| match byte {
+            // Control characters with a meaning in ANSI files are not shown as glyphs
+            0x0a | 0x0d | 0x1b => byte as char,
+            byte => cp437::decode(byte),
+        }))?;
+        let mut canvas = terminal.into_canvas();
+        if let Some(sauce) = sauce {
+            canvas.metadata = sauce.into();
+        }
+        Ok(canvas)
+    }
+}
@@ -0,0 +1,88 @@
+use ratatui::style::{Color, Modifier};
+
+use crate::canvas::raw::ansi_import::AnsiImportError;
+use crate::canvas::raw::terminal_ansi_import::test::rows;
+use crate::canvas::raw::Canvas;
+use crate::file_formats::sauce::test::sauce_record;
+
+#[test]
+fn cp437() {
+    let canvas = Canvas::from_legacy_ansi(b"\xc9\xcd\xbb\r\n\xb0\x01\x8f").unwrap();
+
+    assert_eq!(canvas.get(&(0, 0)).character, '╔');
+    assert_eq!(canvas.get(&(0, 1)).character, '═');
+    assert_eq!(canvas.get(&(0, 2)).character, '╗');
+    assert_eq!(canvas.get(&(1, 0)).character, '░');
+    assert_eq!(canvas.get(&(1, 1)).character, '☺');
+    assert_eq!(canvas.get(&(1, 2)).character, 'Å');
+}
+
+#[test]
+fn wrap() {
+    let mut bytes = vec![b'a'; 80];
+    bytes.push(b'b');
+    let canvas = Canvas::from_legacy_ansi(&bytes).unwrap();
+
+    assert_eq!(canvas.get(&(0, 79)).character, 'a');
+    assert_eq!(canvas.get(&(1, 0)).character, 'b');
+    assert_eq!(canvas.area().columns, 80);
+}
+
+#[test]
+fn cursor_forward() {
+    let canvas = Canvas::from_legacy_ansi(b"a\x1b[3Cb\x1b[Cc\x1b[100Cd").unwrap();
+
+    assert_eq!(canvas.get(&(0, 0)).character, 'a');
+    assert_eq!(canvas.get(&(0, 4)).character, 'b');
+    assert_eq!(canvas.get(&(0, 6)).character, 'c');
+    assert_eq!(canvas.get(&(0, 79)).character, 'd');
+}
+
+#[test]
+fn bold_is_bright() {
+    let canvas = Canvas::from_legacy_ansi(b"\x1b[1;31ma\x1b[0;1mb\x1b[0;31mc").unwrap();
+
+    assert_eq!(canvas.get(&(0, 0)).fg, Color::LightRed);
+    assert_eq!(canvas.get(&(0, 0)).modifiers, Modifier::empty());
+    assert_eq!(canvas.get(&(0, 1)).fg, Color::White);
+    assert_eq!(canvas.get(&(0, 2)).fg, Color::Red);
+}
+
+#[test]
+fn sauce() {
+    let mut bytes = b"abc\x1a".to_vec();
+    bytes.extend(sauce_record("Title", "Author", "Group", &["comment"]));
+    let canvas = Canvas::from_legacy_ansi(&bytes).unwrap();
+
+    assert_eq!(canvas.cells.len(), 3);
+    assert_eq!(canvas.area().columns, 3);
+    assert_eq!(canvas.metadata.title, "Title");
+    assert_eq!(canvas.metadata.author, "Author");
+    assert_eq!(canvas.metadata.group, "Group");
+}
+
+#[test]
+fn cursor_movement_and_erasing() {
+    let canvas = Canvas::from_legacy_ansi(include_bytes!("cursor.ans")).unwrap();
+
+    assert_eq!(rows(&canvas), ["╔══╗x z", "║  ║ y", "╚══╝", "", "aXc"]);
+    assert_eq!(canvas.get(&(0, 4)).fg, Color::LightRed);
+    assert_eq!(canvas.get(&(0, 6)).fg, Color::Reset);
+}
+
+#[test]
+fn unsupported_sequences_are_ignored() {
+    let canvas = Canvas::from_legacy_ansi(b"a\x1b[5Zb\x1b[?25lc\x1b[>1;2cd").unwrap();
+
+    assert_eq!(rows(&canvas), ["abcd"]);
+}
+
+#[test]
+fn unfinished_sequence() {
+    let result = Canvas::from_legacy_ansi(b"a\x1b[1;3");
+
+    assert!(matches!(
+        result,
+        Err(AnsiImportError::UnfinishedEscapeSequence((0, 1)))
+    ));
+}
@@ -2,17 +2,22 @@ use crate::canvas::raw::CellContentType;
 use crate::Ground;
 use derive_more::IntoIterator;
 use ratatui::style::{Color, Modifier};
-use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::canvas::raw::CanvasIndex;
+use crate::canvas::raw::{is_wide, WIDE_CHARACTER_CONTINUATION};
 
 use super::yank::CanvasYank;
 use super::Canvas;
 use super::CanvasCell;
+use super::CanvasMetadata;
+use super::Grapheme;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CanvasDiffUnit {
     pub ch: Option<char>,
+    pub combining: Option<String>,
     pub fg: Option<Color>,
     pub bg: Option<Color>,
     pub modifier: Option<Modifier>,
@@ -22,6 +27,7 @@ impl CanvasDiffUnit {
     pub fn ch(ch: char) -> Self {
         Self {
             ch: Some(ch),
+            combining: Some(String::new()),
             fg: None,
             bg: None,
             modifier: None,
@@ -30,6 +36,7 @@ impl CanvasDiffUnit {
     pub fn fg(fg: Color) -> Self {
         Self {
             ch: None,
+            combining: None,
             fg: Some(fg),
             bg: None,
             modifier: None,
@@ -38,6 +45,7 @@ impl CanvasDiffUnit {
     pub fn bg(bg: Color) -> Self {
         Self {
             ch: None,
+            combining: None,
             fg: None,
             bg: Some(bg),
             modifier: None,
@@ -46,6 +54,7 @@ impl CanvasDiffUnit {
     pub fn modifier(modifier: Modifier) -> Self {
         Self {
             ch: None,
+            combining: None,
             fg: None,
             bg: None,
             modifier: Some(modifier),
@@ -86,6 +95,7 @@ impl<'a> Iterator for CanvasModificationToDiffIter<'a> {
             let index = (index.0 + index_yank.0, index.1 + index_yank.1);
             if content_type.contains(CellContentType::TEXT) {
                 entry.ch = Some(cell_yank.character);
+                entry.combining = Some(cell_yank.combining);
             }
             if content_type.contains(CellContentType::FG) {
                 entry.fg = Some(cell_yank.fg);
@@ -104,6 +114,18 @@ impl<'a> Iterator for CanvasModificationToDiffIter<'a> {
             match value {
                 CanvasModification::SetCharacter(index, character) => {
                     entry.ch = Some(character);
+                    entry.combining = Some(String::new());
+                    index
+                }
+                CanvasModification::SetGrapheme(index, grapheme) => {
+                    entry.ch = Some(grapheme.character());
+                    entry.combining = Some(grapheme.combining().to_string());
+                    index
+                }
+                CanvasModification::AddCombiningCharacter(index, character) => {
+                    let mut combining = canvas.combining(index).to_string();
+                    combining.push(character);
+                    entry.combining = Some(combining);
                     index
                 }
                 CanvasModification::SetFgColor(index, color) => {
@@ -148,6 +170,7 @@ impl<'a> Iterator for CanvasModificationToDiffIter<'a> {
                 }
                 CanvasModification::SetCell(index, cell) => {
                     entry.ch = Some(cell.character);
+                    entry.combining = Some(cell.combining);
                     entry.fg = Some(cell.fg);
                     entry.bg = Some(cell.bg);
                     entry.modifier = Some(cell.modifiers);
@@ -157,6 +180,8 @@ impl<'a> Iterator for CanvasModificationToDiffIter<'a> {
                     self.paste_iter = Some((index, yank.content_type, yank.cells.into_iter()));
                     return self.next();
                 }
+                // Doesn't affect any cells, handled by `CanvasDiffBuilder::add_modifications`
+                CanvasModification::SetMetadata(_) => return None,
             }
         };
         Some((index, entry))
@@ -167,10 +192,11 @@ impl<'a> Iterator for CanvasModificationToDiffIter<'a> {
 pub struct CanvasDiffBuilder {
     #[into_iterator(owned, ref, ref_mut)]
     pub entries: BTreeMap<CanvasIndex, CanvasDiffUnit>,
+    pub metadata: Option<CanvasMetadata>,
 }
 
-#[derive(Debug, Default, Clone)]
-pub struct CanvasDiff(Vec<(CanvasIndex, CanvasDiffUnit)>);
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CanvasDiff(Vec<(CanvasIndex, CanvasDiffUnit)>, Option<CanvasMetadata>);
 
 impl CanvasDiff {
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (&CanvasIndex, &mut CanvasDiffUnit)> {
@@ -179,6 +205,31 @@ impl CanvasDiff {
             .map(|value| (&value.0, &mut value.1))
     }
 
+    /// Number of cells changed by the diff.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Indices of the cells changed by the diff.
+    pub fn indices(&self) -> impl Iterator<Item = CanvasIndex> + '_ {
+        self.0.iter().map(|(index, _unit)| *index)
+    }
+
+    /// Merges the reversed diffs of two consecutive changes into the reversed diff of both, where
+    /// `self` is the one of the earlier change. Its values take precedence, as they are the ones
+    /// from before both changes.
+    pub fn merge_reversed(self, later: CanvasDiff) -> CanvasDiff {
+        let mut builder = CanvasDiffBuilder::default();
+        diff_builder_add(&mut builder, self.0, true);
+        diff_builder_add(&mut builder, later.0, false);
+        builder.metadata = self.1.or(later.1);
+        builder.serialize()
+    }
+
     pub fn from_modifications<T>(canvas: &Canvas, modifications: T) -> Self
     where
         T: IntoIterator<Item = CanvasModification>,
@@ -190,7 +241,7 @@ impl CanvasDiff {
     where
         T: IntoIterator<Item = (CanvasIndex, CanvasDiffUnit)>,
     {
-        Self(diff_units.into_iter().collect())
+        Self(diff_units.into_iter().collect(), None)
     }
 }
 
@@ -206,6 +257,11 @@ pub fn diff_builder_add(
                 target.ch = Some(ch);
             }
         }
+        if let Some(combining) = entry.combining {
+            if !(!overwrite && target.combining.is_some()) {
+                target.combining = Some(combining);
+            }
+        }
         if let Some(fg) = entry.fg {
             if !(!overwrite && target.fg.is_some()) {
                 target.fg = Some(fg);
@@ -230,8 +286,80 @@ impl CanvasDiffBuilder {
     ) -> Self {
         let mut selff = Self::default();
         selff.add_modifications(modifications, canvas, true);
+        selff.pair_wide_characters(canvas);
         selff
     }
+    /// Returns the cell at `index` after applying the diff to `canvas`.
+    fn resulting_cell(&self, canvas: &Canvas, index: CanvasIndex) -> CanvasCell {
+        let mut cell = canvas.cell(index);
+        if let Some(entry) = self.entries.get(&index) {
+            cell.character = entry.ch.unwrap_or(cell.character);
+            if let Some(combining) = &entry.combining {
+                cell.combining.clone_from(combining);
+            }
+            cell.fg = entry.fg.unwrap_or(cell.fg);
+            cell.bg = entry.bg.unwrap_or(cell.bg);
+            cell.modifiers = entry.modifier.unwrap_or(cell.modifiers);
+        }
+        cell
+    }
+    /// Extends the diff so that every wide character is followed by a continuation cell with
+    /// the same colors and modifiers, and every continuation cell follows a wide character.
+    ///
+    /// Wide characters get a continuation cell added, unless the cell to their right is set
+    /// explicitly by the diff, in which case the wide character is replaced by a space. Orphaned
+    /// continuation cells are replaced by spaces.
+    fn pair_wide_characters(&mut self, canvas: &Canvas) {
+        let explicit: BTreeSet<CanvasIndex> = self
+            .entries
+            .iter()
+            .filter(|(_index, entry)| entry.ch.is_some())
+            .map(|(index, _entry)| *index)
+            .collect();
+        let mut queue: BTreeSet<CanvasIndex> = self
+            .entries
+            .keys()
+            .flat_map(|&(row, column)| [(row, column - 1), (row, column), (row, column + 1)])
+            .collect();
+        // Only cells to the right of the current one are modified, so every cell is done once
+        // it has been popped
+        while let Some((row, column)) = queue.pop_first() {
+            let cell = self.resulting_cell(canvas, (row, column));
+            let right = (row, column + 1);
+            if is_wide(cell.character) {
+                let right_cell = self.resulting_cell(canvas, right);
+                let continuation = CanvasCell {
+                    character: WIDE_CHARACTER_CONTINUATION,
+                    combining: String::new(),
+                    ..cell
+                };
+                if right_cell == continuation {
+                    continue;
+                }
+                if explicit.contains(&right) && right_cell.character != WIDE_CHARACTER_CONTINUATION
+                {
+                    self.add_diff_unit((row, column), CanvasDiffUnit::ch(' '));
+                    queue.insert(right);
+                } else {
+                    self.add_diff_unit(
+                        right,
+                        CanvasDiffUnit {
+                            ch: Some(continuation.character),
+                            combining: Some(continuation.combining),
+                            fg: Some(continuation.fg),
+                            bg: Some(continuation.bg),
+                            modifier: Some(continuation.modifiers),
+                        },
+                    );
+                    queue.insert((row, column + 2));
+                }
+            } else if cell.character == WIDE_CHARACTER_CONTINUATION
+                && !is_wide(self.resulting_cell(canvas, (row, column - 1)).character)
+            {
+                self.add_diff_unit((row, column), CanvasDiffUnit::ch(' '));
+            }
+        }
+    }
     pub fn add_modifications(
         &mut self,
         modifications: impl IntoIterator<Item = CanvasModification>,
@@ -239,23 +367,41 @@ impl CanvasDiffBuilder {
         overwrite: bool,
     ) {
         for operation in modifications {
+            if let CanvasModification::SetMetadata(metadata) = operation {
+                if overwrite || self.metadata.is_none() {
+                    self.metadata = Some(metadata);
+                }
+                continue;
+            }
             diff_builder_add(self, operation.to_diff(canvas), overwrite);
         }
     }
     pub fn add_diff(&mut self, diff: Self, overwrite: bool) {
+        if let Some(metadata) = diff.metadata {
+            if overwrite || self.metadata.is_none() {
+                self.metadata = Some(metadata);
+            }
+        }
         diff_builder_add(self, diff.entries, overwrite);
     }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.metadata.is_none()
+    }
     pub fn add_diff_unit(&mut self, index: CanvasIndex, diff: CanvasDiffUnit) {
         diff_builder_add(self, [(index, diff)], true);
     }
     pub fn serialize(self) -> CanvasDiff {
-        CanvasDiff(self.entries.into_iter().collect())
+        CanvasDiff(self.entries.into_iter().collect(), self.metadata)
     }
 }
 
 #[derive(Debug, Clone)]
 pub enum CanvasModification {
     SetCharacter(CanvasIndex, char),
+    /// Adds a character to the grapheme cluster of the cell.
+    AddCombiningCharacter(CanvasIndex, char),
+    /// Sets the character and the characters combining with it.
+    SetGrapheme(CanvasIndex, Grapheme),
     SetFgColor(CanvasIndex, Color),
     SetBgColor(CanvasIndex, Color),
     AddModifier(CanvasIndex, Modifier),
@@ -263,6 +409,7 @@ pub enum CanvasModification {
     SetModifiers(CanvasIndex, Modifier),
     SetCell(CanvasIndex, CanvasCell),
     Paste(CanvasIndex, CanvasYank),
+    SetMetadata(CanvasMetadata),
 }
 
 impl CanvasModification {
@@ -281,9 +428,17 @@ impl Canvas {
                 .into_iter()
                 .map(|value| (&value.0, &mut value.1)),
         );
+        self.apply_metadata_diff(&mut diff.1);
     }
     pub fn apply_diff_builder<'a>(&mut self, diff: &mut CanvasDiffBuilder) {
-        self.apply_diff_by_iterator(diff.into_iter())
+        self.apply_diff_by_iterator(diff.entries.iter_mut());
+        self.apply_metadata_diff(&mut diff.metadata);
+    }
+    // Swaps metadata of the canvas with the one in the diff, if any, reversing the diff in-place
+    fn apply_metadata_diff(&mut self, metadata: &mut Option<CanvasMetadata>) {
+        if let Some(metadata) = metadata {
+            std::mem::swap(&mut self.metadata, metadata);
+        }
     }
     // Applies a diff iterator to canvas and reverses diff in-place
     fn apply_diff_by_iterator<'a>(
@@ -292,11 +447,17 @@ impl Canvas {
     ) {
         for (index, diff) in diff {
             let index = *index;
+            // Setting the character removes the combining characters, so they are swapped
+            // afterwards
+            let old_combining = self.combining(index).to_string();
             if let Some(ch) = &mut diff.ch {
                 let old = self.character(index);
                 self.set_character(index, *ch);
                 *ch = old;
             }
+            if let Some(combining) = &mut diff.combining {
+                self.set_combining(index, std::mem::replace(combining, old_combining));
+            }
             if let Some(fg) = &mut diff.fg {
                 let old = self.fg(index);
                 self.set_fg(index, *fg);
@@ -11,6 +11,7 @@ impl Canvas {
             let cell = self.get_mut(&index);
             if yank.content_type.contains(CellContentType::TEXT) {
                 cell.character = cell_yank.character;
+                cell.combining.clone_from(&cell_yank.combining);
             }
             if yank.content_type.contains(CellContentType::FG) {
                 cell.fg = cell_yank.fg;
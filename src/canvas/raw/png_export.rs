@@ -0,0 +1,133 @@
+use ratatui::style::{Color, Modifier};
+
+use crate::config::color_theme::ColorThemeCanvas;
+use crate::Ground;
+
+use super::{Canvas, CanvasCell, WIDE_CHARACTER_CONTINUATION};
+
+#[cfg(test)]
+mod test;
+
+pub const PNG_CELL_WIDTH: u32 = 8;
+pub const PNG_CELL_HEIGHT: u32 = 13;
+const PNG_UNDERLINE_ROW: u32 = 11;
+const PNG_CROSSED_OUT_ROW: u32 = 6;
+
+/// Colors used for `Color::Reset` when the color theme has no default colors.
+const PNG_DEFAULT_FG: (u8, u8, u8) = (229, 229, 229);
+const PNG_DEFAULT_BG: (u8, u8, u8) = (0, 0, 0);
+
+/// The public domain "Fixed" 8x13 font from the X.Org misc-misc fonts.
+///
+/// Consists of one record per glyph, sorted by code point, where each record is the code point as
+/// a little endian `u16` followed by one byte per pixel row, with the most significant bit as the
+/// leftmost pixel.
+const FONT: &[u8] = include_bytes!("png_export/font_8x13.bin");
+const FONT_RECORD_SIZE: usize = 2 + PNG_CELL_HEIGHT as usize;
+
+/// Returns the bitmap of `character`, or `None` if the font doesn't have it.
+fn glyph(character: char) -> Option<&'static [u8]> {
+    let code_point = u16::try_from(character as u32).ok()?;
+    let records = FONT.len() / FONT_RECORD_SIZE;
+    let record = |i: usize| &FONT[i * FONT_RECORD_SIZE..(i + 1) * FONT_RECORD_SIZE];
+    let (mut low, mut high) = (0, records);
+    while low < high {
+        let middle = (low + high) / 2;
+        let record = record(middle);
+        match u16::from_le_bytes([record[0], record[1]]).cmp(&code_point) {
+            std::cmp::Ordering::Less => low = middle + 1,
+            std::cmp::Ordering::Greater => high = middle,
+            std::cmp::Ordering::Equal => return Some(&record[2..]),
+        }
+    }
+    None
+}
+
+/// Bitmap drawn for characters missing from the font: a box filling most of the cell.
+fn replacement_glyph() -> [u8; PNG_CELL_HEIGHT as usize] {
+    let mut bitmap = [0b0100_0010; PNG_CELL_HEIGHT as usize];
+    bitmap[0] = 0;
+    bitmap[1] = 0b0111_1110;
+    bitmap[PNG_CELL_HEIGHT as usize - 2] = 0b0111_1110;
+    bitmap[PNG_CELL_HEIGHT as usize - 1] = 0;
+    bitmap
+}
+
+/// Returns the pixel rows of `cell`, with modifiers that change the shape of the glyph applied.
+fn cell_bitmap(cell: &CanvasCell) -> [u8; PNG_CELL_HEIGHT as usize] {
+    let mut bitmap = match glyph(cell.character) {
+        Some(glyph) => glyph.try_into().unwrap(),
+        // Wide characters are drawn within their own cell, leaving their continuation blank
+        None if cell.character == WIDE_CHARACTER_CONTINUATION => [0; PNG_CELL_HEIGHT as usize],
+        None => replacement_glyph(),
+    };
+    if cell.modifiers.contains(Modifier::HIDDEN) {
+        return [0; PNG_CELL_HEIGHT as usize];
+    }
+    if cell.modifiers.contains(Modifier::BOLD) {
+        for row in bitmap.iter_mut() {
+            *row |= *row >> 1;
+        }
+    }
+    if cell.modifiers.contains(Modifier::UNDERLINED) {
+        bitmap[PNG_UNDERLINE_ROW as usize] = 0xff;
+    }
+    if cell.modifiers.contains(Modifier::CROSSED_OUT) {
+        bitmap[PNG_CROSSED_OUT_ROW as usize] = 0xff;
+    }
+    bitmap
+}
+
+impl Canvas {
+    /// Rasterizes the canvas to a PNG image, drawing each cell as `PNG_CELL_WIDTH` x
+    /// `PNG_CELL_HEIGHT` pixels with a built-in bitmap font.
+    ///
+    /// Colors are resolved through `color_theme`. Characters missing from the font are drawn as a
+    /// replacement box.
+    pub fn export_png(&self, color_theme: &ColorThemeCanvas) -> anyhow::Result<Vec<u8>> {
+        let area = self.area();
+        let width = area.columns as u32 * PNG_CELL_WIDTH;
+        let height = area.rows as u32 * PNG_CELL_HEIGHT;
+        if width == 0 || height == 0 {
+            anyhow::bail!("Cannot export an empty canvas as PNG");
+        }
+        let default_fg = color_theme
+            .resolve_rgb(Color::Reset, Ground::Foreground)
+            .unwrap_or(PNG_DEFAULT_FG);
+        let default_bg = color_theme
+            .resolve_rgb(Color::Reset, Ground::Background)
+            .unwrap_or(PNG_DEFAULT_BG);
+
+        let mut pixels =
+            [default_bg.0, default_bg.1, default_bg.2].repeat((width * height) as usize);
+        for (&(row, column), cell) in self.cells.iter() {
+            let mut fg = color_theme
+                .resolve_rgb(cell.fg, Ground::Foreground)
+                .unwrap_or(default_fg);
+            let mut bg = color_theme
+                .resolve_rgb(cell.bg, Ground::Background)
+                .unwrap_or(default_bg);
+            if cell.modifiers.contains(Modifier::REVERSED) {
+                (fg, bg) = (bg, fg);
+            }
+            let x = (column - area.first_column()) as u32 * PNG_CELL_WIDTH;
+            let y = (row - area.first_row()) as u32 * PNG_CELL_HEIGHT;
+            for (dy, bits) in cell_bitmap(cell).into_iter().enumerate() {
+                for dx in 0..PNG_CELL_WIDTH {
+                    let (r, g, b) = if bits & (0x80 >> dx) != 0 { fg } else { bg };
+                    let offset = (((y + dy as u32) * width + x + dx) * 3) as usize;
+                    pixels[offset..offset + 3].copy_from_slice(&[r, g, b]);
+                }
+            }
+        }
+
+        let mut result = Vec::new();
+        let mut encoder = png::Encoder::new(&mut result, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&pixels)?;
+        writer.finish()?;
+        Ok(result)
+    }
+}
@@ -0,0 +1,189 @@
+use ratatui::style::{Color, Modifier};
+
+use crate::canvas::raw::html_export::test::color_theme;
+use crate::canvas::raw::png_export::{PNG_CELL_HEIGHT, PNG_CELL_WIDTH};
+use crate::canvas::raw::Canvas;
+use crate::file_formats::FileFormat;
+
+/// Decoded PNG, with pixels as RGB triplets.
+struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<(u8, u8, u8)>,
+}
+
+impl Image {
+    fn decode(png: &[u8]) -> Self {
+        let decoder = png::Decoder::new(png);
+        let mut reader = decoder.read_info().unwrap();
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).unwrap();
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        Self {
+            width: info.width,
+            height: info.height,
+            pixels: buffer[..info.buffer_size()]
+                .chunks(3)
+                .map(|pixel| (pixel[0], pixel[1], pixel[2]))
+                .collect(),
+        }
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> (u8, u8, u8) {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// Returns the pixels of the cell at `(row, column)` relative to the top left cell, as one
+    /// string per pixel row, with `#` for pixels of color `fg` and `.` for other pixels.
+    fn cell(&self, (row, column): (u32, u32), fg: (u8, u8, u8)) -> Vec<String> {
+        (0..PNG_CELL_HEIGHT)
+            .map(|dy| {
+                (0..PNG_CELL_WIDTH)
+                    .map(|dx| {
+                        let pixel =
+                            self.pixel(column * PNG_CELL_WIDTH + dx, row * PNG_CELL_HEIGHT + dy);
+                        if pixel == fg {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn from_extension() {
+    assert_eq!(FileFormat::from_extension(Some("png")), Ok(FileFormat::Png));
+    assert_eq!(FileFormat::from_extension(Some("PNG")), Ok(FileFormat::Png));
+}
+
+#[test]
+fn dimensions() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((-1, 2), 'a');
+    canvas.set_character((1, 4), 'b');
+    let image = Image::decode(
+        &canvas
+            .export_png(&color_theme(Color::Reset, Color::Reset))
+            .unwrap(),
+    );
+
+    assert_eq!(image.width, 3 * PNG_CELL_WIDTH);
+    assert_eq!(image.height, 3 * PNG_CELL_HEIGHT);
+}
+
+#[test]
+fn empty() {
+    assert!(Canvas::default()
+        .export_png(&color_theme(Color::Reset, Color::Reset))
+        .is_err());
+}
+
+#[test]
+fn glyph() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'I');
+    canvas.set_fg((0, 0), Color::Rgb(255, 255, 255));
+    let image = Image::decode(
+        &canvas
+            .export_png(&color_theme(Color::Reset, Color::Reset))
+            .unwrap(),
+    );
+
+    assert_eq!(
+        image.cell((0, 0), (255, 255, 255)),
+        [
+            "........", "........", ".#####..", "...#....", "...#....", "...#....", "...#....",
+            "...#....", "...#....", "...#....", ".#####..", "........", "........",
+        ]
+    );
+}
+
+#[test]
+fn box_drawing_connects() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), '│');
+    canvas.set_character((1, 0), '│');
+    let image = Image::decode(
+        &canvas
+            .export_png(&color_theme(Color::Reset, Color::Reset))
+            .unwrap(),
+    );
+    let fg = (229, 229, 229);
+
+    for y in 0..2 * PNG_CELL_HEIGHT {
+        assert_eq!(image.pixel(3, y), fg);
+    }
+}
+
+#[test]
+fn colors() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), '█');
+    canvas.set_fg((0, 0), Color::Red);
+    canvas.set_character((0, 1), ' ');
+    canvas.set_bg((0, 1), Color::Indexed(12));
+    canvas.set_character((0, 2), ' ');
+    let image = Image::decode(
+        &canvas
+            .export_png(&color_theme(Color::Reset, Color::Rgb(1, 2, 3)))
+            .unwrap(),
+    );
+
+    assert_eq!(image.pixel(0, 0), (200, 0, 0));
+    assert_eq!(image.pixel(PNG_CELL_WIDTH, 0), (0, 0, 255));
+    assert_eq!(image.pixel(2 * PNG_CELL_WIDTH, 0), (1, 2, 3));
+}
+
+#[test]
+fn reversed() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), ' ');
+    canvas.set_fg((0, 0), Color::Rgb(9, 9, 9));
+    canvas.add_modifier((0, 0), Modifier::REVERSED);
+    let image = Image::decode(
+        &canvas
+            .export_png(&color_theme(Color::Reset, Color::Reset))
+            .unwrap(),
+    );
+
+    assert_eq!(image.pixel(0, 0), (9, 9, 9));
+}
+
+#[test]
+fn missing_glyph() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), '🎨');
+    canvas.set_fg((0, 0), Color::Rgb(255, 255, 255));
+    let image = Image::decode(
+        &canvas
+            .export_png(&color_theme(Color::Reset, Color::Reset))
+            .unwrap(),
+    );
+
+    assert_eq!(
+        image.cell((0, 0), (255, 255, 255)),
+        [
+            "........", ".######.", ".#....#.", ".#....#.", ".#....#.", ".#....#.", ".#....#.",
+            ".#....#.", ".#....#.", ".#....#.", ".#....#.", ".######.", "........",
+        ]
+    );
+}
+
+#[test]
+fn underlined() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), ' ');
+    canvas.set_fg((0, 0), Color::Rgb(255, 255, 255));
+    canvas.add_modifier((0, 0), Modifier::UNDERLINED);
+    let image = Image::decode(
+        &canvas
+            .export_png(&color_theme(Color::Reset, Color::Reset))
+            .unwrap(),
+    );
+
+    assert_eq!(image.cell((0, 0), (255, 255, 255))[11], "########");
+}
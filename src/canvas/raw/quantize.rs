@@ -0,0 +1,244 @@
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+
+use derive_more::Display;
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::config::color_theme::{palette_index, xterm_rgb};
+use crate::Ground;
+
+use super::{Canvas, CanvasIndex};
+
+#[cfg(test)]
+mod test;
+
+/// The colors a terminal supports, to which the colors of the canvas are reduced when exporting.
+#[derive(Clone, Copy, Debug, Default, Display, PartialEq, Deserialize)]
+pub enum ColorDepth {
+    /// All colors, nothing is reduced.
+    #[default]
+    #[serde(rename = "truecolor")]
+    #[display("true color")]
+    TrueColor,
+    /// The 240 colors of the 256 color palette that don't depend on the terminal's color scheme.
+    #[serde(rename = "256")]
+    #[display("256 colors")]
+    Indexed256,
+    /// The 16 standard colors.
+    #[serde(rename = "16")]
+    #[display("16 colors")]
+    Indexed16,
+}
+
+impl FromStr for ColorDepth {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "truecolor" | "24bit" => Ok(Self::TrueColor),
+            "256" => Ok(Self::Indexed256),
+            "16" => Ok(Self::Indexed16),
+            _ => anyhow::bail!("Unknown color depth {value:?}, expected truecolor, 256 or 16"),
+        }
+    }
+}
+
+/// How to distribute the error of reducing colors across neighboring cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dithering {
+    /// Each cell gets the nearest color.
+    #[default]
+    None,
+    /// A 4x4 Bayer matrix pattern.
+    Ordered,
+    /// Floyd-Steinberg error diffusion.
+    ErrorDiffusion,
+}
+
+impl FromStr for Dithering {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "none" => Ok(Self::None),
+            "ordered" => Ok(Self::Ordered),
+            "error_diffusion" | "diffusion" => Ok(Self::ErrorDiffusion),
+            _ => anyhow::bail!(
+                "Unknown dithering {value:?}, expected none, ordered or error_diffusion"
+            ),
+        }
+    }
+}
+
+const BAYER_MATRIX: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+type Rgb = [f32; 3];
+
+/// Converts an sRGB color to the perceptual OKLab color space, see
+/// <https://bottosson.github.io/posts/oklab/>.
+fn oklab([r, g, b]: Rgb) -> [f32; 3] {
+    let linear = |value: f32| {
+        let value = value / 255.0;
+        if value <= 0.04045 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let (r, g, b) = (linear(r), linear(g), linear(b));
+    let l = (0.41222146 * r + 0.53633255 * g + 0.051445995 * b).cbrt();
+    let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+    let s = (0.08830246 * r + 0.28171885 * g + 0.6299787 * b).cbrt();
+    [
+        0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+        1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+        0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+    ]
+}
+
+fn rgb((r, g, b): (u8, u8, u8)) -> Rgb {
+    [r as f32, g as f32, b as f32]
+}
+
+struct PaletteColor {
+    color: Color,
+    rgb: Rgb,
+    oklab: [f32; 3],
+}
+
+impl ColorDepth {
+    fn palette(self) -> Vec<PaletteColor> {
+        let indices = match self {
+            Self::TrueColor => return Vec::new(),
+            Self::Indexed256 => 16..=255,
+            Self::Indexed16 => 0..=15,
+        };
+        indices
+            .map(|index| {
+                let color = match self {
+                    // The standard colors are used by name, as terminals with only 16 colors
+                    // may not support indexed colors
+                    Self::Indexed16 => STANDARD_COLORS[index as usize],
+                    _ => Color::Indexed(index),
+                };
+                let rgb = rgb(xterm_rgb(index));
+                PaletteColor {
+                    color,
+                    rgb,
+                    oklab: oklab(rgb),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the RGB value of `color` if it has to be reduced to fit this color depth.
+    fn rgb_to_reduce(self, color: Color) -> Option<Rgb> {
+        match (self, color, palette_index(color)) {
+            (Self::TrueColor, ..) => None,
+            (_, Color::Rgb(r, g, b), _) => Some(rgb((r, g, b))),
+            (Self::Indexed16, Color::Indexed(16..=255), Some(index)) => Some(rgb(xterm_rgb(index))),
+            _ => None,
+        }
+    }
+
+    /// Maximum offset added to colors by ordered dithering, about the distance between
+    /// neighboring colors of the palette.
+    fn ordered_dithering_spread(self) -> f32 {
+        match self {
+            Self::TrueColor => 0.0,
+            Self::Indexed256 => 40.0,
+            Self::Indexed16 => 128.0,
+        }
+    }
+}
+
+const STANDARD_COLORS: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+fn nearest(palette: &[PaletteColor], target: Rgb) -> &PaletteColor {
+    let target = oklab(target.map(|value| value.clamp(0.0, 255.0)));
+    let distance = |color: &PaletteColor| -> f32 {
+        (0..3).map(|i| (color.oklab[i] - target[i]).powi(2)).sum()
+    };
+    palette
+        .iter()
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+        .unwrap()
+}
+
+impl Canvas {
+    /// Reduces the colors of the canvas to `depth`, using `dithering` to distribute the error
+    /// across neighboring cells. Colors are compared in the OKLab color space, against the xterm
+    /// default palette.
+    ///
+    /// Returns the number of cells that changed.
+    pub fn quantize(&mut self, depth: ColorDepth, dithering: Dithering) -> usize {
+        let palette = depth.palette();
+        let mut changed = BTreeSet::new();
+        for ground in [Ground::Foreground, Ground::Background] {
+            let mut errors: HashMap<CanvasIndex, Rgb> = HashMap::new();
+            // Cells are iterated row by row, as error diffusion requires
+            for (&(row, column), cell) in self.cells.iter_mut() {
+                let color = match ground {
+                    Ground::Foreground => &mut cell.fg,
+                    Ground::Background => &mut cell.bg,
+                };
+                let Some(mut target) = depth.rgb_to_reduce(*color) else {
+                    continue;
+                };
+                match dithering {
+                    Dithering::None => (),
+                    Dithering::Ordered => {
+                        let threshold =
+                            BAYER_MATRIX[row.rem_euclid(4) as usize][column.rem_euclid(4) as usize];
+                        let offset = ((threshold as f32 + 0.5) / 16.0 - 0.5)
+                            * depth.ordered_dithering_spread();
+                        target = target.map(|value| value + offset);
+                    }
+                    Dithering::ErrorDiffusion => {
+                        if let Some(error) = errors.remove(&(row, column)) {
+                            target = [0, 1, 2].map(|i| target[i] + error[i]);
+                        }
+                    }
+                }
+                let quantized = nearest(&palette, target);
+                if dithering == Dithering::ErrorDiffusion {
+                    let error = [0, 1, 2].map(|i| target[i].clamp(0.0, 255.0) - quantized.rgb[i]);
+                    for (offset, weight) in
+                        [((0, 1), 7.0), ((1, -1), 3.0), ((1, 0), 5.0), ((1, 1), 1.0)]
+                    {
+                        let neighbor = errors
+                            .entry((row + offset.0, column + offset.1))
+                            .or_default();
+                        for i in 0..3 {
+                            neighbor[i] += error[i] * weight / 16.0;
+                        }
+                    }
+                }
+                if quantized.color != *color {
+                    *color = quantized.color;
+                    changed.insert((row, column));
+                }
+            }
+        }
+        changed.len()
+    }
+}
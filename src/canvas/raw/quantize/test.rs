@@ -0,0 +1,108 @@
+use ratatui::style::Color;
+
+use crate::canvas::raw::quantize::{ColorDepth, Dithering};
+use crate::canvas::raw::Canvas;
+
+#[test]
+fn parse() {
+    assert_eq!("256".parse::<ColorDepth>().unwrap(), ColorDepth::Indexed256);
+    assert_eq!("16".parse::<ColorDepth>().unwrap(), ColorDepth::Indexed16);
+    assert_eq!(
+        "24bit".parse::<ColorDepth>().unwrap(),
+        ColorDepth::TrueColor
+    );
+    assert!("8".parse::<ColorDepth>().is_err());
+    assert_eq!("ordered".parse::<Dithering>().unwrap(), Dithering::Ordered);
+    assert!("random".parse::<Dithering>().is_err());
+}
+
+#[test]
+fn true_color() {
+    let mut canvas = Canvas::default();
+    canvas.set_fg((0, 0), Color::Rgb(12, 34, 56));
+    let changed = canvas.quantize(ColorDepth::TrueColor, Dithering::ErrorDiffusion);
+
+    assert_eq!(changed, 0);
+    assert_eq!(canvas.fg((0, 0)), Color::Rgb(12, 34, 56));
+}
+
+#[test]
+fn indexed_256() {
+    let mut canvas = Canvas::default();
+    canvas.set_fg((0, 0), Color::Rgb(255, 0, 0));
+    canvas.set_bg((0, 0), Color::Rgb(0, 0, 1));
+    canvas.set_fg((0, 1), Color::Rgb(130, 130, 130));
+    canvas.set_fg((1, 0), Color::Red);
+    canvas.set_fg((1, 1), Color::Indexed(100));
+    let changed = canvas.quantize(ColorDepth::Indexed256, Dithering::None);
+
+    assert_eq!(changed, 2);
+    assert_eq!(canvas.fg((0, 0)), Color::Indexed(196));
+    assert_eq!(canvas.bg((0, 0)), Color::Indexed(16));
+    assert_eq!(canvas.fg((0, 1)), Color::Indexed(244));
+    // Colors of the palette are kept
+    assert_eq!(canvas.fg((1, 0)), Color::Red);
+    assert_eq!(canvas.fg((1, 1)), Color::Indexed(100));
+}
+
+#[test]
+fn indexed_16() {
+    let mut canvas = Canvas::default();
+    canvas.set_fg((0, 0), Color::Rgb(255, 0, 0));
+    canvas.set_fg((0, 1), Color::Indexed(21));
+    canvas.set_fg((0, 2), Color::Indexed(4));
+    let changed = canvas.quantize(ColorDepth::Indexed16, Dithering::None);
+
+    assert_eq!(changed, 2);
+    assert_eq!(canvas.fg((0, 0)), Color::LightRed);
+    assert_eq!(canvas.fg((0, 1)), Color::Blue);
+    assert_eq!(canvas.fg((0, 2)), Color::Indexed(4));
+}
+
+fn gray_canvas() -> Canvas {
+    let mut canvas = Canvas::default();
+    for row in 0..8 {
+        for column in 0..8 {
+            canvas.set_bg((row, column), Color::Rgb(100, 100, 100));
+        }
+    }
+    canvas
+}
+
+fn distinct_backgrounds(canvas: &Canvas) -> Vec<Color> {
+    let mut colors: Vec<Color> = Vec::new();
+    for row in 0..8 {
+        for column in 0..8 {
+            let color = canvas.bg((row, column));
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+    }
+    colors
+}
+
+#[test]
+fn no_dithering() {
+    let mut canvas = gray_canvas();
+    canvas.quantize(ColorDepth::Indexed16, Dithering::None);
+
+    assert_eq!(distinct_backgrounds(&canvas).len(), 1);
+}
+
+#[test]
+fn ordered_dithering() {
+    let mut canvas = gray_canvas();
+    canvas.quantize(ColorDepth::Indexed16, Dithering::Ordered);
+
+    assert!(distinct_backgrounds(&canvas).len() > 1);
+}
+
+#[test]
+fn error_diffusion() {
+    let mut canvas = gray_canvas();
+    let changed = canvas.quantize(ColorDepth::Indexed16, Dithering::ErrorDiffusion);
+
+    assert_eq!(changed, 64);
+    assert!(distinct_backgrounds(&canvas).len() > 1);
+}
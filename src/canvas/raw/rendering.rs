@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use ratatui::{
     layout::Layout,
     prelude::{Buffer, Constraint, Line, Rect},
@@ -11,14 +13,20 @@ use crate::{
     selections::Selection,
 };
 
-use super::Canvas;
+use super::{is_wide, Canvas, DEFAULT_CELL, WIDE_CHARACTER_CONTINUATION};
 
 pub struct CanvasWidget<'a> {
-    pub canvas: &'a Canvas,
+    pub canvas: Cow<'a, Canvas>,
     pub focus: CanvasIndex,
     pub cursor: Option<CanvasIndex>,
     pub visual_rect: Option<(CanvasIndex, CanvasIndex)>,
     pub selection: Option<Selection>,
+    /// Cells marked as changed, like since the last save.
+    pub changed: Option<Selection>,
+    /// Another frame shown dimmed where the canvas has no cells, to trace animations.
+    pub onion_skin: Option<Cow<'a, Canvas>>,
+    /// Fixed area of the artwork, which is surrounded by a border.
+    pub page: Option<CanvasRect>,
     pub config: &'a Config,
 }
 
@@ -60,13 +68,16 @@ pub fn canvas_layout_chunks(area: Rect) -> (Rect, Rect, Rect) {
 }
 
 impl<'a> CanvasWidget<'a> {
-    pub fn from_canvas(canvas: &'a Canvas, config: &'a Config) -> Self {
+    pub fn from_canvas(canvas: Cow<'a, Canvas>, config: &'a Config) -> Self {
         CanvasWidget {
-            canvas,
             focus: canvas.area().center(),
+            canvas,
             cursor: None,
             visual_rect: None,
             selection: None,
+            changed: None,
+            onion_skin: None,
+            page: None,
             config,
         }
     }
@@ -234,6 +245,26 @@ impl Widget for ColumnNumbersWidget<'_> {
     }
 }
 
+/// Box drawing character of the border around `page` at `index`, if `index` is on the border.
+fn page_border_symbol(page: CanvasRect, index: CanvasIndex) -> Option<&'static str> {
+    let (row, column) = index;
+    let top = row == page.first_row() - 1;
+    let bottom = row == page.last_row() + 1;
+    let left = column == page.first_column() - 1;
+    let right = column == page.last_column() + 1;
+    let inside_rows = page.includes_row(row);
+    let inside_columns = page.includes_column(column);
+    match (top, bottom, left, right) {
+        (true, _, true, _) => Some("┌"),
+        (true, _, _, true) => Some("┐"),
+        (_, true, true, _) => Some("└"),
+        (_, true, _, true) => Some("┘"),
+        (true, _, _, _) | (_, true, _, _) if inside_columns => Some("─"),
+        (_, _, true, _) | (_, _, _, true) if inside_rows => Some("│"),
+        _ => None,
+    }
+}
+
 impl Widget for CanvasWidget<'_> {
     fn render(self, area: Rect, buffer: &mut Buffer) {
         let (canvas_chunk, row_number_chunk, column_number_chunk) = self.layout_chunks(area);
@@ -274,21 +305,57 @@ impl Widget for CanvasWidget<'_> {
                 let target = buffer.cell_mut((x, y)).unwrap();
                 let color_theme = &self.config.color_theme().canvas;
 
-                if let Some(cell) = self.canvas.cells.get(&(row, column)) {
-                    target.set_symbol(String::from(cell.character).as_str());
-                    target.set_style(
-                        color_theme.apply_to_style(
-                            Style::default()
-                                .fg(cell.fg)
-                                .bg(cell.bg)
-                                .add_modifier(cell.modifiers),
-                        ),
-                    );
+                let cell = self.canvas.cells.get(&(row, column));
+                let onion_skin_cell = self
+                    .onion_skin
+                    .as_ref()
+                    .filter(|_onion_skin| cell.is_none_or(|cell| *cell == DEFAULT_CELL))
+                    .and_then(|onion_skin| onion_skin.cells.get(&(row, column)));
+                if let Some((cell, dim)) = onion_skin_cell
+                    .map(|cell| (cell, true))
+                    .or(cell.map(|cell| (cell, false)))
+                {
+                    let symbol = match cell.character {
+                        // Covered by the wide character to the left, unless it's outside the
+                        // chunk
+                        WIDE_CHARACTER_CONTINUATION => " ".to_string(),
+                        // Wide characters are cut off at the right edge of the chunk
+                        character if is_wide(character) && x + 1 == x_right => " ".to_string(),
+                        _ => cell.grapheme(),
+                    };
+                    target.set_symbol(symbol.as_str());
+                    let mut style = Style::default()
+                        .fg(cell.fg)
+                        .bg(cell.bg)
+                        .add_modifier(cell.modifiers);
+                    if dim {
+                        style = style.add_modifier(Modifier::DIM);
+                    }
+                    target.set_style(color_theme.apply_to_style(style));
                 } else {
                     target.set_style(
                         color_theme.apply_to_style(color_theme.default_style.clone().into()),
                     );
                 }
+                if let Some(symbol) = self
+                    .page
+                    .and_then(|page| page_border_symbol(page, (row, column)))
+                {
+                    target.set_symbol(symbol);
+                    target.set_style(self.config.color_theme().row_numbers);
+                }
+                if let Some(ref changed) = self.changed {
+                    if changed.contains(&(row, column)) {
+                        target.set_style(
+                            Style::default().bg(self
+                                .config
+                                .color_theme()
+                                .canvas
+                                .changed_highlight_bg
+                                .into()),
+                        );
+                    }
+                }
                 if let Some(ref selection) = self.selection {
                     if selection.contains(&(row, column)) {
                         target.set_style(
@@ -0,0 +1,211 @@
+use ratatui::style::{Color, Modifier};
+
+use crate::config::color_theme::ColorThemeCanvas;
+use crate::Ground;
+
+use super::html_export::{css_color, push_escaped};
+use super::{Canvas, CanvasCell, CanvasIndex, WIDE_CHARACTER_CONTINUATION};
+
+#[cfg(test)]
+mod test;
+
+pub const SVG_CELL_WIDTH: u32 = 10;
+pub const SVG_CELL_HEIGHT: u32 = 20;
+const SVG_FONT_SIZE: u32 = 16;
+/// Distance from the top of a cell to the text baseline.
+const SVG_BASELINE: u32 = 15;
+
+/// The SVG attributes needed to draw the text of a cell.
+#[derive(Clone, Debug, PartialEq)]
+struct TextStyle {
+    fill: Option<(u8, u8, u8)>,
+    modifiers: Modifier,
+}
+
+impl TextStyle {
+    const SUPPORTED_MODIFIERS: Modifier = Modifier::BOLD
+        .union(Modifier::DIM)
+        .union(Modifier::ITALIC)
+        .union(Modifier::UNDERLINED)
+        .union(Modifier::CROSSED_OUT);
+
+    fn push_attributes(&self, result: &mut String) {
+        if let Some(fill) = self.fill {
+            result.push_str(format!(" fill=\"{}\"", css_color(fill)).as_str());
+        }
+        if self.modifiers.contains(Modifier::BOLD) {
+            result.push_str(" font-weight=\"bold\"");
+        }
+        if self.modifiers.contains(Modifier::DIM) {
+            result.push_str(" opacity=\"0.5\"");
+        }
+        if self.modifiers.contains(Modifier::ITALIC) {
+            result.push_str(" font-style=\"italic\"");
+        }
+        let mut text_decorations = Vec::new();
+        if self.modifiers.contains(Modifier::UNDERLINED) {
+            text_decorations.push("underline");
+        }
+        if self.modifiers.contains(Modifier::CROSSED_OUT) {
+            text_decorations.push("line-through");
+        }
+        if !text_decorations.is_empty() {
+            result
+                .push_str(format!(" text-decoration=\"{}\"", text_decorations.join(" ")).as_str());
+        }
+    }
+}
+
+/// A horizontal run of adjacent cells sharing the same `value`.
+struct Run<T> {
+    start: CanvasIndex,
+    length: u32,
+    value: T,
+    text: String,
+}
+
+impl<T: PartialEq> Run<T> {
+    fn continues_with(&self, index: CanvasIndex, value: &T) -> bool {
+        let (row, column) = index;
+        row == self.start.0 && column == self.start.1 + self.length as i16 && *value == self.value
+    }
+}
+
+/// Splits `cells` into runs, where `value` gives the value of each cell a run must share. Cells
+/// with a value of `None` are not part of any run.
+fn runs<'a, T: PartialEq>(
+    cells: impl Iterator<Item = (&'a CanvasIndex, &'a CanvasCell)>,
+    value: impl Fn(&CanvasCell) -> Option<T>,
+) -> Vec<Run<T>> {
+    let mut runs: Vec<Run<T>> = Vec::new();
+    for (index, cell) in cells {
+        let Some(value) = value(cell) else {
+            continue;
+        };
+        match runs.last_mut() {
+            Some(run) if run.continues_with(*index, &value) => {
+                run.length += 1;
+                if cell.character != WIDE_CHARACTER_CONTINUATION {
+                    run.text.push(cell.character);
+                    run.text.push_str(&cell.combining);
+                }
+            }
+            _ => runs.push(Run {
+                start: *index,
+                length: 1,
+                value,
+                text: cell.grapheme().replace(WIDE_CHARACTER_CONTINUATION, ""),
+            }),
+        }
+    }
+    runs
+}
+
+impl Canvas {
+    /// Exports the canvas as an SVG image, with cells laid out on a grid of
+    /// `SVG_CELL_WIDTH` x `SVG_CELL_HEIGHT`.
+    ///
+    /// Each run of identical background colors is drawn as one `<rect>`, and each run of
+    /// identically styled text as one `<tspan>`, inside one `<text>` per row. Colors are resolved
+    /// through `color_theme`, like for `export_html()`.
+    pub fn export_svg(&self, color_theme: &ColorThemeCanvas) -> String {
+        let area = self.area();
+        let x = |column: i16| (column - area.first_column()) as u32 * SVG_CELL_WIDTH;
+        let y = |row: i16| (row - area.first_row()) as u32 * SVG_CELL_HEIGHT;
+        // Reset colors are left out, so that they fall back to the default colors drawn for the
+        // whole image, except when the colors are reversed.
+        let colors = |cell: &CanvasCell| {
+            let reversed = cell.modifiers.contains(Modifier::REVERSED);
+            let resolve = |color, ground| match color {
+                Color::Reset if !reversed => None,
+                color => color_theme.resolve_rgb(color, ground),
+            };
+            let fg = resolve(cell.fg, Ground::Foreground);
+            let bg = resolve(cell.bg, Ground::Background);
+            if reversed {
+                (bg, fg)
+            } else {
+                (fg, bg)
+            }
+        };
+
+        let width = area.columns as u32 * SVG_CELL_WIDTH;
+        let height = area.rows as u32 * SVG_CELL_HEIGHT;
+        let mut result = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\" font-family=\"monospace\" font-size=\"{SVG_FONT_SIZE}\" xml:space=\"preserve\""
+        );
+        if let Some(fg) = color_theme.resolve_rgb(Color::Reset, Ground::Foreground) {
+            result.push_str(format!(" fill=\"{}\"", css_color(fg)).as_str());
+        }
+        result.push_str(">\n");
+
+        if let Some(bg) = color_theme.resolve_rgb(Color::Reset, Ground::Background) {
+            result.push_str(
+                format!(
+                    "<rect width=\"{width}\" height=\"{height}\" fill=\"{}\"/>\n",
+                    css_color(bg)
+                )
+                .as_str(),
+            );
+        }
+
+        let background_runs = runs(self.cells.iter(), |cell| colors(cell).1);
+        for run in background_runs {
+            let (row, column) = run.start;
+            result.push_str(
+                format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{SVG_CELL_HEIGHT}\" fill=\"{}\"/>\n",
+                    x(column),
+                    y(row),
+                    run.length * SVG_CELL_WIDTH,
+                    css_color(run.value),
+                )
+                .as_str(),
+            );
+        }
+
+        let text_runs = runs(self.cells.iter(), |cell| {
+            let style = TextStyle {
+                fill: colors(cell).0,
+                modifiers: cell.modifiers.intersection(TextStyle::SUPPORTED_MODIFIERS),
+            };
+            let decorated = style
+                .modifiers
+                .intersects(Modifier::UNDERLINED | Modifier::CROSSED_OUT);
+            let invisible =
+                cell.modifiers.contains(Modifier::HIDDEN) || (cell.character == ' ' && !decorated);
+            (!invisible).then_some(style)
+        });
+        let mut text_row = None;
+        for run in text_runs {
+            let (row, column) = run.start;
+            if text_row != Some(row) {
+                if text_row.is_some() {
+                    result.push_str("</text>\n");
+                }
+                result.push_str(format!("<text y=\"{}\">", y(row) + SVG_BASELINE).as_str());
+                text_row = Some(row);
+            }
+            result.push_str(
+                format!(
+                    "<tspan x=\"{}\" textLength=\"{}\" lengthAdjust=\"spacingAndGlyphs\"",
+                    x(column),
+                    run.length * SVG_CELL_WIDTH,
+                )
+                .as_str(),
+            );
+            run.value.push_attributes(&mut result);
+            result.push('>');
+            for character in run.text.chars() {
+                push_escaped(character, &mut result);
+            }
+            result.push_str("</tspan>");
+        }
+        if text_row.is_some() {
+            result.push_str("</text>\n");
+        }
+
+        result.push_str("</svg>\n");
+        result
+    }
+}
@@ -0,0 +1,152 @@
+use ratatui::style::{Color, Modifier};
+
+use crate::canvas::raw::html_export::test::color_theme;
+use crate::canvas::raw::Canvas;
+
+fn svg(width: u32, height: u32, contents: &str) -> String {
+    svg_with_attributes(width, height, "", contents)
+}
+
+fn svg_with_attributes(width: u32, height: u32, attributes: &str, contents: &str) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\" font-family=\"monospace\" font-size=\"16\" xml:space=\"preserve\"{attributes}>\n{contents}</svg>\n"
+    )
+}
+
+#[test]
+fn basic() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_character((0, 1), 'b');
+    let result = canvas.export_svg(&color_theme(Color::Reset, Color::Reset));
+
+    assert_eq!(
+        result,
+        svg(
+            20,
+            20,
+            "<text y=\"15\"><tspan x=\"0\" textLength=\"20\" lengthAdjust=\"spacingAndGlyphs\">ab</tspan></text>\n"
+        )
+    );
+}
+
+#[test]
+fn geometry() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((-1, 3), 'a');
+    canvas.set_character((1, 5), 'b');
+    let result = canvas.export_svg(&color_theme(Color::Reset, Color::Reset));
+
+    assert_eq!(
+        result,
+        svg(
+            30,
+            60,
+            concat!(
+                "<text y=\"15\"><tspan x=\"0\" textLength=\"10\" lengthAdjust=\"spacingAndGlyphs\">a</tspan></text>\n",
+                "<text y=\"55\"><tspan x=\"20\" textLength=\"10\" lengthAdjust=\"spacingAndGlyphs\">b</tspan></text>\n",
+            )
+        )
+    );
+}
+
+#[test]
+fn runs() {
+    let mut canvas = Canvas::default();
+    for (column, character) in "abcd".chars().enumerate() {
+        canvas.set_character((0, column as i16), character);
+    }
+    canvas.set_fg((0, 0), Color::Rgb(255, 0, 0));
+    canvas.set_fg((0, 1), Color::Rgb(255, 0, 0));
+    canvas.set_bg((0, 1), Color::Blue);
+    canvas.set_bg((0, 2), Color::Blue);
+    canvas.set_bg((0, 3), Color::Blue);
+    let result = canvas.export_svg(&color_theme(Color::Reset, Color::Reset));
+
+    assert_eq!(
+        result,
+        svg(
+            40,
+            20,
+            concat!(
+                "<rect x=\"10\" y=\"0\" width=\"30\" height=\"20\" fill=\"#0000c8\"/>\n",
+                "<text y=\"15\">",
+                "<tspan x=\"0\" textLength=\"20\" lengthAdjust=\"spacingAndGlyphs\" fill=\"#ff0000\">ab</tspan>",
+                "<tspan x=\"20\" textLength=\"20\" lengthAdjust=\"spacingAndGlyphs\">cd</tspan>",
+                "</text>\n",
+            )
+        )
+    );
+}
+
+#[test]
+fn blank_cells() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_character((0, 1), ' ');
+    canvas.set_bg((0, 1), Color::Rgb(1, 2, 3));
+    canvas.set_character((0, 2), 'b');
+    let result = canvas.export_svg(&color_theme(Color::Reset, Color::Reset));
+
+    assert_eq!(
+        result,
+        svg(
+            30,
+            20,
+            concat!(
+                "<rect x=\"10\" y=\"0\" width=\"10\" height=\"20\" fill=\"#010203\"/>\n",
+                "<text y=\"15\">",
+                "<tspan x=\"0\" textLength=\"10\" lengthAdjust=\"spacingAndGlyphs\">a</tspan>",
+                "<tspan x=\"20\" textLength=\"10\" lengthAdjust=\"spacingAndGlyphs\">b</tspan>",
+                "</text>\n",
+            )
+        )
+    );
+}
+
+#[test]
+fn modifiers() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_character((0, 1), ' ');
+    canvas.set_character((0, 2), 'b');
+    canvas.add_modifier((0, 0), Modifier::BOLD | Modifier::ITALIC);
+    canvas.add_modifier((0, 1), Modifier::UNDERLINED | Modifier::CROSSED_OUT);
+    canvas.add_modifier((0, 2), Modifier::SLOW_BLINK);
+    let result = canvas.export_svg(&color_theme(Color::Reset, Color::Reset));
+
+    assert_eq!(
+        result,
+        svg(
+            30,
+            20,
+            concat!(
+                "<text y=\"15\">",
+                "<tspan x=\"0\" textLength=\"10\" lengthAdjust=\"spacingAndGlyphs\" font-weight=\"bold\" font-style=\"italic\">a</tspan>",
+                "<tspan x=\"10\" textLength=\"10\" lengthAdjust=\"spacingAndGlyphs\" text-decoration=\"underline line-through\"> </tspan>",
+                "<tspan x=\"20\" textLength=\"10\" lengthAdjust=\"spacingAndGlyphs\">b</tspan>",
+                "</text>\n",
+            )
+        )
+    );
+}
+
+#[test]
+fn default_style() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), '<');
+    let result = canvas.export_svg(&color_theme(Color::Rgb(9, 9, 9), Color::Black));
+
+    assert_eq!(
+        result,
+        svg_with_attributes(
+            10,
+            20,
+            " fill=\"#090909\"",
+            concat!(
+                "<rect width=\"10\" height=\"20\" fill=\"#010101\"/>\n",
+                "<text y=\"15\"><tspan x=\"0\" textLength=\"10\" lengthAdjust=\"spacingAndGlyphs\">&lt;</tspan></text>\n",
+            )
+        )
+    );
+}
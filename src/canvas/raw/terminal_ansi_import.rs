@@ -0,0 +1,312 @@
+use ratatui::style::{Color, Modifier};
+
+use super::ansi_import::{apply_sgr, AnsiImportError, CsiSequence};
+use super::legacy_ansi_import::{bold_as_bright, LEGACY_ANSI_COLUMNS};
+use super::{is_wide, Canvas, CanvasCell, CanvasIndex};
+
+#[cfg(test)]
+pub(crate) mod test;
+
+const TAB_WIDTH: i16 = 8;
+
+/// An escape sequence, as far as the virtual terminal is concerned.
+enum TerminalSequence {
+    Csi(CsiSequence),
+    SaveCursor,
+    RestoreCursor,
+    /// A sequence the virtual terminal doesn't support, which is ignored.
+    Unsupported,
+}
+
+impl TerminalSequence {
+    /// Reads the rest of an escape sequence after `ESC`.
+    fn read(
+        index: CanvasIndex,
+        characters: &mut impl Iterator<Item = char>,
+    ) -> Result<Self, AnsiImportError> {
+        let Some(character) = characters.next() else {
+            return Err(AnsiImportError::UnfinishedEscapeSequence(index));
+        };
+        match character {
+            '[' => {
+                let mut parameters = String::new();
+                let mut supported = true;
+                loop {
+                    let Some(character) = characters.next() else {
+                        return Err(AnsiImportError::UnfinishedEscapeSequence(index));
+                    };
+                    match character {
+                        '0'..='9' | ';' => parameters.push(character),
+                        '@'..='~' if supported => {
+                            return Ok(Self::Csi(CsiSequence {
+                                parameters,
+                                final_character: character,
+                            }))
+                        }
+                        '@'..='~' => return Ok(Self::Unsupported),
+                        // Private parameters like in `ESC[?25l`, and intermediate bytes
+                        _ => supported = false,
+                    }
+                }
+            }
+            // Operating system commands and other strings, like the window title, end with BEL
+            // or `ESC \`
+            ']' | 'P' | 'X' | '^' | '_' => {
+                let mut previous = character;
+                for character in characters.by_ref() {
+                    if character == '\u{07}' || (previous == '\u{1b}' && character == '\\') {
+                        return Ok(Self::Unsupported);
+                    }
+                    previous = character;
+                }
+                Err(AnsiImportError::UnfinishedEscapeSequence(index))
+            }
+            // Character set designations, followed by the character set
+            '(' | ')' | '*' | '+' => match characters.next() {
+                Some(_) => Ok(Self::Unsupported),
+                None => Err(AnsiImportError::UnfinishedEscapeSequence(index)),
+            },
+            '7' => Ok(Self::SaveCursor),
+            '8' => Ok(Self::RestoreCursor),
+            _ => Ok(Self::Unsupported),
+        }
+    }
+}
+
+/// A minimal terminal emulator drawing onto a canvas, with an unlimited number of rows and no
+/// scrolling.
+pub(super) struct VirtualTerminal {
+    canvas: Canvas,
+    cursor: CanvasIndex,
+    saved_cursor: CanvasIndex,
+    /// Index of the character printed last, if the cursor hasn't moved since, to which
+    /// combining characters are added.
+    previous: Option<CanvasIndex>,
+    columns: Option<u16>,
+    /// Whether the terminal behaves like DOS `ANSI.SYS`, where lines wrap as soon as their last
+    /// column is printed and bold is shown as bright foreground colors.
+    dos: bool,
+    fg: Color,
+    bg: Color,
+    modifiers: Modifier,
+}
+
+impl VirtualTerminal {
+    fn new(columns: Option<u16>) -> Self {
+        Self {
+            canvas: Canvas::default(),
+            cursor: (0, 0),
+            saved_cursor: (0, 0),
+            previous: None,
+            columns,
+            dos: false,
+            fg: Color::Reset,
+            bg: Color::Reset,
+            modifiers: Modifier::default(),
+        }
+    }
+
+    /// A terminal like the DOS ones legacy ANSI art was made for.
+    pub(super) fn dos() -> Self {
+        Self {
+            dos: true,
+            ..Self::new(Some(LEGACY_ANSI_COLUMNS as u16))
+        }
+    }
+
+    /// The rightmost column of the screen, which is the rightmost column drawn to so far if the
+    /// width of the terminal is unknown.
+    fn last_column(&self) -> i16 {
+        match self.columns {
+            Some(columns) => columns as i16 - 1,
+            None => self.canvas.area().last_column(),
+        }
+    }
+
+    fn set_row(&mut self, row: i16) {
+        self.cursor.0 = row.max(0);
+    }
+
+    fn set_column(&mut self, column: i16) {
+        self.cursor.1 = column.max(0);
+        if let Some(columns) = self.columns {
+            self.cursor.1 = self.cursor.1.min(columns as i16 - 1);
+        }
+    }
+
+    fn print(&mut self, character: char) {
+        if let Some(previous) = self.previous {
+            if self.canvas.continues_grapheme(previous, character) {
+                self.canvas.add_combining_character(previous, character);
+                return;
+            }
+        }
+        let width = if is_wide(character) { 2 } else { 1 };
+        if let Some(columns) = self.columns {
+            // Wide characters wrap as a whole
+            if self.cursor.1 + width > columns as i16 {
+                self.cursor = (self.cursor.0 + 1, 0);
+            }
+        }
+        let (fg, modifiers) = if self.dos {
+            bold_as_bright(self.fg, self.modifiers)
+        } else {
+            (self.fg, self.modifiers)
+        };
+        let cell = CanvasCell {
+            character,
+            combining: String::new(),
+            fg,
+            bg: self.bg,
+            modifiers,
+        };
+        self.canvas.set_cell(self.cursor, cell);
+        self.previous = Some(self.cursor);
+        self.cursor.1 += self.canvas.pair_wide_character(self.cursor);
+        if let (true, Some(columns)) = (self.dos, self.columns) {
+            if self.cursor.1 >= columns as i16 {
+                self.cursor = (self.cursor.0 + 1, 0);
+            }
+        }
+    }
+
+    /// Erases the cells of `row` from `first_column` to `last_column`, filling them with the
+    /// current background color like terminals do. Only cells inside the area drawn to so far
+    /// are erased, so that erasing far away from it doesn't fill a huge area.
+    fn erase(&mut self, row: i16, first_column: i16, last_column: i16) {
+        let area = self.canvas.area();
+        if !area.includes_row(row) {
+            return;
+        }
+        let cell = CanvasCell {
+            bg: self.bg,
+            ..Default::default()
+        };
+        for column in first_column.max(area.first_column())..=last_column.min(area.last_column()) {
+            self.canvas.set_cell((row, column), cell.clone());
+        }
+    }
+
+    /// Erases all rows from `first_row` to `last_row`, inside the area drawn to so far.
+    fn erase_rows(&mut self, first_row: i16, last_row: i16) {
+        let area = self.canvas.area();
+        let last_column = self.last_column();
+        for row in first_row.max(area.first_row())..=last_row.min(area.last_row()) {
+            self.erase(row, 0, last_column);
+        }
+    }
+
+    /// Prints `characters`, interpreting control characters and escape sequences.
+    pub(super) fn run(
+        &mut self,
+        mut characters: impl Iterator<Item = char>,
+    ) -> Result<(), AnsiImportError> {
+        while let Some(character) = characters.next() {
+            if character.is_control() {
+                self.previous = None;
+            }
+            match character {
+                '\u{1b}' => match TerminalSequence::read(self.cursor, &mut characters)? {
+                    TerminalSequence::Csi(sequence) => self.apply_csi(&sequence)?,
+                    TerminalSequence::SaveCursor => self.saved_cursor = self.cursor,
+                    TerminalSequence::RestoreCursor => self.cursor = self.saved_cursor,
+                    TerminalSequence::Unsupported => (),
+                },
+                '\u{0d}' => self.cursor.1 = 0,
+                // Line feed, like files printed through a terminal, also returns the carriage
+                '\u{0a}' => self.cursor = (self.cursor.0 + 1, 0),
+                '\u{08}' => self.set_column(self.cursor.1 - 1),
+                '\t' => {
+                    let tab_stop = (self.cursor.1 / TAB_WIDTH + 1) * TAB_WIDTH;
+                    self.set_column(tab_stop);
+                }
+                // Other control characters, like the bell, don't change the screen
+                character if character.is_control() => (),
+                character => self.print(character),
+            }
+        }
+        Ok(())
+    }
+
+    /// The drawn canvas, without the cells left empty.
+    pub(super) fn into_canvas(self) -> Canvas {
+        let mut canvas = self.canvas;
+        canvas.clean();
+        canvas
+    }
+
+    fn apply_csi(&mut self, sequence: &CsiSequence) -> Result<(), AnsiImportError> {
+        let mut values = sequence.values();
+        let first = values.next().unwrap_or(0);
+        let second = values.next().unwrap_or(0);
+        // Movement amounts and positions of 0 are treated as 1
+        let amount = first.max(1).min(i16::MAX as u64) as i16;
+        let (row, column) = self.cursor;
+        match sequence.final_character {
+            'm' => apply_sgr(
+                self.cursor,
+                sequence,
+                &mut self.fg,
+                &mut self.bg,
+                &mut self.modifiers,
+            )?,
+            'A' => self.set_row(row - amount),
+            'B' => self.set_row(row.saturating_add(amount)),
+            'C' => self.set_column(column.saturating_add(amount)),
+            'D' => self.set_column(column - amount),
+            'E' => {
+                self.set_row(row.saturating_add(amount));
+                self.set_column(0);
+            }
+            'F' => {
+                self.set_row(row - amount);
+                self.set_column(0);
+            }
+            'G' => self.set_column(amount - 1),
+            'd' => self.set_row(amount - 1),
+            'H' | 'f' => {
+                self.set_row(amount - 1);
+                self.set_column(second.max(1).min(i16::MAX as u64) as i16 - 1);
+            }
+            'J' => {
+                let last_row = self.canvas.area().last_row();
+                match first {
+                    0 => {
+                        self.erase(row, column, self.last_column());
+                        if let Some(next_row) = row.checked_add(1) {
+                            self.erase_rows(next_row, last_row);
+                        }
+                    }
+                    1 => {
+                        self.erase_rows(0, row - 1);
+                        self.erase(row, 0, column);
+                    }
+                    _ => self.erase_rows(0, last_row),
+                }
+            }
+            'K' => match first {
+                0 => self.erase(row, column, self.last_column()),
+                1 => self.erase(row, 0, column),
+                _ => self.erase(row, 0, self.last_column()),
+            },
+            's' => self.saved_cursor = self.cursor,
+            'u' => self.cursor = self.saved_cursor,
+            // Unsupported sequences are ignored, like terminals do
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+impl Canvas {
+    /// Imports ANSI text by running it through a virtual terminal, which supports cursor
+    /// movement, erasing and carriage returns overwriting earlier cells. This allows importing
+    /// recorded terminal output.
+    ///
+    /// Lines wrap at `columns`, if given. Unsupported escape sequences are ignored.
+    pub fn from_terminal_ansi(ansi: &str, columns: Option<u16>) -> Result<Self, AnsiImportError> {
+        let mut terminal = VirtualTerminal::new(columns);
+        terminal.run(ansi.chars())?;
+        Ok(terminal.into_canvas())
+    }
+}
@@ -0,0 +1,144 @@
+use ratatui::style::{Color, Modifier};
+
+use crate::canvas::raw::ansi_import::AnsiImportError;
+use crate::canvas::raw::Canvas;
+
+/// Returns the characters of `canvas` as one string per row, from column 0.
+pub(crate) fn rows(canvas: &Canvas) -> Vec<String> {
+    let area = canvas.area();
+    (0..=area.last_row())
+        .map(|row| {
+            (0..=area.last_column())
+                .map(|column| canvas.character((row, column)))
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn carriage_return_overwrites() {
+    let canvas = Canvas::from_terminal_ansi("abcd\rxy\n12", None).unwrap();
+
+    assert_eq!(rows(&canvas), ["xycd", "12"]);
+}
+
+#[test]
+fn cursor_movement() {
+    let ansi = "abc\x1b[2Dx\x1b[Bd\x1b[2Ae\x1b[3Cf";
+    let canvas = Canvas::from_terminal_ansi(ansi, None).unwrap();
+
+    assert_eq!(rows(&canvas), ["axce   f", "  d"]);
+}
+
+#[test]
+fn cursor_position() {
+    let ansi = "\x1b[3;4Ha\x1b[Hb\x1b[2;2fc\x1b[5Gd";
+    let canvas = Canvas::from_terminal_ansi(ansi, None).unwrap();
+
+    assert_eq!(rows(&canvas), ["b", " c  d", "   a"]);
+}
+
+#[test]
+fn erase_line() {
+    let ansi = "abcdef\r\x1b[2C\x1b[K\n123456\x1b[3D\x1b[1K\nxyz\x1b[2K";
+    let canvas = Canvas::from_terminal_ansi(ansi, None).unwrap();
+
+    assert_eq!(rows(&canvas), ["ab", "    56"]);
+}
+
+#[test]
+fn erase_screen() {
+    let ansi = "abc\ndef\x1b[2Jx";
+    let canvas = Canvas::from_terminal_ansi(ansi, None).unwrap();
+
+    assert_eq!(canvas.cells.len(), 1);
+    assert_eq!(canvas.get(&(1, 3)).character, 'x');
+}
+
+#[test]
+fn erase_fills_background() {
+    let ansi = "abcd\r\x1b[41m\x1b[K";
+    let canvas = Canvas::from_terminal_ansi(ansi, None).unwrap();
+
+    assert_eq!(canvas.cells.len(), 4);
+    assert_eq!(canvas.get(&(0, 3)).character, ' ');
+    assert_eq!(canvas.get(&(0, 3)).bg, Color::Red);
+}
+
+#[test]
+fn erase_stays_inside_drawn_area() {
+    let ansi = "\x1b[1;32767Ha\x1b[32767;1H\x1b[1J\x1b[2J";
+    let canvas = Canvas::from_terminal_ansi(ansi, None).unwrap();
+
+    assert_eq!(canvas.cells.len(), 0);
+}
+
+#[test]
+fn erase_below_last_row() {
+    let ansi = "\x1b[32767;1H\x1b[Babc\x1b[2D\x1b[J";
+    let canvas = Canvas::from_terminal_ansi(ansi, None).unwrap();
+
+    assert_eq!(canvas.character((i16::MAX, 0)), 'a');
+    assert_eq!(canvas.character((i16::MAX, 1)), ' ');
+}
+
+#[test]
+fn wrap() {
+    let canvas = Canvas::from_terminal_ansi("abcde\x1b[10Cfg", Some(3)).unwrap();
+
+    assert_eq!(rows(&canvas), ["abc", "def", "g"]);
+}
+
+#[test]
+fn sgr() {
+    let canvas = Canvas::from_terminal_ansi("\x1b[1;31ma\x1b[22mb\x1b[0mc", None).unwrap();
+
+    assert_eq!(canvas.get(&(0, 0)).fg, Color::Red);
+    assert_eq!(canvas.get(&(0, 0)).modifiers, Modifier::BOLD);
+    assert_eq!(canvas.get(&(0, 1)).fg, Color::Red);
+    assert_eq!(canvas.get(&(0, 1)).modifiers, Modifier::empty());
+    assert_eq!(canvas.get(&(0, 2)).fg, Color::Reset);
+}
+
+#[test]
+fn ignores_unsupported_sequences() {
+    let ansi = "\x1b[?25l\x1b]0;title\x07\x1b(Ba\x1b[?1049h\x1b=b\x1b[5n\x07c";
+    let canvas = Canvas::from_terminal_ansi(ansi, None).unwrap();
+
+    assert_eq!(rows(&canvas), ["abc"]);
+}
+
+#[test]
+fn save_and_restore_cursor() {
+    let canvas = Canvas::from_terminal_ansi("a\x1b7bc\x1b8d\x1b[sef\x1b[ug", None).unwrap();
+
+    assert_eq!(rows(&canvas), ["adgf"]);
+}
+
+#[test]
+fn tab() {
+    let canvas = Canvas::from_terminal_ansi("a\tb\t\tc", None).unwrap();
+
+    assert_eq!(canvas.get(&(0, 8)).character, 'b');
+    assert_eq!(canvas.get(&(0, 24)).character, 'c');
+}
+
+#[test]
+fn unfinished_escape_sequence() {
+    let result = Canvas::from_terminal_ansi("a\x1b[1", None);
+
+    assert!(matches!(
+        result,
+        Err(AnsiImportError::UnfinishedEscapeSequence((0, 1)))
+    ));
+}
+
+#[test]
+fn grapheme_clusters() {
+    let canvas = Canvas::from_terminal_ansi("a\u{308}b\rc\u{301}", None).unwrap();
+
+    assert_eq!(canvas.get(&(0, 0)).grapheme(), "c\u{301}");
+    assert_eq!(canvas.get(&(0, 1)).grapheme(), "b");
+}
@@ -1,8 +1,12 @@
-use super::Canvas;
+use ratatui::style::Color;
+
+use super::{Canvas, Grapheme, WIDE_CHARACTER_CONTINUATION};
+use crate::canvas::rect::CanvasRect;
+use crate::canvas::{CanvasModification, VersionControlledCanvas};
 
 const RESET_ALL: &str = "\u{1b}[0m";
 const RESET_FG: &str = "\u{1b}[39m";
-const _RESET_BG: &str = "\u{1b}[49m";
+const RESET_BG: &str = "\u{1b}[49m";
 
 #[allow(non_snake_case)]
 fn FG_INDEXED(index: u8) -> String {
@@ -34,6 +38,57 @@ const REVERSED: &str = "\u{1b}[7m";
 const _HIDDEN: &str = "\u{1b}[8m";
 const CROSSED_OUT: &str = "\u{1b}[9m";
 
+#[test]
+fn grapheme_continued_by_combining_characters() {
+    let mut grapheme = Grapheme::from('e');
+    assert!(grapheme.continued_by('\u{301}'));
+    assert!(!grapheme.continued_by('f'));
+    grapheme.push('\u{301}');
+    assert_eq!(grapheme.character(), 'e');
+    assert_eq!(grapheme.combining(), "\u{301}");
+
+    let canvas = Canvas::from_txt("e\u{301}e".to_string()).unwrap();
+    assert!(grapheme.matches(canvas.get(&(0, 0))));
+    assert!(!grapheme.matches(canvas.get(&(0, 1))));
+    assert!(!Grapheme::from('e').matches(canvas.get(&(0, 0))));
+}
+
+#[test]
+fn set_grapheme_replaces_whole_cluster() {
+    let mut canvas = VersionControlledCanvas::default();
+    canvas.create_commit(vec![
+        CanvasModification::SetCharacter((0, 0), 'a'),
+        CanvasModification::AddCombiningCharacter((0, 0), '\u{308}'),
+    ]);
+    let mut grapheme = Grapheme::from('e');
+    grapheme.push('\u{301}');
+    canvas.create_commit(vec![CanvasModification::SetGrapheme((0, 0), grapheme)]);
+    assert_eq!(canvas.raw().get(&(0, 0)).grapheme(), "e\u{301}");
+
+    canvas.create_commit(vec![CanvasModification::SetGrapheme((0, 0), 'x'.into())]);
+    assert_eq!(canvas.raw().get(&(0, 0)).grapheme(), "x");
+}
+
+#[test]
+fn fit_to_page() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_bg((0, 1), Color::Red);
+    canvas.set_character((0, 5), 'x');
+    canvas.set_character((1, 2), '漢');
+    canvas.set_character((1, 3), WIDE_CHARACTER_CONTINUATION);
+    canvas.fit_to(CanvasRect {
+        row: 0,
+        column: 0,
+        rows: 2,
+        columns: 3,
+    });
+    // Cells outside the page are removed, and the page is padded with spaces, keeping trailing
+    // background colored spaces
+    let expected = format!("{RESET_ALL}a{} {RESET_BG} \n   \n", BG_INDEXED(1));
+    assert_eq!(canvas.export_ansi().unwrap(), expected);
+}
+
 mod import_export {
     use super::*;
 
@@ -45,6 +100,18 @@ mod import_export {
         assert_eq!(input, output);
     }
 
+    #[test]
+    fn wide_characters() {
+        assert_preserved(&format!("{RESET_ALL}a漢字b\n 🎨\n"));
+        assert_preserved(&format!("{RESET_ALL}{}漢{RESET_ALL} x\n", FG_INDEXED(1)));
+    }
+
+    #[test]
+    fn grapheme_clusters() {
+        assert_preserved(&format!("{RESET_ALL}e\u{301}a\u{308}\u{304}\n"));
+        assert_preserved(&format!("{RESET_ALL}👨\u{200d}👩\u{200d}👧x❤\u{fe0f}\n"));
+    }
+
     #[test]
     fn basic() {
         assert_preserved(format!("{RESET_ALL}abc\n").as_str());
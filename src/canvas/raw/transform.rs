@@ -1,12 +1,18 @@
 use crate::canvas::raw::cell_map::BTreeCellMap;
 use crate::canvas::raw::CanvasCell;
 use crate::canvas::raw::CanvasIndex;
+use crate::canvas::raw::{is_wide, WIDE_CHARACTER_CONTINUATION};
 use crate::Axis;
+use crate::ProgramState;
 use crate::RotationDirection;
+use keystrokes_parsing::Presetable;
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
+#[cfg(test)]
+mod test;
+
 fn cell_map_insert_option(
     cells: &mut BTreeCellMap,
     index: CanvasIndex,
@@ -32,6 +38,7 @@ fn swap_indices_iter(
         apply_character_swap_map_to_cell_option(previous_cell.as_mut(), character_swaps);
         previous_cell = cell_map_insert_option(cells, index, previous_cell);
     }
+    apply_character_swap_map_to_cell_option(previous_cell.as_mut(), character_swaps);
     cell_map_insert_option(cells, first, previous_cell);
 }
 
@@ -67,6 +74,102 @@ fn apply_character_swap_map_to_cell_option(cell: Option<&mut CanvasCell>, map: &
     }
 }
 
+/// Characters replaced by others when cells are rotated, like `─` by `│`. Deserialized from the
+/// replacements for clockwise rotation, where a pair only given one way, like `─` to `│`, is
+/// swapped both ways, unless it is part of a longer cycle. Counterclockwise rotation replaces
+/// the characters the opposite way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CharacterRotationMap {
+    clockwise: CharacterSwapMap,
+    counterclockwise: CharacterSwapMap,
+}
+
+impl CharacterRotationMap {
+    pub fn from_clockwise(rotations: impl IntoIterator<Item = (char, char)>) -> Self {
+        let mut clockwise = HashMap::new();
+        for (a, b) in rotations {
+            clockwise.insert(a, b);
+        }
+        let rotated: HashSet<char> = clockwise.values().copied().collect();
+        for (a, b) in clockwise.clone() {
+            // Characters rotated from others, like `┐` rotated from `┌`, are part of a longer
+            // cycle, and rotating back to them is left to the other entries of the cycle
+            if !rotated.contains(&a) {
+                clockwise.entry(b).or_insert(a);
+            }
+        }
+        let counterclockwise = clockwise.iter().map(|(a, b)| (*b, *a)).collect();
+        Self {
+            clockwise: CharacterSwapMap(clockwise),
+            counterclockwise: CharacterSwapMap(counterclockwise),
+        }
+    }
+    pub fn swaps(&self, direction: RotationDirection) -> &CharacterSwapMap {
+        match direction {
+            RotationDirection::Clockwise => &self.clockwise,
+            RotationDirection::Counterclockwise => &self.counterclockwise,
+        }
+    }
+}
+
+impl Default for CharacterRotationMap {
+    fn default() -> Self {
+        Self::from_clockwise([
+            ('─', '│'),
+            ('━', '┃'),
+            ('═', '║'),
+            ('-', '|'),
+            ('/', '\\'),
+            ('┌', '┐'),
+            ('┐', '┘'),
+            ('┘', '└'),
+            ('└', '┌'),
+            ('┏', '┓'),
+            ('┓', '┛'),
+            ('┛', '┗'),
+            ('┗', '┏'),
+            ('╔', '╗'),
+            ('╗', '╝'),
+            ('╝', '╚'),
+            ('╚', '╔'),
+            ('╭', '╮'),
+            ('╮', '╯'),
+            ('╯', '╰'),
+            ('╰', '╭'),
+            ('├', '┬'),
+            ('┬', '┤'),
+            ('┤', '┴'),
+            ('┴', '├'),
+            ('┣', '┳'),
+            ('┳', '┫'),
+            ('┫', '┻'),
+            ('┻', '┣'),
+            ('╠', '╦'),
+            ('╦', '╣'),
+            ('╣', '╩'),
+            ('╩', '╠'),
+            ('▀', '▐'),
+            ('▐', '▄'),
+            ('▄', '▌'),
+            ('▌', '▀'),
+            ('→', '↓'),
+            ('↓', '←'),
+            ('←', '↑'),
+            ('↑', '→'),
+        ])
+    }
+}
+
+impl<'de> Deserialize<'de> for CharacterRotationMap {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let rotations = HashMap::<char, char>::deserialize(deserializer)?;
+        Ok(Self::from_clockwise(rotations))
+    }
+}
+
 pub fn map_from_swap_pairs(swaps: impl Iterator<Item = (char, char)>) -> CharacterSwapMap {
     let mut map = HashMap::new();
     for (a, b) in swaps {
@@ -102,6 +205,46 @@ pub fn mirror_cells(cells: &mut BTreeCellMap, axis: Axis, mirror: i16, swaps: &C
         indices_mirrored.insert(index);
         indices_mirrored.insert(index_mirrored);
     }
+    if axis == Axis::X {
+        reorder_wide_character_pairs(cells);
+    }
+}
+
+/// Swaps wide characters that have ended up to the right of their continuation cell, like after
+/// mirroring horizontally, back in front of it.
+pub fn reorder_wide_character_pairs(cells: &mut BTreeCellMap) {
+    let character = |cells: &BTreeCellMap, index: CanvasIndex| {
+        cells.get(&index).map(|cell: &CanvasCell| cell.character)
+    };
+    for (row, column) in cells.keys().cloned().collect::<Vec<_>>() {
+        let is_reversed_pair = character(cells, (row, column)) == Some(WIDE_CHARACTER_CONTINUATION)
+            && !character(cells, (row, column - 1)).is_some_and(is_wide)
+            && character(cells, (row, column + 1)).is_some_and(is_wide);
+        if is_reversed_pair {
+            let continuation = cells.remove(&(row, column)).unwrap();
+            let wide = cells.insert((row, column + 1), continuation).unwrap();
+            cells.insert((row, column), wide);
+        }
+    }
+}
+
+/// Moves every cell to the index `target` returns for it and replaces its character by `swaps`.
+/// The target is given in `i32`, so that it can be computed without overflowing, and cells whose
+/// target doesn't fit the canvas coordinates are dropped.
+pub fn move_cells(
+    cells: BTreeCellMap,
+    target: impl Fn(CanvasIndex) -> (i32, i32),
+    swaps: &CharacterSwapMap,
+) -> BTreeCellMap {
+    cells
+        .into_iter()
+        .filter_map(|(index, mut cell)| {
+            let (row, column) = target(index);
+            let moved = (i16::try_from(row).ok()?, i16::try_from(column).ok()?);
+            apply_character_swap_map_to_cell_option(Some(&mut cell), swaps);
+            Some((moved, cell))
+        })
+        .collect()
 }
 
 fn rotate_index(
@@ -109,14 +252,15 @@ fn rotate_index(
     rotation_index: CanvasIndex,
     direction: RotationDirection,
 ) -> CanvasIndex {
+    let (rows, columns) = (index.0 - rotation_index.0, index.1 - rotation_index.1);
     if direction == RotationDirection::Clockwise {
-        (rotation_index.0 + index.1, rotation_index.1 - index.0)
+        (rotation_index.0 + columns, rotation_index.1 - rows)
     } else {
-        (rotation_index.0 - index.1, rotation_index.1 + index.0)
+        (rotation_index.0 - columns, rotation_index.1 + rows)
     }
 }
 
-/// Given axis is not the mirror line, but rather the axis along which the mirroring is performed.
+/// Rotates the cells a quarter turn around `rotation_index`.
 pub fn rotate_cells(
     cells: &mut BTreeCellMap,
     rotation_index: CanvasIndex,
@@ -129,6 +273,11 @@ pub fn rotate_cells(
             continue;
         };
         let index_b = rotate_index(index, rotation_index, direction);
+        if index_b == index {
+            apply_character_swap_map_to_cell_option(cells.get_mut(&index), swaps);
+            indices_rotated.insert(index);
+            continue;
+        }
         let index_c = rotate_index(index_b, rotation_index, direction);
         let index_d = rotate_index(index_c, rotation_index, direction);
         swap_indices!(cells, swaps, index, index_b, index_c, index_d,);
@@ -138,3 +287,137 @@ pub fn rotate_cells(
         indices_rotated.insert(index_d);
     }
 }
+
+/// Makes every wide character followed by a continuation cell, after the cells have been moved
+/// around. Wide characters get a continuation cell added if the cell to their right is empty,
+/// otherwise they are replaced by a space, like orphaned continuation cells are.
+pub fn pair_wide_characters(cells: &mut BTreeCellMap) {
+    let space = |cell: &mut CanvasCell| {
+        cell.character = ' ';
+        cell.combining.clear();
+    };
+    for (row, column) in cells.keys().cloned().collect::<Vec<_>>() {
+        let cell = &cells[&(row, column)];
+        if is_wide(cell.character) {
+            match cells.get(&(row, column + 1)) {
+                Some(right) if right.character == WIDE_CHARACTER_CONTINUATION => {}
+                Some(_right) => space(cells.get_mut(&(row, column)).unwrap()),
+                None => {
+                    let continuation = CanvasCell {
+                        character: WIDE_CHARACTER_CONTINUATION,
+                        combining: String::new(),
+                        ..cell.clone()
+                    };
+                    cells.insert((row, column + 1), continuation);
+                }
+            }
+        } else if cell.character == WIDE_CHARACTER_CONTINUATION
+            && !cells
+                .get(&(row, column - 1))
+                .is_some_and(|left| is_wide(left.character))
+        {
+            space(cells.get_mut(&(row, column)).unwrap());
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub enum Scale {
+    /// Makes every cell a square of this many cells.
+    #[presetable(default)]
+    Up(u32),
+    /// Makes every square of this many cells a single cell.
+    Down(u32),
+}
+
+/// Largest factor cells can be scaled up by, as every cell turns into `factor * factor` cells.
+pub const SCALE_UP_FACTOR_MAX: u32 = 16;
+
+/// Largest factor the cells can be scaled up by, where the scaled cells still fit the canvas
+/// coordinates, capped at `SCALE_UP_FACTOR_MAX`.
+pub fn scale_up_factor_max(cells: &BTreeCellMap) -> u32 {
+    let reach = cells
+        .keys()
+        .map(|&(row, column)| {
+            let reach = |coordinate: i16| {
+                if coordinate < 0 {
+                    coordinate.unsigned_abs() as u32
+                } else {
+                    coordinate as u32 + 1
+                }
+            };
+            reach(row).max(reach(column))
+        })
+        .max()
+        .unwrap_or(1);
+    (i16::MAX as u32 + 1)
+        .div_euclid(reach)
+        .clamp(1, SCALE_UP_FACTOR_MAX)
+}
+
+/// Scales the cells around the index `(0, 0)` by a factor of at least 1. Scaling up duplicates
+/// every cell, where wide characters are duplicated together with their continuation cell.
+/// Scaling down samples the top left cell of every square, or the first cell of it if the top
+/// left cell is empty. Scaling up is limited to `SCALE_UP_FACTOR_MAX`.
+pub fn scale_cells(cells: &mut BTreeCellMap, scale: Scale) {
+    let scaled = match scale {
+        Scale::Up(factor) => scale_up(cells, factor.min(SCALE_UP_FACTOR_MAX) as i32),
+        Scale::Down(factor) => scale_down(cells, i32::try_from(factor).unwrap_or(i32::MAX)),
+    };
+    *cells = scaled;
+    pair_wide_characters(cells);
+}
+
+fn scale_up(cells: &BTreeCellMap, factor: i32) -> BTreeCellMap {
+    let mut result = BTreeCellMap::new();
+    for (&(row, column), cell) in cells {
+        // A wide character and its continuation cell are scaled as a pair, so that the copies of
+        // the wide character alternate with copies of the continuation cell
+        let pair_column = if cell.character == WIDE_CHARACTER_CONTINUATION {
+            column - 1
+        } else {
+            column
+        };
+        let is_pair = cell.character == WIDE_CHARACTER_CONTINUATION || is_wide(cell.character);
+        for row_offset in 0..factor {
+            for column_offset in 0..factor {
+                let scaled_column = column as i32 * factor + column_offset;
+                let scaled = (row as i32 * factor + row_offset, scaled_column);
+                let (Ok(scaled_row), Ok(scaled_column)) =
+                    (i16::try_from(scaled.0), i16::try_from(scaled.1))
+                else {
+                    continue;
+                };
+                let source = if is_pair {
+                    let offset = scaled.1 - pair_column as i32 * factor;
+                    cells.get(&(row, pair_column + (offset % 2) as i16))
+                } else {
+                    Some(cell)
+                };
+                if let Some(source) = source {
+                    result.insert((scaled_row, scaled_column), source.clone());
+                }
+            }
+        }
+    }
+    result
+}
+
+fn scale_down(cells: &BTreeCellMap, factor: i32) -> BTreeCellMap {
+    let mut result = BTreeCellMap::new();
+    for (&(row, column), cell) in cells {
+        let scaled = (
+            (row as i32).div_euclid(factor) as i16,
+            (column as i32).div_euclid(factor) as i16,
+        );
+        let is_top_left =
+            (row as i32).rem_euclid(factor) == 0 && (column as i32).rem_euclid(factor) == 0;
+        if is_top_left {
+            result.insert(scaled, cell.clone());
+        } else {
+            result.entry(scaled).or_insert_with(|| cell.clone());
+        }
+    }
+    result
+}
@@ -0,0 +1,177 @@
+use crate::canvas::raw::cell_map::BTreeCellMap;
+use crate::canvas::raw::transform::{
+    map_from_swap_pairs, mirror_cells, move_cells, pair_wide_characters, rotate_cells, scale_cells,
+    scale_up_factor_max, CharacterRotationMap, Scale, SCALE_UP_FACTOR_MAX,
+};
+use crate::canvas::raw::{CanvasCell, WIDE_CHARACTER_CONTINUATION};
+use crate::{Axis, RotationDirection};
+
+fn cells(characters: &[((i16, i16), char)]) -> BTreeCellMap {
+    characters
+        .iter()
+        .map(|(index, character)| {
+            (
+                *index,
+                CanvasCell {
+                    character: *character,
+                    ..Default::default()
+                },
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn mirror_swaps_both_cells() {
+    let mut result = cells(&[((0, -1), '('), ((0, 1), ')')]);
+    mirror_cells(
+        &mut result,
+        Axis::X,
+        0,
+        &map_from_swap_pairs([('(', ')')].into_iter()),
+    );
+    assert_eq!(result, cells(&[((0, -1), '('), ((0, 1), ')')]));
+}
+
+#[test]
+fn move_cells_far() {
+    let original = cells(&[((0, 20000), '('), ((0, -20000), ')'), ((0, i16::MIN), 'x')]);
+    // Mirrored around the column 20000, in half cells
+    let result = move_cells(
+        original,
+        |(row, column)| (row as i32, 40000 - column as i32),
+        &map_from_swap_pairs([('(', ')')].into_iter()),
+    );
+    assert_eq!(result, cells(&[((0, 20000), ')')]));
+}
+
+#[test]
+fn rotate_around_index() {
+    let rotations = CharacterRotationMap::from_clockwise([('─', '│'), ('┌', '┐'), ('┐', '┘')]);
+    // ┌─
+    //  X
+    let mut result = cells(&[((4, 5), '┌'), ((4, 6), '─'), ((5, 6), 'X')]);
+    let swaps = rotations.swaps(RotationDirection::Clockwise);
+    rotate_cells(&mut result, (5, 6), RotationDirection::Clockwise, swaps);
+    // ┐
+    // │X
+    assert_eq!(
+        result,
+        cells(&[((4, 7), '┐'), ((5, 7), '│'), ((5, 6), 'X')])
+    );
+
+    let swaps = rotations.swaps(RotationDirection::Counterclockwise);
+    rotate_cells(
+        &mut result,
+        (5, 6),
+        RotationDirection::Counterclockwise,
+        swaps,
+    );
+    assert_eq!(
+        result,
+        cells(&[((4, 5), '┌'), ((4, 6), '─'), ((5, 6), 'X')])
+    );
+}
+
+#[test]
+fn rotation_map_swaps_pairs_given_one_way() {
+    let rotations = CharacterRotationMap::from_clockwise([('─', '│'), ('┌', '┐'), ('┐', '┘')]);
+    let swaps = rotations.swaps(RotationDirection::Clockwise);
+    assert_eq!(swaps.0.get(&'│'), Some(&'─'));
+    assert_eq!(swaps.0.get(&'┐'), Some(&'┘'));
+    let swaps = rotations.swaps(RotationDirection::Counterclockwise);
+    assert_eq!(swaps.0.get(&'┘'), Some(&'┐'));
+    assert_eq!(swaps.0.get(&'─'), Some(&'│'));
+}
+
+#[test]
+fn rotation_map_keeps_partial_cycles_one_way() {
+    // Hash maps iterate in a different order every time, which made the inverted entries of a
+    // partial cycle depend on the order
+    for _ in 0..20 {
+        let rotations = CharacterRotationMap::from_clockwise([('┌', '┐'), ('┐', '┘')]);
+        let swaps = rotations.swaps(RotationDirection::Clockwise);
+        assert_eq!(swaps.0.get(&'┌'), Some(&'┐'));
+        assert_eq!(swaps.0.get(&'┐'), Some(&'┘'));
+        assert_eq!(swaps.0.get(&'┘'), None);
+        let swaps = rotations.swaps(RotationDirection::Counterclockwise);
+        assert_eq!(swaps.0.get(&'┐'), Some(&'┌'));
+        assert_eq!(swaps.0.get(&'┘'), Some(&'┐'));
+        assert_eq!(swaps.0.get(&'┌'), None);
+    }
+}
+
+#[test]
+fn scale_up_and_down() {
+    let original = cells(&[((0, 0), 'a'), ((0, 1), 'b'), ((-1, 0), 'c')]);
+    let mut result = original.clone();
+    scale_cells(&mut result, Scale::Up(2));
+    assert_eq!(
+        result,
+        cells(&[
+            ((-2, 0), 'c'),
+            ((-2, 1), 'c'),
+            ((-1, 0), 'c'),
+            ((-1, 1), 'c'),
+            ((0, 0), 'a'),
+            ((0, 1), 'a'),
+            ((0, 2), 'b'),
+            ((0, 3), 'b'),
+            ((1, 0), 'a'),
+            ((1, 1), 'a'),
+            ((1, 2), 'b'),
+            ((1, 3), 'b'),
+        ])
+    );
+    scale_cells(&mut result, Scale::Down(2));
+    assert_eq!(result, original);
+}
+
+#[test]
+fn scale_factor_limits() {
+    assert_eq!(
+        scale_up_factor_max(&cells(&[((0, 0), 'a')])),
+        SCALE_UP_FACTOR_MAX
+    );
+    assert_eq!(scale_up_factor_max(&cells(&[((-4096, 0), 'a')])), 8);
+    assert_eq!(scale_up_factor_max(&cells(&[((0, 4096), 'a')])), 7);
+    assert_eq!(scale_up_factor_max(&cells(&[((i16::MAX, 0), 'a')])), 1);
+
+    let mut result = cells(&[((1000, -1000), 'a'), ((0, 0), 'b')]);
+    scale_cells(&mut result, Scale::Down(u32::MAX));
+    assert_eq!(result, cells(&[((0, -1), 'a'), ((0, 0), 'b')]));
+}
+
+#[test]
+fn scale_up_wide_characters() {
+    let mut result = cells(&[((0, 0), '字'), ((0, 1), WIDE_CHARACTER_CONTINUATION)]);
+    scale_cells(&mut result, Scale::Up(2));
+    let row = |row| {
+        cells(&[
+            ((row, 0), '字'),
+            ((row, 1), WIDE_CHARACTER_CONTINUATION),
+            ((row, 2), '字'),
+            ((row, 3), WIDE_CHARACTER_CONTINUATION),
+        ])
+    };
+    let mut expected = row(0);
+    expected.extend(row(1));
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn pair_wide_characters_after_rotation() {
+    let mut result = cells(&[((0, 0), '字'), ((0, 1), WIDE_CHARACTER_CONTINUATION)]);
+    let rotations = CharacterRotationMap::default();
+    let direction = RotationDirection::Clockwise;
+    rotate_cells(&mut result, (0, 0), direction, rotations.swaps(direction));
+    pair_wide_characters(&mut result);
+    assert_eq!(
+        result,
+        cells(&[
+            ((0, 0), '字'),
+            ((0, 1), WIDE_CHARACTER_CONTINUATION),
+            ((1, 0), ' ')
+        ])
+    );
+}
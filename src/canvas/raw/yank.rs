@@ -1,14 +1,16 @@
 use ratatui::style::Color;
 use ratatui::style::Modifier;
-use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::canvas::raw::Canvas;
 use crate::canvas::raw::CanvasCell;
 use crate::canvas::raw::CanvasIndex;
 use crate::canvas::raw::CellContentType;
+use crate::canvas::raw::{is_wide, WIDE_CHARACTER_CONTINUATION};
 
 // A piece of art yanked from a Canvas
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CanvasYank {
     pub cells: BTreeMap<CanvasIndex, CanvasCell>,
     pub content_type: CellContentType,
@@ -21,11 +23,21 @@ impl Canvas {
         content_type: CellContentType,
         origo: CanvasIndex,
     ) -> CanvasYank {
+        // Wide characters are always yanked together with their continuation cell
+        let mut indices: BTreeSet<CanvasIndex> = indices.into_iter().collect();
+        for (row, column) in indices.clone() {
+            match self.character((row, column)) {
+                WIDE_CHARACTER_CONTINUATION => indices.insert((row, column - 1)),
+                character if is_wide(character) => indices.insert((row, column + 1)),
+                _ => false,
+            };
+        }
         let mut cells = BTreeMap::new();
         for index in indices {
             let mut cell = self.cells.get(&index).cloned().unwrap_or_default();
             if !content_type.contains(CellContentType::TEXT) {
                 cell.character = ' ';
+                cell.combining.clear();
             }
             if !content_type.contains(CellContentType::FG) {
                 cell.fg = Color::Reset;
@@ -44,3 +56,20 @@ impl Canvas {
         }
     }
 }
+
+impl CanvasYank {
+    /// Yanks all cells of `canvas`, with the canvas origo as the yank origo.
+    pub fn from_canvas(canvas: &Canvas) -> Self {
+        let indices: Vec<_> = canvas.cells.iter().map(|(index, _cell)| *index).collect();
+        canvas.yank(indices, CellContentType::ALL, (0, 0))
+    }
+
+    /// Returns a canvas containing the yanked cells, with the yank origo as the canvas origo.
+    pub fn to_canvas(&self) -> Canvas {
+        let mut canvas = Canvas::default();
+        for (index, cell) in &self.cells {
+            canvas.set_cell(*index, cell.clone());
+        }
+        canvas
+    }
+}
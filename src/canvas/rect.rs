@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 use super::raw::CanvasIndex;
 
-#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub struct CanvasRect {
     pub row: i16,
     pub column: i16,
@@ -56,12 +58,16 @@ impl CanvasRect {
         self.column
     }
 
+    // Wrapping, because `rows` and `columns` can exceed `i16::MAX` while the last row and column
+    // still fit
     pub fn last_row(&self) -> i16 {
-        self.row + (self.rows as i16) - 1
+        self.row.wrapping_add(self.rows as i16).wrapping_sub(1)
     }
 
     pub fn last_column(&self) -> i16 {
-        self.column + (self.columns as i16) - 1
+        self.column
+            .wrapping_add(self.columns as i16)
+            .wrapping_sub(1)
     }
 
     pub fn center(&self) -> CanvasIndex {
@@ -82,6 +88,15 @@ impl CanvasRect {
         self.includes_row(row) && self.includes_column(column)
     }
 
+    /// The index inside the rectangle closest to `index`. The rectangle must not be empty.
+    pub fn clamp_index(&self, index: CanvasIndex) -> CanvasIndex {
+        let (row, column) = index;
+        (
+            row.clamp(self.first_row(), self.last_row()),
+            column.clamp(self.first_column(), self.last_column()),
+        )
+    }
+
     /// Returns a tuple (rows, columns) describing how far and in which direction an index is away
     /// from self.
     pub fn away_index(&self, index: CanvasIndex) -> (i16, i16) {
@@ -121,6 +136,15 @@ impl CanvasRect {
         }
     }
 
+    /// Grows the rectangle to also cover `other`. Empty rectangles cover nothing.
+    pub fn include_rect(&mut self, other: CanvasRect) {
+        if other.rows == 0 || other.columns == 0 {
+            return;
+        }
+        self.include_index((other.first_row(), other.first_column()));
+        self.include_index((other.last_row(), other.last_column()));
+    }
+
     pub fn indices_contained(&self) -> Vec<CanvasIndex> {
         let mut result = Vec::new();
         for row in self.row..(self.row + self.rows as i16) {
@@ -0,0 +1,69 @@
+//! The system clipboard, accessed through the terminal with OSC 52 escape sequences. This works
+//! over SSH and without any clipboard libraries, as long as the terminal supports it.
+
+use std::io::{self, Write};
+
+use crate::canvas::raw::yank::CanvasYank;
+use crate::canvas::raw::Canvas;
+
+#[cfg(test)]
+mod test;
+
+/// Yank slot whose contents are also copied to the system clipboard.
+pub const CLIPBOARD_YANK_SLOT: char = '+';
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let value = chunk.iter().enumerate().fold(0u32, |value, (i, byte)| {
+            value | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                result.push(BASE64_ALPHABET[(value >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                result.push('=');
+            }
+        }
+    }
+    result
+}
+
+/// Returns the OSC 52 escape sequence setting the clipboard to `text`.
+pub fn osc52(text: &str) -> String {
+    format!("\u{1b}]52;c;{}\u{07}", base64(text.as_bytes()))
+}
+
+/// Copies `text` to the system clipboard by writing an OSC 52 escape sequence to the terminal.
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    stdout.write_all(osc52(text).as_bytes())?;
+    stdout.flush()
+}
+
+/// Copies `yank` to the system clipboard, see [`yank_to_text`].
+pub fn copy_yank_to_clipboard(yank: &CanvasYank, ansi: bool) -> anyhow::Result<()> {
+    copy_to_clipboard(&yank_to_text(yank, ansi)?)?;
+    Ok(())
+}
+
+/// Returns the contents of `yank` as text, with ANSI escape sequences for colors and modifiers
+/// if `ansi` is set.
+pub fn yank_to_text(yank: &CanvasYank, ansi: bool) -> anyhow::Result<String> {
+    let canvas = yank.to_canvas();
+    if ansi {
+        canvas.export_ansi()
+    } else {
+        canvas.export_txt_decolorize()
+    }
+}
+
+/// Reads pasted text, which may contain ANSI escape sequences, into a yank whose top left corner
+/// is the beginning of the text.
+pub fn yank_from_text(text: &str) -> anyhow::Result<CanvasYank> {
+    let canvas = Canvas::from_terminal_ansi(text, None)?;
+    Ok(CanvasYank::from_canvas(&canvas))
+}
@@ -0,0 +1,54 @@
+use ratatui::style::Color;
+
+use crate::canvas::raw::Canvas;
+use crate::canvas::raw::CellContentType;
+use crate::clipboard::{base64, osc52, yank_from_text, yank_to_text};
+
+#[test]
+fn base64_padding() {
+    assert_eq!(base64(b""), "");
+    assert_eq!(base64(b"f"), "Zg==");
+    assert_eq!(base64(b"fo"), "Zm8=");
+    assert_eq!(base64(b"foo"), "Zm9v");
+    assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    assert_eq!(base64("─│".as_bytes()), "4pSA4pSC");
+}
+
+#[test]
+fn osc52_sequence() {
+    assert_eq!(osc52("hi"), "\u{1b}]52;c;aGk=\u{07}");
+}
+
+fn yank_fixture() -> crate::canvas::raw::yank::CanvasYank {
+    let mut canvas = Canvas::default();
+    canvas.set_character((3, 4), 'a');
+    canvas.set_fg((3, 4), Color::Rgb(1, 2, 3));
+    canvas.set_character((4, 6), 'b');
+    canvas.yank([(3, 4), (4, 6)], CellContentType::ALL, (3, 4))
+}
+
+#[test]
+fn yank_to_plain_text() {
+    assert_eq!(yank_to_text(&yank_fixture(), false).unwrap(), "a\n  b\n");
+}
+
+#[test]
+fn yank_to_ansi() {
+    let text = yank_to_text(&yank_fixture(), true).unwrap();
+
+    assert!(text.contains('\u{1b}'));
+    assert_eq!(
+        Canvas::from_ansi(text).unwrap().fg((0, 0)),
+        Color::Rgb(1, 2, 3)
+    );
+}
+
+#[test]
+fn yank_from_pasted_text() {
+    let yank = yank_from_text("\u{1b}[31ma\u{1b}[0m\r\n\tb").unwrap();
+
+    assert_eq!(yank.cells[&(0, 0)].character, 'a');
+    assert_eq!(yank.cells[&(0, 0)].fg, Color::Red);
+    assert_eq!(yank.cells[&(1, 8)].character, 'b');
+    assert_eq!(yank.content_type, CellContentType::ALL);
+}
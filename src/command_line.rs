@@ -5,8 +5,18 @@ use ratatui::style::Style;
 use ratatui::widgets::Widget;
 use tui_textarea::{CursorMove, TextArea};
 
-use crate::actions::session::{ForceQuit, LossySave, LossySaveAs, Quit, Save, SaveAs, SaveQuit};
+use crate::actions::frames::FrameCommand;
+use crate::actions::history::{
+    BrowseHistory, Checkpoint, RestoreCheckpoint, ToggleChangedCells, UndoList,
+};
+use crate::actions::layers::LayerCommand;
+use crate::actions::metadata::{AddMetadataComment, MetadataField, SetMetadataField};
+use crate::actions::page::{Crop, Resize, Trim};
+use crate::actions::session::{
+    ExportOptions, ForceQuit, LossySave, LossySaveAs, Quit, ReadImage, Save, SaveAs, SaveQuit,
+};
 use crate::actions::{Action, FallibleAction};
+use crate::canvas::raw::image_import::ImageImportOptions;
 use crate::ProgramState;
 
 #[derive(Clone)]
@@ -70,6 +80,61 @@ impl<'a> Widget for CommandLineWidget<'a> {
     }
 }
 
+/// Parses the arguments of `:w`, which are a file name and `++colors=<depth>` or
+/// `++dither=<mode>` options, in any order. Returns `None` if there are no arguments.
+fn parse_save_arguments<'b>(
+    program_state: &ProgramState,
+    arguments: impl Iterator<Item = &'b str>,
+) -> anyhow::Result<Option<(String, ExportOptions)>> {
+    let mut filename = None;
+    let mut options = None;
+    for argument in arguments {
+        let options =
+            options.get_or_insert_with(|| ExportOptions::from_config(&program_state.config));
+        if let Some(depth) = argument.strip_prefix("++colors=") {
+            options.color_depth = depth.parse()?;
+        } else if let Some(dithering) = argument.strip_prefix("++dither=") {
+            options.dithering = dithering.parse()?;
+        } else if filename.replace(argument.to_string()).is_some() {
+            anyhow::bail!("Only one file name can be given");
+        }
+    }
+    let Some(options) = options else {
+        return Ok(None);
+    };
+    let filename = match filename {
+        Some(filename) => filename,
+        None => program_state.open_file.clone().ok_or_else(|| {
+            anyhow::anyhow!("No file open. Use \"save as\" instead (:w <filename>)")
+        })?,
+    };
+    Ok(Some((filename, options)))
+}
+
+/// Parses the arguments of `:r`, which are a file name and `++width=<columns>`,
+/// `++colors=<depth>` or `++dither=<mode>` options, in any order.
+fn parse_read_image_arguments<'b>(
+    arguments: impl Iterator<Item = &'b str>,
+) -> anyhow::Result<ReadImage> {
+    let mut filename = None;
+    let mut options = ImageImportOptions::default();
+    for argument in arguments {
+        if let Some(columns) = argument.strip_prefix("++width=") {
+            options.columns = Some(columns.parse()?);
+        } else if let Some(depth) = argument.strip_prefix("++colors=") {
+            options.color_depth = depth.parse()?;
+        } else if let Some(dithering) = argument.strip_prefix("++dither=") {
+            options.dithering = dithering.parse()?;
+        } else if filename.replace(argument.to_string()).is_some() {
+            anyhow::bail!("Only one file name can be given");
+        }
+    }
+    let Some(filename) = filename else {
+        anyhow::bail!("No file name given (:r <filename>)");
+    };
+    Ok(ReadImage { filename, options })
+}
+
 /// Executes the command stored in `program_state.command_line`
 pub fn execute_command(program_state: &mut ProgramState) -> anyhow::Result<()> {
     let command = program_state.command_line.lines().join("\n");
@@ -77,33 +142,72 @@ pub fn execute_command(program_state: &mut ProgramState) -> anyhow::Result<()> {
     let Some(command_name) = command_split.next() else {
         return Ok(());
     };
+    // Everything after the command name, for commands taking free text
+    let command_text = command
+        .trim_start()
+        .split_once(char::is_whitespace)
+        .map(|(_name, text)| text.trim())
+        .unwrap_or_default();
+    let set_metadata_field = |field| SetMetadataField {
+        field,
+        value: command_text.to_string(),
+    };
     let result: anyhow::Result<()> = match command_name {
         "q" => Quit {}.try_execute(program_state),
         "q!" => {
             ForceQuit {}.execute(program_state);
             Ok(())
         }
-        "w" => {
-            if let Some(filename) = command_split.next() {
-                SaveAs {
-                    filename: filename.to_string(),
-                }
-                .try_execute(program_state)
-            } else {
-                Save {}.try_execute(program_state)
+        "w" => match parse_save_arguments(program_state, command_split) {
+            Ok(Some((filename, options))) => {
+                SaveAs { filename, options }.try_execute(program_state)
             }
-        }
-        "w!" => {
-            if let Some(filename) = command_split.next() {
-                LossySaveAs {
-                    filename: filename.to_string(),
-                }
-                .try_execute(program_state)
-            } else {
-                LossySave {}.try_execute(program_state)
+            Ok(None) => Save {}.try_execute(program_state),
+            Err(err) => Err(err),
+        },
+        "w!" => match parse_save_arguments(program_state, command_split) {
+            Ok(Some((filename, options))) => {
+                LossySaveAs { filename, options }.try_execute(program_state)
             }
-        }
+            Ok(None) => LossySave {}.try_execute(program_state),
+            Err(err) => Err(err),
+        },
         "x" | "wq" => SaveQuit {}.try_execute(program_state),
+        "r" => parse_read_image_arguments(command_split)
+            .and_then(|read_image| read_image.try_execute(program_state)),
+        "title" => set_metadata_field(MetadataField::Title).try_execute(program_state),
+        "author" => set_metadata_field(MetadataField::Author).try_execute(program_state),
+        "group" => set_metadata_field(MetadataField::Group).try_execute(program_state),
+        "font" => set_metadata_field(MetadataField::Font).try_execute(program_state),
+        "comment" => AddMetadataComment {
+            comment: command_text.to_string(),
+        }
+        .try_execute(program_state),
+        "frame" => FrameCommand {
+            arguments: command_text.to_string(),
+        }
+        .try_execute(program_state),
+        "resize" => Resize {
+            arguments: command_text.to_string(),
+        }
+        .try_execute(program_state),
+        "crop" => Crop {}.try_execute(program_state),
+        "trim" => Trim {}.try_execute(program_state),
+        "undolist" => UndoList {}.try_execute(program_state),
+        "history" => BrowseHistory {}.try_execute(program_state),
+        "changes" => ToggleChangedCells {}.try_execute(program_state),
+        "checkpoint" => Checkpoint {
+            name: command_text.to_string(),
+        }
+        .try_execute(program_state),
+        "restore" => RestoreCheckpoint {
+            name: command_text.to_string(),
+        }
+        .try_execute(program_state),
+        "layer" => LayerCommand {
+            arguments: command_text.to_string(),
+        }
+        .try_execute(program_state),
         command => Err(anyhow::anyhow!("Command not found: {}", command)),
     };
     match result {
@@ -1,4 +1,8 @@
+use crate::actions::history::CommitMerging;
+use crate::canvas::raw::quantize::{ColorDepth, Dithering};
+use crate::canvas::raw::transform::CharacterRotationMap;
 use crate::canvas::raw::transform::CharacterSwapMap;
+use crate::file_formats::irc::IrcPalette;
 use crate::input_mode::InputMode;
 use crate::line_drawing::LineDrawingCharacters;
 use derive_more::Display;
@@ -56,7 +60,21 @@ nest! {
             pub x: CharacterSwapMap,
             pub y: CharacterSwapMap,
         },
+        #[serde(default)]
+        pub character_rotations: CharacterRotationMap,
         pub line_drawing_characters: LineDrawingCharacters,
+        #[serde(default)]
+        pub irc_palette: IrcPalette,
+        #[serde(default)]
+        pub export_color_depth: ColorDepth,
+        #[serde(default)]
+        pub export_dithering: Dithering,
+        #[serde(default)]
+        pub clipboard_ansi: bool,
+        #[serde(default)]
+        pub undo_file: bool,
+        #[serde(default)]
+        pub commit_merging: CommitMerging,
         pub autoreload_config: bool,
         pub message_popup_suppress_keystroke: bool,
         pub input_mode: HashMap<InputMode, ConfigInputMode>,
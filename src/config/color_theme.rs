@@ -31,6 +31,8 @@ nest! {
             },
             pub visual_mode_highlight_bg: ColorToml,
             pub selection_highlight_bg: ColorToml,
+            #[serde(default = "default_changed_highlight_bg")]
+            pub changed_highlight_bg: ColorToml,
         },
         pub row_numbers: StyleConfig,
         pub column_numbers: StyleConfig,
@@ -41,6 +43,11 @@ nest! {
     }
 }
 
+/// Dark green, which most artworks don't use as background.
+fn default_changed_highlight_bg() -> ColorToml {
+    ColorToml(Color::Indexed(22))
+}
+
 impl ColorThemeCanvas {
     pub fn apply_to_color(&self, color: Color, ground: Ground) -> Color {
         let color_theme = self;
@@ -80,6 +87,17 @@ impl ColorThemeCanvas {
             _ => color,
         }
     }
+    /// Resolves `color` to an RGB triplet through the color theme, for exporting to formats
+    /// without a terminal palette. Standard colors the theme doesn't give as RGB, and indexed
+    /// colors above 15, use the xterm default palette. Returns `None` for `Color::Reset` with no
+    /// default color in the theme.
+    pub fn resolve_rgb(&self, color: Color, ground: Ground) -> Option<(u8, u8, u8)> {
+        match self.apply_to_color(color, ground) {
+            Color::Reset => None,
+            Color::Rgb(r, g, b) => Some((r, g, b)),
+            color => palette_index(color).map(xterm_rgb),
+        }
+    }
     pub fn apply_to_style(&self, style: Style) -> Style {
         let color_theme = self;
         style
@@ -88,6 +106,65 @@ impl ColorThemeCanvas {
     }
 }
 
+/// Returns the index of `color` in the 256 color palette, or `None` for `Color::Reset` and
+/// `Color::Rgb`.
+pub fn palette_index(color: Color) -> Option<u8> {
+    Some(match color {
+        Color::Reset | Color::Rgb(..) => return None,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(index) => index,
+    })
+}
+
+/// Returns the RGB triplet of palette color `index` in the xterm default palette.
+pub fn xterm_rgb(index: u8) -> (u8, u8, u8) {
+    const STANDARD_COLORS: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+    match index {
+        0..=15 => STANDARD_COLORS[index as usize],
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = index - 16;
+            (level(index / 36), level((index / 6) % 6), level(index % 6))
+        }
+        232..=255 => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
 pub fn apply_color_theme_to_color(
     color: Color,
     color_theme: &ColorThemeCanvas,
@@ -122,6 +199,11 @@ impl From<ColorToml> for Color {
         value.to_ratatui_color()
     }
 }
+impl From<Color> for ColorToml {
+    fn from(value: Color) -> Self {
+        ColorToml(value)
+    }
+}
 impl TryFrom<StringOrU8> for ColorToml {
     type Error = String;
     fn try_from(value: StringOrU8) -> Result<Self, Self::Error> {
@@ -4,14 +4,18 @@ use crate::actions::ActionRepeatableEnum;
 use crate::canvas::raw::continuous_region::ContinuousRegionRelativeType;
 use crate::canvas::raw::iter::CanvasIterationJump;
 use crate::canvas::raw::iter::WordBoundaryType;
+use crate::canvas::raw::transform::Scale;
 use crate::canvas::raw::CellContentType;
+use crate::canvas::raw::Grapheme;
 use crate::color_picker::target::ColorPickerTargetEnum;
 use crate::color_picker::target::ColorPickerTargetMotion;
 use crate::keystrokes::ColorOrSlot;
 use crate::keystrokes::ColorOrSlotSpecification;
+use crate::motions::ContinuousRegionBound;
 use crate::motions::MotionEnum;
 use crate::motions::MotionRepeatEnum;
 use crate::operators::OperatorEnum;
+use crate::operators::TransformCenter;
 use crate::operators::UpdateSelectionOperator;
 use crate::selections::Selection;
 use crate::selections::SelectionSlotSpecification;
@@ -30,6 +34,7 @@ use keystrokes_parsing::FromKeystrokes;
 use keystrokes_parsing::FromKeystrokesBy;
 use keystrokes_parsing::FromKeystrokesError;
 use keystrokes_parsing::Keymap;
+use keystrokes_parsing::Keystroke;
 use keystrokes_parsing::KeystrokeIterator;
 use keystrokes_parsing::Presetable;
 use ratatui::style::Color;
@@ -91,6 +96,7 @@ keymaps! {
     selection_specifications: SelectionSpecification,
     cell_content_types: CellContentType,
     continuous_region_relative_types: ContinuousRegionRelativeType,
+    continuous_region_bounds: ContinuousRegionBound,
     canvas_iteration_jumps: CanvasIterationJump,
     word_boundary_types: WordBoundaryType,
     color_or_slots: ColorOrSlot,
@@ -101,8 +107,10 @@ keymaps! {
     grounds: Ground,
     axes: Axis,
     rotation_directions: RotationDirection,
+    scales: Scale,
     yank_slot_specifications: YankSlotSpecification,
     update_selection_operators: UpdateSelectionOperator,
+    transform_centers: TransformCenter,
     actions: ActionBatch,
     actions_repeatable: ActionRepeatableEnum,
     selections: Selection,
@@ -133,6 +141,8 @@ impl_presetable_by_self!(RotationDirection);
 impl_presetable_by_self!(Color);
 impl_presetable_by_self!(UpdateSelectionOperator);
 impl_presetable_by_self!(Selection);
+impl_presetable_by_self!(ContinuousRegionBound);
+impl_presetable_by_self!(TransformCenter);
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub enum CharKeymapEntry {
@@ -175,6 +185,49 @@ impl Presetable<ProgramState> for char {
     }
 }
 
+/// Adds the characters typed after `character` which continue its grapheme cluster, like
+/// combining accents, to it.
+fn grapheme_from_keystrokes(character: char, keystrokes: &mut KeystrokeIterator) -> Grapheme {
+    let mut grapheme = Grapheme::from(character);
+    while let Some(Keystroke {
+        code: KeyCode::Char(ch),
+        modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
+    }) = keystrokes.peek()
+    {
+        if !grapheme.continued_by(*ch) {
+            break;
+        }
+        grapheme.push(*ch);
+        keystrokes.next();
+    }
+    grapheme
+}
+impl Presetable<ProgramState> for Grapheme {
+    type Preset = CharKeymapEntry;
+    fn from_keystrokes_by_preset(
+        preset: Self::Preset,
+        keystrokes: &mut KeystrokeIterator,
+        config: &ProgramState,
+    ) -> Result<Self, keystrokes_parsing::FromKeystrokesError> {
+        match preset {
+            CharKeymapEntry::Char(value) => Ok(value.into()),
+            CharKeymapEntry::Type => {
+                let character = char::from_keystrokes_by_preset(preset, keystrokes, config)?;
+                Ok(grapheme_from_keystrokes(character, keystrokes))
+            }
+        }
+    }
+}
+impl FromKeystrokes<ProgramState> for Grapheme {
+    fn from_keystrokes(
+        keystrokes: &mut KeystrokeIterator,
+        config: &ProgramState,
+    ) -> Result<Self, FromKeystrokesError> {
+        let character = char::from_keystrokes(keystrokes, config)?;
+        Ok(grapheme_from_keystrokes(character, keystrokes))
+    }
+}
+
 // impl FromKeystrokes for u16 {
 //     fn from_keystrokes(
 //         keystrokes: &mut KeystrokeIterator,
@@ -1,10 +1,27 @@
 use std::path::Path;
 
-#[derive(Debug, Default)]
+pub mod cp437;
+pub mod image;
+pub mod irc;
+pub mod sauce;
+pub mod undo;
+pub mod upaint;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub enum FileFormat {
     #[default]
     Ansi,
     Txt,
+    /// HTML page fragment, with colors resolved through the color theme.
+    Html,
+    /// SVG image, with colors resolved through the color theme.
+    Svg,
+    /// PNG image, rasterized with a built-in bitmap font.
+    Png,
+    /// Text with mIRC formatting codes, as used on IRC.
+    Irc,
+    /// Native project format, storing the canvas together with the workspace state.
+    Upaint,
 }
 
 impl FileFormat {
@@ -14,6 +31,12 @@ impl FileFormat {
                 "ansi" => Ok(FileFormat::Ansi),
                 "ans" => Ok(FileFormat::Ansi),
                 "txt" => Ok(FileFormat::Txt),
+                "html" => Ok(FileFormat::Html),
+                "htm" => Ok(FileFormat::Html),
+                "svg" => Ok(FileFormat::Svg),
+                "png" => Ok(FileFormat::Png),
+                "irc" => Ok(FileFormat::Irc),
+                "upaint" => Ok(FileFormat::Upaint),
                 _ => Err(()),
             },
             None => Ok(FileFormat::default()),
@@ -0,0 +1,73 @@
+//! Code page 437, the character set of IBM PC compatibles, used by BBS-era ANSI art.
+
+/// Characters of all 256 bytes, using the graphical glyphs for the control range, like the
+/// IBM PC did when writing directly to video memory.
+const CP437: [char; 256] = [
+    // 0x00
+    ' ', '☺', '☻', '♥', '♦', '♣', '♠', '•', '◘', '○', '◙', '♂', '♀', '♪', '♫', '☼',
+    // 0x10
+    '►', '◄', '↕', '‼', '¶', '§', '▬', '↨', '↑', '↓', '→', '←', '∟', '↔', '▲', '▼',
+    // 0x20
+    ' ', '!', '"', '#', '$', '%', '&', '\'', '(', ')', '*', '+', ',', '-', '.', '/',
+    // 0x30
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', ':', ';', '<', '=', '>', '?',
+    // 0x40
+    '@', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O',
+    // 0x50
+    'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '[', '\\', ']', '^', '_',
+    // 0x60
+    '`', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o',
+    // 0x70
+    'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '{', '|', '}', '~', '⌂',
+    // 0x80
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å',
+    // 0x90
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ',
+    // 0xa0
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»',
+    // 0xb0
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐',
+    // 0xc0
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧',
+    // 0xd0
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀',
+    // 0xe0
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩',
+    // 0xf0
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/// Returns the character of `byte`, using the graphical glyph also for control bytes.
+pub fn decode(byte: u8) -> char {
+    CP437[byte as usize]
+}
+
+/// Decodes text stored in CP437, where control bytes have their ASCII meaning.
+pub fn decode_text(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&byte| {
+            if byte < 0x20 {
+                byte as char
+            } else {
+                decode(byte)
+            }
+        })
+        .collect()
+}
+
+/// Returns the byte of `character`, or `None` if it isn't in the code page.
+pub fn encode(character: char) -> Option<u8> {
+    if character.is_ascii() && !character.is_ascii_control() {
+        return Some(character as u8);
+    }
+    // Byte 0x00 is skipped, as it shares its glyph with the space
+    (1..=255u8).find(|&byte| decode(byte) == character)
+}
+
+/// Encodes text as CP437, replacing characters missing from the code page with `?`.
+pub fn encode_text(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|character| encode(character).unwrap_or(b'?'))
+        .collect()
+}
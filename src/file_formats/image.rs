@@ -0,0 +1,175 @@
+//! Decoding of raster images, which can be imported as half-block pixel art.
+
+#[cfg(test)]
+mod test;
+
+/// An RGBA pixel.
+pub type Pixel = [u8; 4];
+
+/// A decoded image, with pixels stored row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pixels: Vec<Pixel>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, pixels: Vec<Pixel>) -> Self {
+        assert_eq!(pixels.len(), (width * height) as usize);
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Pixel {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// Decodes a PNG or PPM image, detected by its signature.
+    pub fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        if bytes.starts_with(b"\x89PNG") {
+            Self::from_png(bytes)
+        } else if bytes.starts_with(b"P3") || bytes.starts_with(b"P6") {
+            Self::from_ppm(bytes)
+        } else {
+            anyhow::bail!("Image format not recognized, expected PNG or PPM")
+        }
+    }
+
+    pub fn from_png(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut decoder = png::Decoder::new(bytes);
+        // Palettes, low bit depths and 16 bit channels are all converted to 8 bit channels
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        let buffer = &buffer[..info.buffer_size()];
+        let pixels = match info.color_type {
+            png::ColorType::Grayscale => buffer.iter().map(|&v| [v, v, v, 255]).collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks_exact(2)
+                .map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Rgb => buffer
+                .chunks_exact(3)
+                .map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::Rgba => buffer
+                .chunks_exact(4)
+                .map(|p| [p[0], p[1], p[2], p[3]])
+                .collect(),
+            png::ColorType::Indexed => {
+                unreachable!("indexed colors are expanded by the decoder")
+            }
+        };
+        Ok(Self::new(info.width, info.height, pixels))
+    }
+
+    /// Decodes a PPM image, either in the binary (`P6`) or the plain (`P3`) variant.
+    pub fn from_ppm(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut position = 0;
+        let magic = ppm_token(bytes, &mut position)?;
+        let width = ppm_number(bytes, &mut position)?;
+        let height = ppm_number(bytes, &mut position)?;
+        let max_value = ppm_number(bytes, &mut position)?;
+        if max_value == 0 || max_value > u16::MAX as u32 {
+            anyhow::bail!("PPM image has an invalid maximum value {max_value}");
+        }
+        let samples = width as usize * height as usize * 3;
+        let values: Vec<u32> = match magic {
+            b"P3" => (0..samples)
+                .map(|_| ppm_number(bytes, &mut position))
+                .collect::<anyhow::Result<_>>()?,
+            b"P6" => {
+                // A single whitespace character separates the header from the raster
+                position += 1;
+                let bytes_per_sample = if max_value < 256 { 1 } else { 2 };
+                let raster = bytes
+                    .get(position..position + samples * bytes_per_sample)
+                    .ok_or_else(|| anyhow::anyhow!("PPM image is truncated"))?;
+                raster
+                    .chunks_exact(bytes_per_sample)
+                    .map(|sample| {
+                        sample
+                            .iter()
+                            .fold(0, |value, &byte| value << 8 | byte as u32)
+                    })
+                    .collect()
+            }
+            _ => anyhow::bail!("Unsupported PPM variant, expected P3 or P6"),
+        };
+        let scale = |value: u32| (value.min(max_value) * 255 / max_value) as u8;
+        let pixels = values
+            .chunks_exact(3)
+            .map(|rgb| [scale(rgb[0]), scale(rgb[1]), scale(rgb[2]), 255])
+            .collect();
+        Ok(Self::new(width, height, pixels))
+    }
+
+    /// Scales the image to `width` x `height` pixels, averaging the pixels covered by each new
+    /// pixel. Colors are weighted by their alpha, so transparent pixels don't darken the result.
+    pub fn resize(&self, width: u32, height: u32) -> Self {
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            let (y_start, y_end) = source_range(y, height, self.height);
+            for x in 0..width {
+                let (x_start, x_end) = source_range(x, width, self.width);
+                let mut sum = [0u64; 4];
+                for source_y in y_start..y_end {
+                    for source_x in x_start..x_end {
+                        let pixel = self.pixel(source_x, source_y);
+                        let alpha = pixel[3] as u64;
+                        for i in 0..3 {
+                            sum[i] += pixel[i] as u64 * alpha;
+                        }
+                        sum[3] += alpha;
+                    }
+                }
+                let count = ((x_end - x_start) * (y_end - y_start)) as u64;
+                let average = |i: usize| sum[i].checked_div(sum[3]).unwrap_or(0) as u8;
+                pixels.push([average(0), average(1), average(2), (sum[3] / count) as u8]);
+            }
+        }
+        Self::new(width, height, pixels)
+    }
+}
+
+/// Returns the range of source pixels covered by pixel `index` when scaling `source_size` pixels
+/// to `size` pixels. The range contains at least one pixel.
+fn source_range(index: u32, size: u32, source_size: u32) -> (u32, u32) {
+    let start = (index as u64 * source_size as u64 / size as u64) as u32;
+    let end = ((index as u64 + 1) * source_size as u64 / size as u64) as u32;
+    (start, end.max(start + 1).min(source_size))
+}
+
+/// Reads the next whitespace separated token of a PPM header, skipping comments.
+fn ppm_token<'a>(bytes: &'a [u8], position: &mut usize) -> anyhow::Result<&'a [u8]> {
+    loop {
+        match bytes.get(*position) {
+            Some(b'#') => {
+                while !matches!(bytes.get(*position), Some(b'\n') | None) {
+                    *position += 1;
+                }
+            }
+            Some(byte) if byte.is_ascii_whitespace() => *position += 1,
+            Some(_) => break,
+            None => anyhow::bail!("PPM image is truncated"),
+        }
+    }
+    let start = *position;
+    while matches!(bytes.get(*position), Some(byte) if !byte.is_ascii_whitespace()) {
+        *position += 1;
+    }
+    Ok(&bytes[start..*position])
+}
+
+fn ppm_number(bytes: &[u8], position: &mut usize) -> anyhow::Result<u32> {
+    let token = ppm_token(bytes, position)?;
+    std::str::from_utf8(token)
+        .ok()
+        .and_then(|token| token.parse().ok())
+        .ok_or_else(|| anyhow::anyhow!("PPM image contains an invalid number"))
+}
@@ -0,0 +1,68 @@
+use crate::file_formats::image::Image;
+
+#[test]
+fn ppm_plain() {
+    let ppm = b"P3\n# A comment\n2 1\n15\n15 0 0  0 15 15\n";
+    let image = Image::decode(ppm).unwrap();
+
+    assert_eq!((image.width, image.height), (2, 1));
+    assert_eq!(image.pixel(0, 0), [255, 0, 0, 255]);
+    assert_eq!(image.pixel(1, 0), [0, 255, 255, 255]);
+}
+
+#[test]
+fn ppm_binary() {
+    let mut ppm = b"P6 1 2 255\n".to_vec();
+    ppm.extend([1, 2, 3, 4, 5, 6]);
+    let image = Image::decode(&ppm).unwrap();
+
+    assert_eq!((image.width, image.height), (1, 2));
+    assert_eq!(image.pixel(0, 0), [1, 2, 3, 255]);
+    assert_eq!(image.pixel(0, 1), [4, 5, 6, 255]);
+}
+
+#[test]
+fn ppm_truncated() {
+    assert!(Image::decode(b"P6 2 2 255\n\x00\x00\x00").is_err());
+    assert!(Image::decode(b"P3 2 2 255\n0 0 0").is_err());
+}
+
+#[test]
+fn png() {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, 2, 1);
+    encoder.set_color(png::ColorType::GrayscaleAlpha);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&[10, 255, 20, 0]).unwrap();
+    writer.finish().unwrap();
+    let image = Image::decode(&png).unwrap();
+
+    assert_eq!((image.width, image.height), (2, 1));
+    assert_eq!(image.pixel(0, 0), [10, 10, 10, 255]);
+    assert_eq!(image.pixel(1, 0), [20, 20, 20, 0]);
+}
+
+#[test]
+fn unknown_format() {
+    assert!(Image::decode(b"GIF89a").is_err());
+}
+
+#[test]
+fn resize() {
+    let image = Image::new(
+        2,
+        2,
+        vec![
+            [0, 0, 0, 255],
+            [100, 100, 100, 255],
+            [200, 0, 0, 255],
+            // Transparent pixels don't affect the color
+            [0, 0, 255, 0],
+        ],
+    );
+    let resized = image.resize(1, 1);
+
+    assert_eq!(resized.pixel(0, 0), [100, 33, 33, 191]);
+    assert_eq!(image.resize(4, 4).pixel(3, 0), [100, 100, 100, 255]);
+}
@@ -0,0 +1,221 @@
+//! Formatting control codes of IRC messages, as introduced by mIRC and understood by most IRC
+//! clients and bridges. See <https://modern.ircdocs.horse/formatting.html>.
+
+use ratatui::style::{Color, Modifier};
+use serde::Deserialize;
+
+use crate::config::color_theme::{palette_index, xterm_rgb};
+
+pub const IRC_BOLD: char = '\u{02}';
+/// Followed by up to two digits for the foreground color, and optionally a comma and up to two
+/// digits for the background color. Resets the colors when not followed by digits.
+pub const IRC_COLOR: char = '\u{03}';
+/// Like `IRC_COLOR`, but with colors given as six hexadecimal digits.
+pub const IRC_HEX_COLOR: char = '\u{04}';
+pub const IRC_RESET: char = '\u{0f}';
+pub const IRC_MONOSPACE: char = '\u{11}';
+pub const IRC_REVERSED: char = '\u{16}';
+pub const IRC_ITALIC: char = '\u{1d}';
+pub const IRC_STRIKETHROUGH: char = '\u{1e}';
+pub const IRC_UNDERLINE: char = '\u{1f}';
+
+/// Modifiers that have a formatting code, which toggles them.
+pub const IRC_MODIFIERS: [(Modifier, char); 5] = [
+    (Modifier::BOLD, IRC_BOLD),
+    (Modifier::ITALIC, IRC_ITALIC),
+    (Modifier::UNDERLINED, IRC_UNDERLINE),
+    (Modifier::CROSSED_OUT, IRC_STRIKETHROUGH),
+    (Modifier::REVERSED, IRC_REVERSED),
+];
+
+/// Color code meaning the default color of the client.
+pub const IRC_DEFAULT_COLOR: u8 = 99;
+
+/// RGB values of the color codes below `IRC_DEFAULT_COLOR`, as defined by mIRC.
+pub const IRC_COLORS: [(u8, u8, u8); 99] = [
+    // 0-15, the colors supported by all clients
+    (0xff, 0xff, 0xff),
+    (0x00, 0x00, 0x00),
+    (0x00, 0x00, 0x7f),
+    (0x00, 0x93, 0x00),
+    (0xff, 0x00, 0x00),
+    (0x7f, 0x00, 0x00),
+    (0x9c, 0x00, 0x9c),
+    (0xfc, 0x7f, 0x00),
+    (0xff, 0xff, 0x00),
+    (0x00, 0xfc, 0x00),
+    (0x00, 0x93, 0x93),
+    (0x00, 0xff, 0xff),
+    (0x00, 0x00, 0xfc),
+    (0xff, 0x00, 0xff),
+    (0x7f, 0x7f, 0x7f),
+    (0xd2, 0xd2, 0xd2),
+    // 16-87, 12 hues in 6 shades
+    (0x47, 0x00, 0x00),
+    (0x47, 0x21, 0x00),
+    (0x47, 0x47, 0x00),
+    (0x32, 0x47, 0x00),
+    (0x00, 0x47, 0x00),
+    (0x00, 0x47, 0x2c),
+    (0x00, 0x47, 0x47),
+    (0x00, 0x27, 0x47),
+    (0x00, 0x00, 0x47),
+    (0x2e, 0x00, 0x47),
+    (0x47, 0x00, 0x47),
+    (0x47, 0x00, 0x2a),
+    (0x74, 0x00, 0x00),
+    (0x74, 0x3a, 0x00),
+    (0x74, 0x74, 0x00),
+    (0x51, 0x74, 0x00),
+    (0x00, 0x74, 0x00),
+    (0x00, 0x74, 0x49),
+    (0x00, 0x74, 0x74),
+    (0x00, 0x40, 0x74),
+    (0x00, 0x00, 0x74),
+    (0x4b, 0x00, 0x74),
+    (0x74, 0x00, 0x74),
+    (0x74, 0x00, 0x45),
+    (0xb5, 0x00, 0x00),
+    (0xb5, 0x63, 0x00),
+    (0xb5, 0xb5, 0x00),
+    (0x7d, 0xb5, 0x00),
+    (0x00, 0xb5, 0x00),
+    (0x00, 0xb5, 0x71),
+    (0x00, 0xb5, 0xb5),
+    (0x00, 0x63, 0xb5),
+    (0x00, 0x00, 0xb5),
+    (0x75, 0x00, 0xb5),
+    (0xb5, 0x00, 0xb5),
+    (0xb5, 0x00, 0x6b),
+    (0xff, 0x00, 0x00),
+    (0xff, 0x8c, 0x00),
+    (0xff, 0xff, 0x00),
+    (0xb2, 0xff, 0x00),
+    (0x00, 0xff, 0x00),
+    (0x00, 0xff, 0xa0),
+    (0x00, 0xff, 0xff),
+    (0x00, 0x8c, 0xff),
+    (0x00, 0x00, 0xff),
+    (0xa5, 0x00, 0xff),
+    (0xff, 0x00, 0xff),
+    (0xff, 0x00, 0x98),
+    (0xff, 0x59, 0x59),
+    (0xff, 0xb4, 0x59),
+    (0xff, 0xff, 0x71),
+    (0xcf, 0xff, 0x60),
+    (0x6f, 0xff, 0x6f),
+    (0x65, 0xff, 0xc9),
+    (0x6d, 0xff, 0xff),
+    (0x59, 0xb4, 0xff),
+    (0x59, 0x59, 0xff),
+    (0xc4, 0x59, 0xff),
+    (0xff, 0x66, 0xff),
+    (0xff, 0x59, 0xbc),
+    (0xff, 0x9c, 0x9c),
+    (0xff, 0xd3, 0x9c),
+    (0xff, 0xff, 0x9c),
+    (0xe2, 0xff, 0x9c),
+    (0x9c, 0xff, 0x9c),
+    (0x9c, 0xff, 0xdb),
+    (0x9c, 0xff, 0xff),
+    (0x9c, 0xd3, 0xff),
+    (0x9c, 0x9c, 0xff),
+    (0xdc, 0x9c, 0xff),
+    (0xff, 0x9c, 0xff),
+    (0xff, 0x94, 0xd3),
+    // 88-98, grays
+    (0x00, 0x00, 0x00),
+    (0x13, 0x13, 0x13),
+    (0x28, 0x28, 0x28),
+    (0x36, 0x36, 0x36),
+    (0x4d, 0x4d, 0x4d),
+    (0x65, 0x65, 0x65),
+    (0x81, 0x81, 0x81),
+    (0x9f, 0x9f, 0x9f),
+    (0xbc, 0xbc, 0xbc),
+    (0xe2, 0xe2, 0xe2),
+    (0xff, 0xff, 0xff),
+];
+
+/// Named colors of the color codes 0-15.
+const IRC_NAMED_COLORS: [Color; 16] = [
+    Color::White,
+    Color::Black,
+    Color::Blue,
+    Color::Green,
+    Color::LightRed,
+    Color::Red,
+    Color::Magenta,
+    Color::Yellow,
+    Color::LightYellow,
+    Color::LightGreen,
+    Color::Cyan,
+    Color::LightCyan,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::DarkGray,
+    Color::Gray,
+];
+
+/// The color codes used when exporting to IRC.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IrcPalette {
+    /// Color codes 0-15, supported by all clients.
+    Basic,
+    /// Color codes 0-98, supported by most modern clients.
+    #[default]
+    Extended,
+}
+
+impl IrcPalette {
+    fn colors(self) -> usize {
+        match self {
+            Self::Basic => 16,
+            Self::Extended => 99,
+        }
+    }
+}
+
+/// Returns the color of color `code`.
+pub fn color_from_code(code: u8) -> Color {
+    match code {
+        0..=15 => IRC_NAMED_COLORS[code as usize],
+        16..=98 => {
+            let (r, g, b) = IRC_COLORS[code as usize];
+            Color::Rgb(r, g, b)
+        }
+        _ => Color::Reset,
+    }
+}
+
+/// Returns the color code in `palette` closest to `color`, and whether it is an exact match.
+///
+/// Standard colors map to the codes 0-15, other colors to the code with the nearest RGB value,
+/// preferring the codes above 15 for exact matches, as they are imported as RGB colors again.
+pub fn code_from_color(color: Color, palette: IrcPalette) -> (u8, bool) {
+    let (r, g, b) = match (color, palette_index(color)) {
+        (Color::Reset, _) => return (IRC_DEFAULT_COLOR, true),
+        (_, Some(index @ 0..=15)) => {
+            let code = IRC_NAMED_COLORS
+                .iter()
+                .position(|named| palette_index(*named) == Some(index))
+                .unwrap();
+            return (code as u8, true);
+        }
+        (_, Some(index)) => xterm_rgb(index),
+        (Color::Rgb(r, g, b), None) => (r, g, b),
+        (_, None) => unreachable!("only Reset and Rgb have no palette index"),
+    };
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        let square = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        square(r, r2) + square(g, g2) + square(b, b2)
+    };
+    let (code, rgb) = IRC_COLORS[..palette.colors()]
+        .iter()
+        .enumerate()
+        .rev()
+        .min_by_key(|(_code, rgb)| distance(**rgb))
+        .unwrap();
+    (code as u8, *rgb == (r, g, b))
+}
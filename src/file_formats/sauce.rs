@@ -0,0 +1,215 @@
+//! SAUCE (Standard Architecture for Universal Comment Extensions), the metadata record appended
+//! to BBS-era art files. See <https://www.acid.org/info/sauce/sauce.htm>.
+
+use super::cp437;
+use crate::canvas::raw::CanvasMetadata;
+
+#[cfg(test)]
+pub(crate) mod test;
+
+pub const SAUCE_RECORD_SIZE: usize = 128;
+pub const SAUCE_COMMENT_LINE_SIZE: usize = 64;
+/// The DOS end-of-file character, which separates the file contents from the SAUCE record.
+pub const EOF_CHARACTER: u8 = 0x1a;
+/// Maximum number of comment lines, as the count is stored in a single byte.
+pub const SAUCE_MAX_COMMENT_LINES: usize = 255;
+
+/// `DataType` of text based files.
+pub const SAUCE_DATA_TYPE_CHARACTER: u8 = 1;
+/// `FileType` of ANSI files, for `SAUCE_DATA_TYPE_CHARACTER`.
+pub const SAUCE_FILE_TYPE_ANSI: u8 = 1;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Sauce {
+    pub title: String,
+    pub author: String,
+    pub group: String,
+    /// Creation date on the format `CCYYMMDD`.
+    pub date: String,
+    /// Size of the file contents, excluding the EOF character and the SAUCE record.
+    pub file_size: u32,
+    pub data_type: u8,
+    pub file_type: u8,
+    pub t_info: [u16; 4],
+    pub t_flags: u8,
+    /// Font name, for example `IBM VGA`.
+    pub t_info_s: String,
+    pub comments: Vec<String>,
+}
+
+fn field(bytes: &[u8]) -> String {
+    let end = bytes
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(bytes.len());
+    cp437::decode_text(&bytes[..end]).trim_end().to_string()
+}
+
+/// Encodes `text` as a field of `size` bytes, truncated or padded with `padding`.
+fn encode_field(text: &str, size: usize, padding: u8) -> Vec<u8> {
+    let mut bytes = cp437::encode_text(text);
+    bytes.resize(size, padding);
+    bytes
+}
+
+/// Returns today's date on the `CCYYMMDD` format used by SAUCE, in UTC.
+pub fn today() -> String {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    // Converts days since 1970-01-01 to a civil date, see
+    // <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_shifted = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_shifted + 2) / 5 + 1;
+    let month = if month_shifted < 10 {
+        month_shifted + 3
+    } else {
+        month_shifted - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{year:04}{month:02}{day:02}")
+}
+
+impl From<Sauce> for CanvasMetadata {
+    fn from(sauce: Sauce) -> Self {
+        Self {
+            title: sauce.title,
+            author: sauce.author,
+            group: sauce.group,
+            comments: sauce.comments,
+            font: sauce.t_info_s,
+        }
+    }
+}
+
+impl Sauce {
+    /// Splits `bytes` into the file contents and the SAUCE record, if there is one. The contents
+    /// end at the first EOF character, and exclude the SAUCE record and its comment block.
+    pub fn split(bytes: &[u8]) -> (&[u8], Option<Sauce>) {
+        let sauce = Self::parse(bytes);
+        let mut contents_end = bytes.len();
+        if let Some(sauce) = &sauce {
+            contents_end -= SAUCE_RECORD_SIZE;
+            if !sauce.comments.is_empty() {
+                contents_end -= 5 + SAUCE_COMMENT_LINE_SIZE * sauce.comments.len();
+            }
+        }
+        let contents = &bytes[..contents_end];
+        let contents = match contents.iter().position(|&byte| byte == EOF_CHARACTER) {
+            Some(eof) => &contents[..eof],
+            None => contents,
+        };
+        (contents, sauce)
+    }
+
+    /// Creates a record describing an ANSI file of `file_size` bytes, showing a canvas of
+    /// `columns` x `rows` characters, created on `date`.
+    pub fn ansi(
+        metadata: &CanvasMetadata,
+        columns: u16,
+        rows: u16,
+        file_size: u32,
+        date: String,
+    ) -> Self {
+        Self {
+            title: metadata.title.clone(),
+            author: metadata.author.clone(),
+            group: metadata.group.clone(),
+            date,
+            file_size,
+            data_type: SAUCE_DATA_TYPE_CHARACTER,
+            file_type: SAUCE_FILE_TYPE_ANSI,
+            t_info: [columns, rows, 0, 0],
+            t_flags: 0,
+            t_info_s: metadata.font.clone(),
+            comments: metadata.comments.clone(),
+        }
+    }
+
+    /// Adapts the record to `contents` encoded as UTF-8, which SAUCE has no way to tell apart
+    /// from CP437. Unless the contents are plain ASCII, which reads the same in both, the font
+    /// is left out, as claiming a DOS font like `IBM VGA` makes viewers show the contents as
+    /// CP437.
+    pub fn for_utf8(mut self, contents: &[u8]) -> Self {
+        if !contents.is_ascii() {
+            self.t_info_s.clear();
+        }
+        self
+    }
+
+    /// Appends the EOF character, the comment block and the SAUCE record to `contents`.
+    ///
+    /// Text is encoded as CP437, with characters missing from it replaced by `?`, and fields
+    /// longer than the space SAUCE has for them are truncated. At most
+    /// `SAUCE_MAX_COMMENT_LINES` comment lines are stored.
+    pub fn append_to(&self, contents: &mut Vec<u8>) {
+        contents.push(EOF_CHARACTER);
+        let comments = &self.comments[..self.comments.len().min(SAUCE_MAX_COMMENT_LINES)];
+        if !comments.is_empty() {
+            contents.extend_from_slice(b"COMNT");
+            for comment in comments {
+                contents.extend(encode_field(comment, SAUCE_COMMENT_LINE_SIZE, b' '));
+            }
+        }
+        contents.extend_from_slice(b"SAUCE00");
+        contents.extend(encode_field(&self.title, 35, b' '));
+        contents.extend(encode_field(&self.author, 20, b' '));
+        contents.extend(encode_field(&self.group, 20, b' '));
+        contents.extend(encode_field(&self.date, 8, b' '));
+        contents.extend_from_slice(&self.file_size.to_le_bytes());
+        contents.push(self.data_type);
+        contents.push(self.file_type);
+        for t_info in self.t_info {
+            contents.extend_from_slice(&t_info.to_le_bytes());
+        }
+        contents.push(comments.len() as u8);
+        contents.push(self.t_flags);
+        contents.extend(encode_field(&self.t_info_s, 22, 0));
+    }
+
+    /// Parses the SAUCE record at the end of `bytes`, if there is one.
+    pub fn parse(bytes: &[u8]) -> Option<Sauce> {
+        let record_start = bytes.len().checked_sub(SAUCE_RECORD_SIZE)?;
+        let record = &bytes[record_start..];
+        if &record[0..5] != b"SAUCE" {
+            return None;
+        }
+        let u16_at = |offset: usize| u16::from_le_bytes([record[offset], record[offset + 1]]);
+        let mut sauce = Sauce {
+            title: field(&record[7..42]),
+            author: field(&record[42..62]),
+            group: field(&record[62..82]),
+            date: field(&record[82..90]),
+            file_size: u32::from_le_bytes(record[90..94].try_into().unwrap()),
+            data_type: record[94],
+            file_type: record[95],
+            t_info: [u16_at(96), u16_at(98), u16_at(100), u16_at(102)],
+            t_flags: record[105],
+            t_info_s: field(&record[106..128]),
+            comments: Vec::new(),
+        };
+
+        // The comment block is ignored if it can't be found where the record says it is
+        let comment_lines = record[104] as usize;
+        let comments_size = 5 + SAUCE_COMMENT_LINE_SIZE * comment_lines;
+        if comment_lines > 0 {
+            if let Some(comments_start) = record_start.checked_sub(comments_size) {
+                let comments = &bytes[comments_start..record_start];
+                if &comments[0..5] == b"COMNT" {
+                    sauce.comments = comments[5..]
+                        .chunks(SAUCE_COMMENT_LINE_SIZE)
+                        .map(field)
+                        .collect();
+                }
+            }
+        }
+        Some(sauce)
+    }
+}
@@ -0,0 +1,147 @@
+use crate::canvas::raw::CanvasMetadata;
+use crate::file_formats::sauce::{self, Sauce, SAUCE_COMMENT_LINE_SIZE, SAUCE_RECORD_SIZE};
+
+/// Builds a SAUCE record the way it is laid out on disk.
+pub fn sauce_record(title: &str, author: &str, group: &str, comments: &[&str]) -> Vec<u8> {
+    fn padded(text: &str, size: usize) -> Vec<u8> {
+        let mut bytes = text.as_bytes().to_vec();
+        bytes.resize(size, b' ');
+        bytes
+    }
+    let mut record = Vec::new();
+    if !comments.is_empty() {
+        record.extend_from_slice(b"COMNT");
+        for comment in comments {
+            record.extend(padded(comment, 64));
+        }
+    }
+    record.extend_from_slice(b"SAUCE00");
+    record.extend(padded(title, 35));
+    record.extend(padded(author, 20));
+    record.extend(padded(group, 20));
+    record.extend_from_slice(b"19960101");
+    record.extend_from_slice(&[0; 4]);
+    record.extend_from_slice(&[1, 1]);
+    record.extend_from_slice(&[80, 0, 25, 0, 0, 0, 0, 0]);
+    record.push(comments.len() as u8);
+    record.push(0);
+    let mut font = b"IBM VGA".to_vec();
+    font.resize(22, 0);
+    record.extend(font);
+    record
+}
+
+#[test]
+fn no_sauce() {
+    let bytes = b"abc";
+    assert_eq!(Sauce::split(bytes), (&bytes[..], None));
+}
+
+#[test]
+fn eof_without_sauce() {
+    let bytes = b"abc\x1agarbage";
+    assert_eq!(Sauce::split(bytes), (&b"abc"[..], None));
+}
+
+#[test]
+fn split() {
+    let mut bytes = b"abc\x1a".to_vec();
+    bytes.extend(sauce_record("Title", "Author", "Group", &[]));
+    assert_eq!(bytes.len(), 4 + SAUCE_RECORD_SIZE);
+
+    let (contents, sauce) = Sauce::split(&bytes);
+    let sauce = sauce.unwrap();
+
+    assert_eq!(contents, b"abc");
+    assert_eq!(sauce.title, "Title");
+    assert_eq!(sauce.author, "Author");
+    assert_eq!(sauce.group, "Group");
+    assert_eq!(sauce.date, "19960101");
+    assert_eq!(sauce.data_type, 1);
+    assert_eq!(sauce.file_type, 1);
+    assert_eq!(sauce.t_info, [80, 25, 0, 0]);
+    assert_eq!(sauce.t_info_s, "IBM VGA");
+    assert!(sauce.comments.is_empty());
+}
+
+#[test]
+fn comments() {
+    let mut bytes = b"abc\x1a".to_vec();
+    bytes.extend(sauce_record("", "", "", &["first", "second"]));
+
+    let (contents, sauce) = Sauce::split(&bytes);
+
+    assert_eq!(contents, b"abc");
+    assert_eq!(sauce.unwrap().comments, ["first", "second"]);
+}
+
+#[test]
+fn cp437_fields() {
+    let mut bytes = Vec::new();
+    let mut record = sauce_record("", "", "", &[]);
+    record[7] = 0x8f;
+    bytes.extend(record);
+
+    let (_contents, sauce) = Sauce::split(&bytes);
+
+    assert_eq!(sauce.unwrap().title, "Å");
+}
+
+#[test]
+fn append_to() {
+    let metadata = CanvasMetadata {
+        title: "Title".to_string(),
+        author: "Author".to_string(),
+        group: "Group".to_string(),
+        comments: vec!["first".to_string(), "second".to_string()],
+        font: "IBM VGA".to_string(),
+    };
+    let sauce = Sauce::ansi(&metadata, 80, 25, 3, "20240131".to_string());
+    let mut bytes = b"abc".to_vec();
+    sauce.append_to(&mut bytes);
+
+    assert_eq!(
+        bytes.len(),
+        4 + 5 + 2 * SAUCE_COMMENT_LINE_SIZE + SAUCE_RECORD_SIZE
+    );
+    assert_eq!(Sauce::split(&bytes), (&b"abc"[..], Some(sauce.clone())));
+    assert_eq!(CanvasMetadata::from(sauce), metadata);
+}
+
+#[test]
+fn font_only_for_ascii_utf8() {
+    let metadata = CanvasMetadata {
+        font: "IBM VGA".to_string(),
+        ..Default::default()
+    };
+    let sauce = Sauce::ansi(&metadata, 1, 1, 1, "20240131".to_string());
+
+    assert_eq!(sauce.clone().for_utf8(b"a").t_info_s, "IBM VGA");
+    assert_eq!(sauce.for_utf8("█".as_bytes()).t_info_s, "");
+}
+
+#[test]
+fn append_to_truncates_and_encodes() {
+    let metadata = CanvasMetadata {
+        title: "Å".repeat(40),
+        author: "🎨".to_string(),
+        ..Default::default()
+    };
+    let mut bytes = Vec::new();
+    Sauce::ansi(&metadata, 1, 1, 0, "20240131".to_string()).append_to(&mut bytes);
+
+    assert_eq!(bytes.len(), 1 + SAUCE_RECORD_SIZE);
+    assert_eq!(bytes[8], 0x8f);
+    let sauce = Sauce::parse(&bytes).unwrap();
+    assert_eq!(sauce.title, "Å".repeat(35));
+    assert_eq!(sauce.author, "?");
+}
+
+#[test]
+fn today() {
+    let date = sauce::today();
+
+    assert_eq!(date.len(), 8);
+    assert!(date.chars().all(|character| character.is_ascii_digit()));
+    assert!(date.as_str() >= "20240101");
+}
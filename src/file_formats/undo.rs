@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::canvas::history::CanvasHistory;
+use crate::canvas::VersionControlledCanvas;
+
+#[cfg(test)]
+mod test;
+
+/// Version of the undo file format written by this version of upaint.
+pub const UNDO_FILE_VERSION: u32 = 1;
+
+/// Contents of an undo file, which stores the undo history of an artwork next to it, so that
+/// changes can be undone after reopening it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UndoFile {
+    pub version: u32,
+    /// Hash of the saved artwork, as given by `content_hash`. The history only applies to the
+    /// artwork if it hasn't been changed since.
+    pub content_hash: u64,
+    pub history: CanvasHistory,
+}
+
+/// Path of the undo file of the artwork at `path`, which is `.art.ansi.upaint-undo` for
+/// `art.ansi`, in the same directory.
+pub fn undo_file_path(path: &str) -> PathBuf {
+    let path = Path::new(path);
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{file_name}.upaint-undo"))
+}
+
+/// 64 bit FNV-1a hash of `content`, which unlike the hashers of the standard library stays the
+/// same across versions of Rust.
+pub fn content_hash(content: &[u8]) -> u64 {
+    content.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl UndoFile {
+    pub fn new(canvas: &VersionControlledCanvas, content: &[u8]) -> Self {
+        Self {
+            version: UNDO_FILE_VERSION,
+            content_hash: content_hash(content),
+            history: canvas.history(),
+        }
+    }
+
+    /// Writes the undo file of the artwork saved as `content` to `path`.
+    pub fn save(
+        canvas: &VersionControlledCanvas,
+        path: &str,
+        content: &[u8],
+    ) -> anyhow::Result<()> {
+        let ron = ron::to_string(&Self::new(canvas, content))?;
+        std::fs::write(undo_file_path(path), ron)
+            .map_err(|err| anyhow::anyhow!("Could not save undo history: {err}"))
+    }
+
+    /// Reads the undo file of the artwork at `path`, if there is one and the artwork is still
+    /// `content`. A history which can't be restored is an error.
+    pub fn load(path: &str, content: &[u8]) -> anyhow::Result<Option<Self>> {
+        let Ok(ron) = std::fs::read_to_string(undo_file_path(path)) else {
+            return Ok(None);
+        };
+        let file = ron::from_str::<Self>(&ron)
+            .map_err(|err| anyhow::anyhow!("Could not read undo history: {err}"))?;
+        if file.version > UNDO_FILE_VERSION || file.content_hash != content_hash(content) {
+            return Ok(None);
+        }
+        if !file.history.is_valid() {
+            anyhow::bail!("Could not read undo history: the history is corrupted");
+        }
+        Ok(Some(file))
+    }
+
+    pub fn restore(self) -> VersionControlledCanvas {
+        VersionControlledCanvas::from_history(self.history)
+    }
+}
@@ -0,0 +1,51 @@
+use crate::canvas::{CanvasModification, VersionControlledCanvas};
+use crate::file_formats::undo::{content_hash, undo_file_path, UndoFile};
+
+#[test]
+fn path_next_to_artwork() {
+    assert_eq!(
+        undo_file_path("art/cat.ansi").to_str(),
+        Some("art/.cat.ansi.upaint-undo")
+    );
+    assert_eq!(
+        undo_file_path("cat.ans").to_str(),
+        Some(".cat.ans.upaint-undo")
+    );
+}
+
+#[test]
+fn content_hash_is_stable() {
+    assert_eq!(content_hash(b""), 0xcbf29ce484222325);
+    assert_eq!(content_hash(b"a"), 0xaf63dc4c8601ec8c);
+}
+
+#[test]
+fn restores_history() {
+    let mut canvas = VersionControlledCanvas::default();
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 0), 'a')]);
+    canvas.add_layer(None);
+    canvas.create_commit(vec![CanvasModification::SetCharacter((1, 1), 'b')]);
+    canvas.undo();
+    canvas.create_commit(vec![CanvasModification::SetCharacter((2, 2), 'c')]);
+    canvas.stage(CanvasModification::SetCharacter((3, 3), 'd'));
+
+    let ron = ron::to_string(&UndoFile::new(&canvas, b"saved")).unwrap();
+    let file = ron::from_str::<UndoFile>(&ron).unwrap();
+    assert_eq!(file.content_hash, content_hash(b"saved"));
+    let mut restored = file.restore();
+
+    assert_eq!(restored.get_current_revision(), 4);
+    assert_eq!(restored.active_layer(), 1);
+    assert_eq!(restored.raw().character((2, 2)), 'c');
+    assert_eq!(restored.raw().character((3, 3)), ' ');
+    restored.go_to_revision(3);
+    assert_eq!(restored.raw().character((1, 1)), 'b');
+    restored.go_to_revision(0);
+    assert_eq!(restored.layers().len(), 1);
+    assert_eq!(restored.raw().character((0, 0)), ' ');
+    restored.redo();
+    assert_eq!(restored.raw().character((0, 0)), 'a');
+
+    // The canvas the history was taken from is unchanged
+    assert_eq!(canvas.raw().character((3, 3)), 'd');
+}
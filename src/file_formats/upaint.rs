@@ -0,0 +1,180 @@
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::canvas::frames::Frame;
+use crate::canvas::layers::CanvasLayer;
+use crate::canvas::raw::yank::CanvasYank;
+use crate::canvas::raw::CanvasIndex;
+use crate::canvas::rect::CanvasRect;
+use crate::canvas::{Canvas, VersionControlledCanvas};
+use crate::keystrokes::{ColorOrSlot, ColorSlot};
+use crate::macros::Macro;
+use crate::selections::Selection;
+use crate::ProgramState;
+
+#[cfg(test)]
+mod test;
+
+/// Version of the `.upaint` format written by this version of upaint.
+pub const UPAINT_FILE_VERSION: u32 = 1;
+
+/// Contents of a `.upaint` project file.
+///
+/// Unlike the ANSI and TXT formats, this format is lossless, and also stores the workspace state
+/// (selections, marks, yanks, macros, color slots, cursor and focus), so that work can be resumed
+/// exactly where it was left off.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpaintFile {
+    pub version: u32,
+    /// The visible layers flattened, so that versions of upaint without layers can read the
+    /// file, with the metadata of the artwork.
+    pub canvas: Canvas,
+    /// Files without layers get `canvas` as their only layer.
+    pub layers: Vec<CanvasLayer>,
+    pub active_layer: usize,
+    /// Frames of an animation, which replace `layers` if there is more than one frame.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub frames: Vec<Frame>,
+    pub active_frame: usize,
+    /// Fixed area of the artwork, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<CanvasRect>,
+    pub cursor_position: CanvasIndex,
+    pub focus_position: CanvasIndex,
+    pub selections: HashMap<char, Selection>,
+    pub selection_active: Option<char>,
+    pub marks: HashMap<char, CanvasIndex>,
+    pub yanks: HashMap<char, CanvasYank>,
+    pub yank_active: Option<char>,
+    pub macros: HashMap<char, Macro>,
+    pub color_slots: HashMap<ColorSlot, Color>,
+    #[serde(with = "color_or_slot_tagged")]
+    pub color_or_slot_active: ColorOrSlot,
+}
+
+/// `ColorOrSlot` deserializes colors untagged, to be convenient in config files, which RON can't
+/// read back when mixed with tagged variants. Therefore it is stored fully tagged here.
+mod color_or_slot_tagged {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    enum ColorOrSlotTagged {
+        Slot(ColorSlot),
+        Color(Color),
+    }
+
+    pub fn serialize<S>(value: &ColorOrSlot, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match *value {
+            ColorOrSlot::Slot(slot) => ColorOrSlotTagged::Slot(slot),
+            ColorOrSlot::Color(color) => ColorOrSlotTagged::Color(color),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<ColorOrSlot, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(match ColorOrSlotTagged::deserialize(deserializer)? {
+            ColorOrSlotTagged::Slot(slot) => ColorOrSlot::Slot(slot),
+            ColorOrSlotTagged::Color(color) => ColorOrSlot::Color(color),
+        })
+    }
+}
+
+impl UpaintFile {
+    /// Creates a project containing only `canvas`, with an empty workspace.
+    pub fn from_canvas(canvas: Canvas) -> Self {
+        Self {
+            version: UPAINT_FILE_VERSION,
+            cursor_position: canvas.area().center(),
+            focus_position: canvas.area().center(),
+            canvas,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_program_state(program_state: &ProgramState) -> Self {
+        let canvas = &program_state.canvas;
+        let (layers, frames) = if canvas.frames().len() > 1 {
+            (Vec::new(), canvas.frames().to_vec())
+        } else {
+            (canvas.layers().to_vec(), Vec::new())
+        };
+        let mut flattened = canvas.flattened().into_owned();
+        flattened.metadata = canvas.metadata().clone();
+        Self {
+            version: UPAINT_FILE_VERSION,
+            canvas: flattened,
+            layers,
+            active_layer: canvas.active_layer(),
+            frames,
+            active_frame: canvas.active_frame(),
+            page: canvas.page(),
+            cursor_position: program_state.cursor_position,
+            focus_position: program_state.focus_position,
+            selections: program_state.selections.clone(),
+            selection_active: Some(program_state.selection_active),
+            marks: program_state.marks.clone(),
+            yanks: program_state.yanks.clone(),
+            yank_active: Some(program_state.yank_active),
+            macros: program_state.macros.clone(),
+            color_slots: program_state.color_slots.clone(),
+            color_or_slot_active: program_state.color_or_slot_active,
+        }
+    }
+
+    /// Replaces the canvas and workspace state of `program_state` with the contents of the
+    /// project. The canvas history starts over from the restored canvas.
+    pub fn restore(self, program_state: &mut ProgramState) {
+        let metadata = self.canvas.metadata.clone();
+        program_state.canvas = if !self.frames.is_empty() {
+            VersionControlledCanvas::from_frames(self.frames, self.active_frame, self.active_layer)
+        } else if !self.layers.is_empty() {
+            VersionControlledCanvas::from_layers(self.layers, self.active_layer)
+        } else {
+            VersionControlledCanvas::from_canvas(self.canvas)
+        }
+        .with_page(self.page)
+        .with_metadata(metadata);
+        program_state.last_saved_revision = program_state.canvas.get_current_revision();
+        program_state.cursor_position = self.cursor_position;
+        program_state.focus_position = self.focus_position;
+        program_state.selections = self.selections;
+        if let Some(selection_active) = self.selection_active {
+            program_state.selection_active = selection_active;
+        }
+        program_state.marks = self.marks;
+        program_state.yanks = self.yanks;
+        if let Some(yank_active) = self.yank_active {
+            program_state.yank_active = yank_active;
+        }
+        program_state.macros = self.macros;
+        program_state.color_slots = self.color_slots;
+        program_state.color_or_slot_active = self.color_or_slot_active;
+    }
+
+    pub fn to_ron(&self) -> anyhow::Result<String> {
+        Ok(ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    pub fn from_ron(ron: &str) -> anyhow::Result<Self> {
+        let file = ron::from_str::<Self>(ron)?;
+        if file.version > UPAINT_FILE_VERSION {
+            anyhow::bail!(
+                "File was written by a newer version of upaint (format version {}, supported up to {})",
+                file.version,
+                UPAINT_FILE_VERSION
+            );
+        }
+        Ok(file)
+    }
+}
@@ -0,0 +1,115 @@
+use ratatui::style::{Color, Modifier};
+
+use crate::canvas::layers::CanvasLayer;
+use crate::canvas::raw::CellContentType;
+use crate::canvas::{Canvas, CanvasModification, VersionControlledCanvas};
+use crate::file_formats::upaint::UpaintFile;
+use crate::keystrokes::ColorOrSlot;
+use crate::macros::Macro;
+use crate::selections::Selection;
+
+#[test]
+fn canvas() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((0, 0), 'a');
+    canvas.set_fg((0, 0), Color::Rgb(1, 2, 3));
+    canvas.set_character((-3, 5), 'b');
+    canvas.set_bg((-3, 5), Color::Indexed(42));
+    canvas.add_modifier((-3, 5), Modifier::BOLD | Modifier::ITALIC);
+    canvas.metadata.title = "Title".to_string();
+
+    let ron = UpaintFile::from_canvas(canvas.clone()).to_ron().unwrap();
+    let loaded = UpaintFile::from_ron(ron.as_str()).unwrap().canvas;
+
+    assert_eq!(loaded.area(), canvas.area());
+    assert_eq!(loaded.cell((0, 0)), canvas.cell((0, 0)));
+    assert_eq!(loaded.cell((-3, 5)), canvas.cell((-3, 5)));
+    assert_eq!(loaded.metadata, canvas.metadata);
+}
+
+#[test]
+fn layers() {
+    let mut background = Canvas::default();
+    background.set_character((0, 0), 'a');
+    let mut text = Canvas::default();
+    text.set_character((0, 1), 'b');
+    let mut file = UpaintFile::from_canvas(background.clone());
+    file.layers = vec![
+        CanvasLayer::new("Background", background),
+        CanvasLayer::new("Text", text),
+    ];
+    file.layers[1].properties.locked = true;
+    file.active_layer = 1;
+
+    let loaded = UpaintFile::from_ron(file.to_ron().unwrap().as_str()).unwrap();
+
+    assert_eq!(loaded.active_layer, 1);
+    assert_eq!(loaded.layers.len(), 2);
+    assert_eq!(loaded.layers[1].properties, file.layers[1].properties);
+    assert_eq!(loaded.layers[1].canvas.character((0, 1)), 'b');
+}
+
+#[test]
+fn frames() {
+    let mut canvas = VersionControlledCanvas::default();
+    canvas.create_commit([CanvasModification::SetCharacter((0, 0), 'a')]);
+    canvas.duplicate_frame();
+    canvas.create_commit([CanvasModification::SetCharacter((0, 0), 'b')]);
+    canvas.set_frame_duration(250).unwrap();
+    let mut file = UpaintFile::from_canvas(canvas.flattened().into_owned());
+    file.frames = canvas.frames().to_vec();
+    file.active_frame = canvas.active_frame();
+
+    let loaded = UpaintFile::from_ron(file.to_ron().unwrap().as_str()).unwrap();
+    let loaded = VersionControlledCanvas::from_frames(
+        loaded.frames,
+        loaded.active_frame,
+        loaded.active_layer,
+    );
+
+    assert_eq!(loaded.frames().len(), 2);
+    assert_eq!(loaded.active_frame(), 1);
+    assert_eq!(loaded.frames()[1].duration, 250);
+    assert_eq!(loaded.flattened_frame(0).character((0, 0)), 'a');
+    assert_eq!(loaded.raw().character((0, 0)), 'b');
+}
+
+#[test]
+fn workspace() {
+    let mut canvas = Canvas::default();
+    canvas.set_character((1, 1), 'x');
+    let mut file = UpaintFile::from_canvas(canvas.clone());
+    file.cursor_position = (4, -2);
+    file.focus_position = (3, 3);
+    file.selections
+        .insert('a', Selection::from_iter([(0, 0), (1, 1)]));
+    file.marks.insert('m', (7, 8));
+    file.yanks
+        .insert('y', canvas.yank([(1, 1)], CellContentType::ALL, (0, 0)));
+    file.macros.insert(
+        'q',
+        Macro {
+            keystrokes: "ab<C-c>".to_string().try_into().unwrap(),
+        },
+    );
+    file.color_slots.insert('c', Color::Rgb(10, 20, 30));
+    file.color_or_slot_active = ColorOrSlot::Slot('c');
+
+    let loaded = UpaintFile::from_ron(file.to_ron().unwrap().as_str()).unwrap();
+
+    assert_eq!(loaded.cursor_position, (4, -2));
+    assert_eq!(loaded.focus_position, (3, 3));
+    assert_eq!(loaded.selections, file.selections);
+    assert_eq!(loaded.marks, file.marks);
+    assert_eq!(loaded.yanks[&'y'].cells, file.yanks[&'y'].cells);
+    assert_eq!(loaded.macros, file.macros);
+    assert_eq!(loaded.color_slots, file.color_slots);
+    assert_eq!(loaded.color_or_slot_active, ColorOrSlot::Slot('c'));
+}
+
+#[test]
+fn newer_version() {
+    let mut file = UpaintFile::default();
+    file.version = u32::MAX;
+    assert!(UpaintFile::from_ron(file.to_ron().unwrap().as_str()).is_err());
+}
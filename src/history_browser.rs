@@ -0,0 +1,155 @@
+use std::time::SystemTime;
+
+use crossterm::event::{Event, KeyCode};
+use ratatui::{
+    style::{Modifier, Style},
+    widgets::{Block, Borders, List, ListState, StatefulWidget, Widget},
+};
+
+use crate::actions::history::format_age;
+use crate::canvas::VersionControlledCanvas;
+use crate::selections::Selection;
+
+#[cfg(test)]
+mod test;
+
+/// A revision listed by the history browser.
+#[derive(Debug, Clone)]
+struct HistoryBrowserEntry {
+    revision: u64,
+    /// When the commit was made, `None` for the initial canvas.
+    time: Option<SystemTime>,
+    changed_cells: usize,
+}
+
+/// Lists every revision of the canvas, newest first, and previews the selected one without
+/// modifying the canvas.
+#[derive(Debug, Clone)]
+pub struct HistoryBrowser {
+    entries: Vec<HistoryBrowserEntry>,
+    selected: usize,
+    /// Revision of the canvas when the browser was opened.
+    current: u64,
+    /// Copy of the canvas, moved to the selected revision.
+    preview: VersionControlledCanvas,
+    /// Cells changed by the commit at the selected revision.
+    highlight: Selection,
+}
+
+impl HistoryBrowser {
+    /// Opens the browser with the current revision of `canvas` selected. Staged modifications
+    /// aren't previewed.
+    pub fn new(canvas: &VersionControlledCanvas) -> Self {
+        let mut entries = vec![HistoryBrowserEntry {
+            revision: 0,
+            time: None,
+            changed_cells: 0,
+        }];
+        entries.extend(canvas.commits().map(|commit| HistoryBrowserEntry {
+            revision: commit.revision(),
+            time: Some(commit.time()),
+            changed_cells: commit.changed_cells(),
+        }));
+        entries.reverse();
+        let current = canvas.get_current_revision();
+        let mut preview = canvas.clone();
+        preview.clear_staged();
+        let mut result = Self {
+            entries,
+            selected: 0,
+            current,
+            preview,
+            highlight: Selection::new(),
+        };
+        let selected = result
+            .entries
+            .iter()
+            .position(|entry| entry.revision == current)
+            .unwrap_or(0);
+        result.select(selected);
+        result
+    }
+
+    /// Selects the entry at `index`, clamped to the list, and moves the preview to its revision.
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.entries.len() - 1);
+        let revision = self.selected_revision();
+        self.preview.go_to_revision(revision);
+        self.highlight = Selection::new();
+        if let Some(commit) = self.preview.commit(revision) {
+            let frame = commit.frame();
+            self.highlight = commit.changed_indices();
+            // Moving back up the tree leaves the frame of the last undone commit active
+            if let Some(frame) = frame {
+                let _ = self.preview.set_active_frame(frame);
+            }
+        }
+    }
+
+    pub fn selected_revision(&self) -> u64 {
+        self.entries[self.selected].revision
+    }
+
+    /// The canvas at the selected revision.
+    pub fn preview(&self) -> &VersionControlledCanvas {
+        &self.preview
+    }
+
+    /// Cells changed by the commit at the selected revision, in the active frame of the preview.
+    pub fn highlight(&self) -> &Selection {
+        &self.highlight
+    }
+
+    /// Moves the selection, `j` and `k` or the arrow keys moving to older and newer revisions.
+    pub fn input(&mut self, event: Event) {
+        let Event::Key(e) = event else {
+            return;
+        };
+        match e.code {
+            KeyCode::Char('j') | KeyCode::Down => self.select(self.selected + 1),
+            KeyCode::Char('k') | KeyCode::Up => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Char('J') | KeyCode::PageDown => self.select(self.selected + 10),
+            KeyCode::Char('K') | KeyCode::PageUp => self.select(self.selected.saturating_sub(10)),
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => self.select(self.entries.len() - 1),
+            _ => (),
+        }
+    }
+
+    pub fn widget(&self) -> impl Widget + '_ {
+        HistoryBrowserWidget { browser: self }
+    }
+}
+
+pub struct HistoryBrowserWidget<'a> {
+    browser: &'a HistoryBrowser,
+}
+
+impl Widget for HistoryBrowserWidget<'_> {
+    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let items = self.browser.entries.iter().map(|entry| {
+            let marker = if entry.revision == self.browser.current {
+                "*"
+            } else {
+                " "
+            };
+            let when = match entry.time {
+                Some(time) => format_age(time),
+                None => "initial canvas".to_string(),
+            };
+            format!(
+                "{marker}{:>5} {:>6}  {when}",
+                entry.revision, entry.changed_cells
+            )
+        });
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title("History: revision, cells")
+                    .borders(Borders::ALL),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(self.browser.selected));
+        StatefulWidget::render(list, area, buf, &mut state);
+    }
+}
@@ -0,0 +1,43 @@
+use crossterm::event::{Event, KeyCode, KeyEvent};
+
+use crate::canvas::{CanvasModification, VersionControlledCanvas};
+use crate::history_browser::HistoryBrowser;
+use crate::selections::Selection;
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::from(code))
+}
+
+#[test]
+fn previews_without_modifying_the_canvas() {
+    let mut canvas = VersionControlledCanvas::default();
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 0), 'a')]);
+    canvas.create_commit(vec![
+        CanvasModification::SetCharacter((1, 0), 'b'),
+        CanvasModification::SetCharacter((1, 1), 'c'),
+    ]);
+    canvas.undo();
+    canvas.create_commit(vec![CanvasModification::SetCharacter((0, 0), 'x')]);
+
+    let mut browser = HistoryBrowser::new(&canvas);
+    assert_eq!(browser.selected_revision(), 3);
+    assert_eq!(browser.highlight(), &Selection::from([(0, 0)]));
+
+    browser.input(key(KeyCode::Char('j')));
+    assert_eq!(browser.selected_revision(), 2);
+    assert_eq!(browser.preview().raw().character((0, 0)), 'a');
+    assert_eq!(browser.preview().raw().character((1, 1)), 'c');
+    assert_eq!(browser.highlight(), &Selection::from([(1, 0), (1, 1)]));
+
+    browser.input(key(KeyCode::Char('G')));
+    assert_eq!(browser.selected_revision(), 0);
+    assert_eq!(browser.preview().raw().character((0, 0)), ' ');
+    assert!(browser.highlight().is_empty());
+    browser.input(key(KeyCode::Down));
+    assert_eq!(browser.selected_revision(), 0);
+
+    browser.input(key(KeyCode::Char('k')));
+    assert_eq!(browser.selected_revision(), 1);
+    assert_eq!(canvas.get_current_revision(), 3);
+    assert_eq!(canvas.raw().character((0, 0)), 'x');
+}
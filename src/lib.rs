@@ -5,14 +5,17 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::collections::VecDeque;
 use std::fmt::Display;
+use std::time::Instant;
 use tui_textarea::TextArea;
 
 pub mod actions;
 pub mod canvas;
+pub mod clipboard;
 pub mod color_picker;
 pub mod command_line;
 pub mod config;
 pub mod file_formats;
+pub mod history_browser;
 pub mod input_mode;
 pub mod keystrokes;
 pub mod line_drawing;
@@ -36,6 +39,7 @@ use canvas::raw::CanvasIndex;
 use canvas::{rect::CanvasRect, VersionControlledCanvas};
 use color_picker::target::ColorPickerTargetEnum;
 use color_picker::ColorPicker;
+use history_browser::HistoryBrowser;
 use input_mode::InputMode;
 use keystrokes::ColorSlot;
 use keystrokes_parsing::KeystrokeSequence;
@@ -200,9 +204,41 @@ pub struct ProgramState {
     pub macros: HashMap<char, Macro>,
     pub macro_recording: Option<MacroRecording>,
     pub line_drawing: Option<LineDrawingState>,
+    /// Whether the previous frame is shown dimmed below the active frame.
+    pub onion_skin: bool,
+    /// When the looping playback of the frames started, if they are being played.
+    pub playback: Option<Instant>,
+    /// The revisions listed for previewing and jumping to, if they are being browsed.
+    pub history_browser: Option<HistoryBrowser>,
+    /// Revision at which the input mode session whose commits are merged started, see
+    /// `CommitMerging::Session`.
+    pub commit_merging_session: Option<u64>,
+    /// Whether the cells changed since the last save are highlighted.
+    pub changed_cells_highlighted: bool,
+    /// Cells changed since the last save, with the state of the canvas they were found at, see
+    /// `ProgramState::changed_cells`.
+    pub changed_cells_cache: Option<((u64, u64, usize), Selection)>,
 }
 
 impl ProgramState {
+    /// Cells changed since the last save. They are only found again once the canvas, the last
+    /// saved revision or the active frame changed, instead of on every redraw.
+    pub fn changed_cells(&mut self) -> &Selection {
+        let key = (
+            self.canvas.changes(),
+            self.last_saved_revision,
+            self.canvas.active_frame(),
+        );
+        let cache = match self.changed_cells_cache.take() {
+            Some(cache) if cache.0 == key => cache,
+            _ => (
+                key,
+                self.canvas.cells_changed_since(self.last_saved_revision),
+            ),
+        };
+        &self.changed_cells_cache.insert(cache).1
+    }
+
     fn input_mode_config(&self) -> Option<&ConfigInputMode> {
         self.config.input_mode.get(&self.input_mode)
     }
@@ -1,6 +1,7 @@
 use keystrokes_parsing::KeystrokeSequence;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Macro {
     pub keystrokes: KeystrokeSequence,
 }
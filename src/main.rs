@@ -6,8 +6,8 @@ use clap::Parser;
 use crossterm::{
     cursor::{self, SetCursorStyle},
     event::{
-        self, DisableMouseCapture, EnableMouseCapture, KeyboardEnhancementFlags,
-        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
     },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
@@ -27,8 +27,15 @@ use std::{
 };
 
 use upaint::{
-    canvas::VersionControlledCanvas, command_line::create_command_line_textarea,
-    rendering::draw_frame, user_input::handle_user_input, ProgramState,
+    actions::{session::ReadImage, FallibleAction},
+    canvas::raw::image_import::ImageImportOptions,
+    canvas::raw::quantize::{ColorDepth, Dithering},
+    canvas::{raw::legacy_ansi_import::is_legacy_ansi, Canvas, VersionControlledCanvas},
+    command_line::create_command_line_textarea,
+    file_formats::{undo::UndoFile, upaint::UpaintFile, FileFormat},
+    rendering::draw_frame,
+    user_input::handle_user_input,
+    ProgramState,
 };
 
 #[derive(Parser, Debug)]
@@ -37,6 +44,29 @@ pub struct UpaintCli {
     ansi_file: Option<String>,
     #[arg(short, long)]
     config: Option<String>,
+    /// Import the file as a legacy BBS ANSI file, with CP437 encoding and 80 column wrapping.
+    /// This is done automatically for files that aren't valid UTF-8.
+    #[arg(long)]
+    legacy_ansi: bool,
+    /// Import the file by running it through a virtual terminal, which supports cursor movement
+    /// and erasing, for example to import recorded terminal output.
+    #[arg(long, conflicts_with = "legacy_ansi")]
+    terminal_ansi: bool,
+    /// Width of the virtual terminal used by `--terminal-ansi`, at which lines wrap.
+    #[arg(long, requires = "terminal_ansi")]
+    terminal_columns: Option<u16>,
+    /// Import a PNG or PPM image as half-block pixel art, as an undoable change to the canvas.
+    #[arg(long)]
+    image: Option<String>,
+    /// Width in columns the image is scaled to. Defaults to the width of the image, up to 80.
+    #[arg(long, requires = "image")]
+    image_width: Option<u16>,
+    /// Colors the image is reduced to: truecolor, 256 or 16.
+    #[arg(long, requires = "image", default_value = "truecolor")]
+    image_colors: ColorDepth,
+    /// Dithering used when reducing the colors of the image: none, ordered or error_diffusion.
+    #[arg(long, requires = "image", default_value = "none")]
+    image_dither: Dithering,
 }
 
 struct FileLogger;
@@ -120,16 +150,43 @@ fn application(
     let mut program_state = ProgramState::default();
     program_state.exit = false;
     program_state.open_file = args.ansi_file;
-    let ansi_to_load = if !io::stdin().is_terminal() {
-        let mut input_piped = "".to_string();
-        io::stdin().read_to_string(&mut input_piped).unwrap();
+    let bytes_to_load = if !io::stdin().is_terminal() {
+        let mut input_piped = Vec::new();
+        io::stdin().read_to_end(&mut input_piped).unwrap();
         input_piped
     } else if let Some(file_path) = &program_state.open_file {
-        std::fs::read_to_string(file_path).unwrap()
+        std::fs::read(file_path).unwrap()
     } else {
-        "".to_string()
+        Vec::new()
     };
-    program_state.canvas = VersionControlledCanvas::from_ansi(ansi_to_load)?;
+    let open_file_format = match &program_state.open_file {
+        Some(file_path) if io::stdin().is_terminal() => {
+            FileFormat::try_from(file_path.as_str()).ok()
+        }
+        _ => None,
+    };
+    let project = match open_file_format {
+        Some(FileFormat::Upaint) => {
+            Some(UpaintFile::from_ron(std::str::from_utf8(&bytes_to_load)?)?)
+        }
+        _ => None,
+    };
+    if project.is_none() {
+        program_state.canvas = if open_file_format == Some(FileFormat::Irc) {
+            VersionControlledCanvas::from_canvas(Canvas::from_irc(std::str::from_utf8(
+                &bytes_to_load,
+            )?)?)
+        } else if args.terminal_ansi {
+            VersionControlledCanvas::from_canvas(Canvas::from_terminal_ansi(
+                std::str::from_utf8(&bytes_to_load)?,
+                args.terminal_columns,
+            )?)
+        } else if args.legacy_ansi || is_legacy_ansi(&bytes_to_load) {
+            VersionControlledCanvas::from_canvas(Canvas::from_legacy_ansi(&bytes_to_load)?)
+        } else {
+            VersionControlledCanvas::from_canvas(Canvas::from_ansi_bytes(&bytes_to_load)?)
+        };
+    }
     program_state.last_saved_revision = program_state.canvas.get_current_revision();
     let config_source = if let Some(config) = args.config {
         ConfigSource::from_str(config.as_str()).unwrap()
@@ -154,6 +211,37 @@ fn application(
     program_state.yank_active = 'a';
     program_state.highlight = None;
     program_state.highlighting_on = true;
+    if let Some(project) = project {
+        project.restore(&mut program_state);
+    }
+    let opened_file = program_state
+        .open_file
+        .clone()
+        .filter(|_| io::stdin().is_terminal());
+    if let Some(file_path) = opened_file {
+        if program_state.config.undo_file && program_state.canvas.get_current_revision() == 0 {
+            match UndoFile::load(&file_path, &bytes_to_load) {
+                Ok(Some(undo_file)) => {
+                    program_state.canvas = undo_file.restore();
+                    program_state.last_saved_revision = program_state.canvas.get_current_revision();
+                }
+                Ok(None) => (),
+                Err(err) => program_state.new_messages.push_back(err.to_string()),
+            }
+        }
+    }
+    // Imported on top of the restored file, at the restored cursor, as an unsaved change
+    if let Some(image) = args.image {
+        ReadImage {
+            filename: image,
+            options: ImageImportOptions {
+                columns: args.image_width,
+                color_depth: args.image_colors,
+                dithering: args.image_dither,
+            },
+        }
+        .try_execute(&mut program_state)?;
+    }
     let program_state = Arc::new(Mutex::new(program_state));
     let (exit_tx, exit_rx) = mpsc::sync_channel::<()>(1);
     let exit_tx = Arc::new(Mutex::new(exit_tx));
@@ -191,10 +279,23 @@ fn application(
     thread::Builder::new()
         .name("draw screen".to_string())
         .spawn(move || -> anyhow::Result<()> {
+            // While the frames are played back, the screen is also redrawn when the next frame is
+            // due
+            let mut next_frame = None;
             loop {
-                redraw_rx.recv()?;
+                match next_frame {
+                    Some(timeout) => match redraw_rx.recv_timeout(timeout) {
+                        Ok(()) | Err(mpsc::RecvTimeoutError::Timeout) => (),
+                        Err(error @ mpsc::RecvTimeoutError::Disconnected) => Err(error)?,
+                    },
+                    None => redraw_rx.recv()?,
+                }
                 let mut program_state = program_state_draw_screen.lock().unwrap();
                 draw_frame(&mut terminal, &mut program_state)?;
+                next_frame = program_state.playback.map(|started| {
+                    let (_frame, remaining) = program_state.canvas.frame_at(started.elapsed());
+                    remaining
+                });
             }
             // Ok(())
         })?;
@@ -294,6 +395,7 @@ fn setup_terminal() -> anyhow::Result<Terminal<CrosstermBackend<io::Stdout>>> {
         stdout,
         EnterAlternateScreen,
         EnableMouseCapture,
+        EnableBracketedPaste,
         cursor::Hide, // This seems to happen anyways
         PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES),
     )?;
@@ -307,6 +409,7 @@ fn restore_terminal() -> anyhow::Result<()> {
         io::stdout(),
         LeaveAlternateScreen,
         DisableMouseCapture,
+        DisableBracketedPaste,
         SetCursorStyle::DefaultUserShape,
         cursor::Show,
         PopKeyboardEnhancementFlags,
@@ -7,20 +7,28 @@ use crate::canvas::raw::iter::StopCondition;
 use crate::canvas::raw::iter::StopConditionContent;
 use crate::canvas::raw::iter::WordBoundaryType;
 use crate::canvas::raw::CellContentType;
+use crate::canvas::raw::Grapheme;
 use crate::canvas::rect::CanvasRect;
 use crate::canvas::CanvasIndex;
+use crate::config::input_mode::keymaps::UnsignedIntegerKeymapEntry;
 use crate::keystrokes::Count;
 use crate::selections::Selection;
 use crate::selections::SelectionSlotSpecification;
 use crate::DirectionFree;
 use crate::ProgramState;
 use enum_dispatch::enum_dispatch;
+use keystrokes_parsing::PresetStructField;
 use keystrokes_parsing::Presetable;
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
 #[enum_dispatch]
 pub trait Motion: Debug {
     fn cells(&self, program_state: &ProgramState) -> Vec<CanvasIndex>;
+    /// Like `cells`, but with the reason if the motion can't find its cells, to show the user.
+    fn try_cells(&self, program_state: &ProgramState) -> anyhow::Result<Vec<CanvasIndex>> {
+        Ok(self.cells(program_state))
+    }
 }
 #[enum_dispatch(Motion)]
 #[derive(Clone, Debug, PartialEq, Presetable)]
@@ -53,6 +61,7 @@ pub enum MotionRepeatEnum {
     WordBoundary(WordBoundary),
     FindChar(FindChar),
     FindCharRepeat(FindCharRepeat),
+    ChangedCell(ChangedCell),
 }
 
 #[derive(Clone, Debug, PartialEq, Presetable)]
@@ -121,11 +130,13 @@ impl MotionRepeatable for WordBoundary {
     }
 }
 
+/// Cells are matched by their whole grapheme cluster, so finding `e` doesn't find an `e` with a
+/// combining accent.
 #[derive(Clone, Debug, PartialEq, Presetable)]
 #[presetable(config_type = "ProgramState")]
 pub struct FindChar {
     pub direction: DirectionFree,
-    pub ch: char,
+    pub ch: Grapheme,
 }
 impl MotionRepeatable for FindChar {
     fn cells_repeatable(&self, count: u32, program_state: &ProgramState) -> Vec<CanvasIndex> {
@@ -137,7 +148,7 @@ impl MotionRepeatable for FindChar {
             CanvasIterationJump::Diagonals,
             StopCondition::Content {
                 canvas,
-                condition: StopConditionContent::CharacterMatch(self.ch),
+                condition: StopConditionContent::GraphemeMatch(self.ch.clone()),
             },
             count,
         );
@@ -163,6 +174,49 @@ impl MotionRepeatable for FindCharRepeat {
     }
 }
 
+/// Moves to the next cell in reading order which looks different than when the artwork was last
+/// saved, or to the previous one if `backwards` is set, wrapping around at the end.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct ChangedCell {
+    pub jump: CanvasIterationJump,
+    pub backwards: bool,
+}
+impl MotionRepeatable for ChangedCell {
+    fn cells_repeatable(&self, count: u32, program_state: &ProgramState) -> Vec<CanvasIndex> {
+        let from = program_state.cursor_position;
+        let mut changed: Vec<CanvasIndex> = program_state
+            .canvas
+            .cells_changed_since(program_state.last_saved_revision)
+            .into_iter()
+            .collect();
+        if changed.is_empty() {
+            return vec![];
+        }
+        changed.sort();
+        if self.backwards {
+            changed.reverse();
+        }
+        let next = changed
+            .iter()
+            .position(|index| {
+                if self.backwards {
+                    *index < from
+                } else {
+                    *index > from
+                }
+            })
+            .unwrap_or(0);
+        let (row, column) = changed[(next + count.max(1) as usize - 1) % changed.len()];
+        GoToCoordinate {
+            jump: self.jump,
+            row,
+            column,
+        }
+        .cells(program_state)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Presetable)]
 #[presetable(config_type = "ProgramState")]
 pub struct SelectionMotion {
@@ -348,19 +402,60 @@ impl Motion for MatchingCells {
     }
 }
 
+/// Area a continuous region is searched within.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ContinuousRegionBound {
+    #[default]
+    Canvas,
+    Visible,
+    VisualRect,
+}
+impl ContinuousRegionBound {
+    fn rect(&self, program_state: &ProgramState) -> anyhow::Result<CanvasRect> {
+        Ok(match self {
+            Self::Canvas => program_state.canvas.raw().area(),
+            Self::Visible => program_state.canvas_visible,
+            Self::VisualRect => {
+                let Some(corners) = program_state.visual_rect else {
+                    anyhow::bail!("There is no visual rect to limit the region to");
+                };
+                CanvasRect::from_corners(corners)
+            }
+        })
+    }
+}
+
+const CONTINUOUS_REGION_MAX_CELLS: u32 = 1_000_000;
+fn continuous_region_max_cells() -> PresetStructField<UnsignedIntegerKeymapEntry<u32>> {
+    PresetStructField::Preset(UnsignedIntegerKeymapEntry::Number(
+        CONTINUOUS_REGION_MAX_CELLS,
+    ))
+}
 #[derive(Clone, Debug, PartialEq, Presetable)]
 #[presetable(config_type = "ProgramState")]
 pub struct ContinuousRegion {
     pub relative_type: ContinuousRegionRelativeType,
     pub diagonals_allowed: bool,
+    pub bound: ContinuousRegionBound,
+    #[presetable(default = "continuous_region_max_cells")]
+    pub max_cells: u32,
 }
 impl Motion for ContinuousRegion {
     fn cells(&self, program_state: &ProgramState) -> Vec<CanvasIndex> {
+        self.try_cells(program_state).unwrap_or_default()
+    }
+    fn try_cells(&self, program_state: &ProgramState) -> anyhow::Result<Vec<CanvasIndex>> {
         let canvas = program_state.canvas.raw();
         let start = program_state.cursor_position;
         let match_cell = MatchCell::from((canvas.get(&start), self.relative_type));
-        find_continuous_region(&canvas, start, match_cell, self.diagonals_allowed)
-            .into_iter()
-            .collect()
+        let region = find_continuous_region(
+            &canvas,
+            start,
+            match_cell,
+            self.diagonals_allowed,
+            self.bound.rect(program_state)?,
+            self.max_cells as usize,
+        )?;
+        Ok(region.into_iter().collect())
     }
 }
@@ -3,10 +3,17 @@ use crate::actions::ActionBatch;
 use crate::actions::ActionEnum;
 use crate::actions::ChangeMode;
 use crate::actions::InitColorPicker;
+use crate::canvas::raw::cell_map::BTreeCellMap;
+use crate::canvas::raw::transform::{
+    move_cells, pair_wide_characters, reorder_wide_character_pairs,
+};
 use crate::canvas::raw::CanvasCell;
 use crate::canvas::raw::CellContentType;
+use crate::canvas::raw::Grapheme;
+use crate::canvas::rect::CanvasRect;
 use crate::canvas::CanvasIndex;
 use crate::canvas::CanvasModification;
+use crate::clipboard::{copy_yank_to_clipboard, CLIPBOARD_YANK_SLOT};
 use crate::color_picker::target::ColorPickerTargetEnum;
 use crate::color_picker::target::ColorPickerTargetMotion;
 use crate::input_mode::InputMode;
@@ -17,8 +24,10 @@ use crate::motions::SelectionDirectMotion;
 use crate::selections::Selection;
 use crate::selections::SelectionSlotSpecification;
 use crate::yank_slots::YankSlotSpecification;
+use crate::Axis;
 use crate::Ground;
 use crate::ProgramState;
+use crate::RotationDirection;
 use enum_dispatch::enum_dispatch;
 use keystrokes_parsing::Presetable;
 use serde::{Deserialize, Serialize};
@@ -39,6 +48,8 @@ pub enum OperatorEnum {
     Yank(Yank),
     Cut(Cut),
     ColorPickerOperator(ColorPickerOperator),
+    Mirror(Mirror),
+    Rotate(Rotate),
 }
 
 #[derive(Clone, Debug, PartialEq, Presetable)]
@@ -74,13 +85,13 @@ impl Operator for Colorize {
 #[derive(Clone, Debug, PartialEq, Presetable)]
 #[presetable(config_type = "ProgramState")]
 pub struct Replace {
-    pub ch: char,
+    pub ch: Grapheme,
 }
 impl Operator for Replace {
     fn operate(&self, cell_indices: &[CanvasIndex], program_state: &mut ProgramState) {
         let mut canvas_operations = Vec::new();
         for index in cell_indices {
-            canvas_operations.push(CanvasModification::SetCharacter(*index, self.ch));
+            canvas_operations.push(CanvasModification::SetGrapheme(*index, self.ch.clone()));
         }
         program_state.canvas.create_commit(canvas_operations);
     }
@@ -143,9 +154,15 @@ impl Operator for Yank {
                 .canvas
                 .raw()
                 .yank(a, self.content_type, program_state.cursor_position);
-        program_state
-            .yanks
-            .insert(self.slot.as_char(&program_state), yank);
+        let slot = self.slot.as_char(&program_state);
+        if slot == CLIPBOARD_YANK_SLOT {
+            if let Err(err) = copy_yank_to_clipboard(&yank, program_state.config.clipboard_ansi) {
+                program_state
+                    .new_messages
+                    .push_back(format!("Couldn't copy to clipboard: {err}"));
+            }
+        }
+        program_state.yanks.insert(slot, yank);
     }
 }
 #[derive(Clone, Debug, PartialEq, Presetable)]
@@ -195,3 +212,155 @@ impl Operator for ColorPickerOperator {
         .execute(program_state);
     }
 }
+
+/// Point that cells are mirrored or rotated around.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum TransformCenter {
+    /// The center of the smallest rectangle covering the cells, which is the center of the visual
+    /// rect when operating on it.
+    #[default]
+    Cells,
+    Cursor,
+}
+impl TransformCenter {
+    /// The center in half cells, so that it can lie between two cells. It doesn't always fit an
+    /// `i16`, so it is given in `i32`.
+    fn doubled(
+        &self,
+        cell_indices: &[CanvasIndex],
+        program_state: &ProgramState,
+    ) -> Option<(i32, i32)> {
+        match self {
+            Self::Cells => {
+                if cell_indices.is_empty() {
+                    return None;
+                }
+                let rect = CanvasRect::from_cells(cell_indices.iter().copied());
+                Some((
+                    rect.first_row() as i32 + rect.last_row() as i32,
+                    rect.first_column() as i32 + rect.last_column() as i32,
+                ))
+            }
+            Self::Cursor => {
+                let (row, column) = program_state.cursor_position;
+                Some((2 * row as i32, 2 * column as i32))
+            }
+        }
+    }
+}
+
+/// Replaces the cells at `cell_indices` by `cells`, which have been moved from them, in a single
+/// commit. Cells that nothing was moved to are cleared.
+fn replace_transformed_cells(
+    cell_indices: &[CanvasIndex],
+    cells: impl IntoIterator<Item = (CanvasIndex, CanvasCell)>,
+    program_state: &mut ProgramState,
+) {
+    let mut resulting: BTreeCellMap = cell_indices
+        .iter()
+        .map(|index| (*index, CanvasCell::default()))
+        .collect();
+    // Cells transformed to outside the page are dropped
+    resulting.extend(
+        cells
+            .into_iter()
+            .filter(|(index, _cell)| program_state.canvas.in_page(*index)),
+    );
+    program_state.canvas.create_commit(
+        resulting
+            .into_iter()
+            .map(|(index, cell)| CanvasModification::SetCell(index, cell)),
+    );
+}
+
+fn cells_at(cell_indices: &[CanvasIndex], program_state: &ProgramState) -> BTreeCellMap {
+    let canvas = program_state.canvas.raw();
+    cell_indices
+        .iter()
+        .map(|index| (*index, canvas.get(index).clone()))
+        .collect()
+}
+
+/// Mirrors the cells in place, replacing characters by their mirrored counterparts from
+/// `character_mirrors` in the config. Like for `MirrorYank`, the axis is the one along which the
+/// cells are moved, so `X` flips them horizontally.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct Mirror {
+    pub axis: Axis,
+    pub center: TransformCenter,
+}
+impl Operator for Mirror {
+    fn operate(&self, cell_indices: &[CanvasIndex], program_state: &mut ProgramState) {
+        let Some((center_row, center_column)) = self.center.doubled(cell_indices, program_state)
+        else {
+            return;
+        };
+        let cells = cells_at(cell_indices, program_state);
+        let mirrors = &program_state.config.character_mirrors;
+        // Subtracting from twice the center mirrors around the center, even if it lies between
+        // two cells
+        let cells = if self.axis == Axis::X {
+            let mut cells = move_cells(
+                cells,
+                |(row, column)| (row as i32, center_column - column as i32),
+                &mirrors.x,
+            );
+            reorder_wide_character_pairs(&mut cells);
+            cells
+        } else {
+            move_cells(
+                cells,
+                |(row, column)| (center_row - row as i32, column as i32),
+                &mirrors.y,
+            )
+        };
+        replace_transformed_cells(cell_indices, cells, program_state);
+    }
+}
+
+/// Rotates the cells a quarter turn in place, replacing characters by their rotated counterparts
+/// from `character_rotations` in the config. When the center lies between cells, the rotated
+/// cells are moved up or left by half a cell.
+#[derive(Clone, Debug, PartialEq, Presetable)]
+#[presetable(config_type = "ProgramState")]
+pub struct Rotate {
+    pub direction: RotationDirection,
+    pub center: TransformCenter,
+}
+impl Operator for Rotate {
+    fn operate(&self, cell_indices: &[CanvasIndex], program_state: &mut ProgramState) {
+        let Some((center_row, center_column)) = self.center.doubled(cell_indices, program_state)
+        else {
+            return;
+        };
+        let cells = cells_at(cell_indices, program_state);
+        let swaps = program_state
+            .config
+            .character_rotations
+            .swaps(self.direction);
+        // Rotating around 0 turns the center too, so move it back onto the original center
+        let clockwise = self.direction == RotationDirection::Clockwise;
+        let offset = if clockwise {
+            (center_row - center_column, center_row + center_column)
+        } else {
+            (center_row + center_column, center_column - center_row)
+        };
+        let offset = (offset.0.div_euclid(2), offset.1.div_euclid(2));
+        let mut cells = move_cells(
+            cells,
+            |(row, column)| {
+                let (row, column) = (row as i32, column as i32);
+                if clockwise {
+                    (column + offset.0, offset.1 - row)
+                } else {
+                    (offset.0 - column, row + offset.1)
+                }
+            },
+            swaps,
+        );
+        // Continuation cells end up below or above their wide character
+        pair_wide_characters(&mut cells);
+        replace_transformed_cells(cell_indices, cells, program_state);
+    }
+}
@@ -8,8 +8,9 @@ use ratatui::{
 use std::io;
 
 use crate::{
-    command_line::CommandLineWidget, input_mode::InputModeHandler,
-    line_drawing::draw_line_on_canvas, status_bar::StatusBar, ProgramState,
+    canvas::raw::rendering::CanvasWidget, command_line::CommandLineWidget,
+    input_mode::InputModeHandler, line_drawing::draw_line_on_canvas, status_bar::StatusBar,
+    ProgramState,
 };
 
 pub fn draw_frame(
@@ -21,6 +22,7 @@ pub fn draw_frame(
     // terminal.hide_cursor()?;
     terminal.draw(|f| {
         let sidebar_width = match input_handler {
+            _ if program_state.history_browser.is_some() => 36,
             InputModeHandler::ColorPicker => 18,
             _ => 0,
         };
@@ -106,7 +108,35 @@ pub fn draw_frame(
             }
         }
 
-        let mut canvas = program_state.canvas.widget(&program_state.config);
+        let changed = (program_state.changed_cells_highlighted
+            && program_state.highlighting_on
+            && program_state.playback.is_none()
+            && program_state.history_browser.is_none())
+        .then(|| program_state.changed_cells().clone());
+        let mut canvas = if let Some(browser) = &program_state.history_browser {
+            let mut canvas = browser.preview().widget(&program_state.config);
+            canvas.selection = Some(browser.highlight().clone());
+            canvas
+        } else if let Some(started) = program_state.playback {
+            let (frame, _remaining) = program_state.canvas.frame_at(started.elapsed());
+            let mut canvas = CanvasWidget::from_canvas(
+                program_state.canvas.flattened_frame(frame),
+                &program_state.config,
+            );
+            canvas.page = program_state.canvas.page();
+            canvas
+        } else {
+            program_state.canvas.widget(&program_state.config)
+        };
+        let active_frame = program_state.canvas.active_frame();
+        let browsing_history = program_state.history_browser.is_some();
+        if program_state.onion_skin
+            && program_state.playback.is_none()
+            && !browsing_history
+            && active_frame > 0
+        {
+            canvas.onion_skin = Some(program_state.canvas.flattened_frame(active_frame - 1));
+        }
         canvas.focus = program_state.focus_position;
         let canvas_visible = canvas.visible(inner_area);
 
@@ -132,15 +162,18 @@ pub fn draw_frame(
                 crate::canvas::raw::rendering::canvas_layout_chunks(inner_area).0,
             );
 
-        canvas.cursor = Some(program_state.cursor_position);
+        if program_state.playback.is_none() && !browsing_history {
+            canvas.cursor = Some(program_state.cursor_position);
+        }
 
-        if program_state.highlighting_on {
+        if program_state.highlighting_on && program_state.playback.is_none() && !browsing_history {
             if let Some(corners) = program_state.visual_rect {
                 canvas.visual_rect = Some(corners);
             }
             if let Some(highlight) = &program_state.highlight {
                 canvas.selection = Some(highlight.clone());
             }
+            canvas.changed = changed;
         }
 
         f.render_widget(canvas, inner_area);
@@ -170,7 +203,9 @@ pub fn draw_frame(
             f.render_widget(input_mode, command_line_chunk);
         }
 
-        if input_handler == InputModeHandler::ColorPicker {
+        if let Some(browser) = &program_state.history_browser {
+            f.render_widget(browser.widget(), sidebar_chunk);
+        } else if input_handler == InputModeHandler::ColorPicker {
             f.render_widget(program_state.color_picker.widget(), color_picker_chunk);
         }
 
@@ -46,8 +46,30 @@ impl<'a> Widget for StatusBar<'a> {
             )
             .split(area);
 
+        let canvas = &self.program_state.canvas;
+        let layer = if canvas.layers().len() > 1 {
+            format!(
+                " │ {} {}/{}",
+                canvas.active().properties.name,
+                canvas.active_layer() + 1,
+                canvas.layers().len()
+            )
+        } else {
+            String::new()
+        };
+        let frame = if self.program_state.playback.is_some() {
+            format!(" │ ▶ {} frames", canvas.frames().len())
+        } else if canvas.frames().len() > 1 {
+            format!(
+                " │ frame {}/{}",
+                canvas.active_frame() + 1,
+                canvas.frames().len()
+            )
+        } else {
+            String::new()
+        };
         let open_file = format!(
-            "{title}{}",
+            "{title}{}{frame}{layer}",
             if self.program_state.last_saved_revision
                 == self.program_state.canvas.get_current_revision()
             {
@@ -1,3 +1,5 @@
+use crate::actions::clipboard::PasteText;
+use crate::actions::history::merge_commits;
 use crate::actions::ActionBatch;
 use crate::actions::ClearAllModeItems;
 use crate::color_picker::target::ColorPickerTarget;
@@ -53,6 +55,9 @@ pub fn handle_user_input_command_mode(
             program_state.a += 10;
             if e.kind == MouseEventKind::Moved {}
         }
+        Event::Paste(text) => {
+            program_state.command_line.insert_str(text);
+        }
         _ => {
             program_state.a += 10;
         }
@@ -116,6 +121,7 @@ pub fn handle_user_input_action(
                 program_state.mouse_input_state.previous_position = Some((e.row, e.column));
             }
         }
+        Event::Paste(text) => PasteText { text }.execute(program_state),
         _ => {
             program_state.a += 10;
         }
@@ -160,6 +166,34 @@ pub fn handle_user_input_color_picker(
     Ok(())
 }
 
+/// Handles input while the history browser is open. Enter moves the canvas to the selected
+/// revision, escape or `q` closes the browser without changing anything.
+pub fn handle_user_input_history_browser(
+    event: Event,
+    program_state: &mut ProgramState,
+) -> anyhow::Result<()> {
+    let Some(browser) = &mut program_state.history_browser else {
+        return Ok(());
+    };
+    if let Event::Key(e) = event {
+        match e.code {
+            KeyCode::Enter => {
+                let revision = browser.selected_revision();
+                program_state.history_browser = None;
+                program_state.canvas.go_to_revision(revision);
+                program_state.cursor_position = program_state
+                    .canvas
+                    .clamp_to_page(program_state.cursor_position);
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                program_state.history_browser = None;
+            }
+            _ => browser.input(event),
+        }
+    }
+    Ok(())
+}
+
 /// Handles user input
 ///
 /// Returns a tuple of booleans `(redraw, exit)`.
@@ -194,6 +228,13 @@ pub fn handle_user_input(mut event: Event, program_state: &mut ProgramState) ->
         }
     }
 
+    // Accept any keystroke to stop the playback of the frames
+    if let Event::Key(_e) = event {
+        if program_state.playback.take().is_some() {
+            return Ok(());
+        }
+    }
+
     // Debugging
     if let Event::Key(e) = event {
         log::debug!(
@@ -220,8 +261,21 @@ pub fn handle_user_input(mut event: Event, program_state: &mut ProgramState) ->
         }
     }
 
-    program_state
+    if program_state.history_browser.is_some() {
+        return handle_user_input_history_browser(event, program_state);
+    }
+
+    let latest_revision = program_state.canvas.latest_revision();
+    let result = program_state
         .input_mode
         .clone()
-        .handle_input(event, program_state)
+        .handle_input(event, program_state);
+    merge_commits(program_state, latest_revision);
+    if program_state.canvas.take_locked_layer_modified() {
+        let name = &program_state.canvas.active().properties.name;
+        program_state
+            .new_messages
+            .push_back(format!("Layer {name:?} is locked"));
+    }
+    result
 }
@@ -119,7 +119,7 @@ pub fn test() {
                     rows: 0,
                     columns: 1,
                 },
-                ch: 'x',
+                ch: 'x'.into(),
             }),
         }),
         "c" => ActionBatch(vec![ActionEnum::Operation(actions::Operation {